@@ -5,10 +5,9 @@
 //!
 //! Run with: cargo run --example terminal_notification
 
-use kodegen_native_notify::{
-    ImageData, ImagePlacement, MediaAttachment, NotificationBuilder, NotificationManager, Platform,
-    RichText, Url,
-};
+use std::time::Duration;
+
+use kodegen_native_notify::{CommandNotification, CommandTheme, NotificationManager, Platform, Url};
 
 /// KODEGEN logo URL for branding in notifications
 const LOGO_URL: &str = "https://kodegen.ai/assets/icon_128x128@2x.png";
@@ -50,67 +49,38 @@ async fn main() -> anyhow::Result<()> {
     Finished `release` profile [optimized] target(s) in 45.23s"#;
 
     // ========================================
-    // BUILD NOTIFICATION (exact same as hooks/notify.rs)
+    // BUILD NOTIFICATION
     // ========================================
-    let cmd_short = truncate(command, 40);
-    let duration = format_duration(duration_ms);
-    let output_preview = truncate_output(terminal_output, 20);
-
-    let (icon, status) = match (exit_code, completed) {
-        (Some(0), true) => ("✓", "success".to_string()),
-        (Some(code), true) => ("✗", format!("exit {}", code)),
-        (None, false) => ("⏳", "running".to_string()),
-        _ => ("•", "unknown".to_string()),
+    let theme = CommandTheme {
+        logo_url: Url::parse(LOGO_URL).ok(),
+        ..CommandTheme::default()
     };
-
-    let title = format!("{} terminal {}: {}", icon, terminal_id, cmd_short);
-    let transcript_link = format_transcript_link(transcript_path);
-
-    let body_html = format!(
-        r#"<div style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Ubuntu, sans-serif;">
-            <img src="{}" width="24" height="24" alt="KODEGEN"/>
-            <p><strong>{}</strong> in {}</p>
-            <p><em>cwd:</em> {}</p>
-            <pre style="background:#1e1e1e;color:#d4d4d4;padding:8px;border-radius:4px;font-size:12px;white-space:pre-wrap;word-wrap:break-word;">{}</pre>
-            <p>{}</p>
-        </div>"#,
-        LOGO_URL,
-        status,
-        duration,
-        html_escape(cwd),
-        html_escape(&output_preview),
-        transcript_link
-    );
+    let mut command_notification = CommandNotification::new(command)
+        .with_cwd(cwd)
+        .with_duration(Duration::from_millis(duration_ms))
+        .with_output(terminal_output)
+        .with_terminal_id(terminal_id)
+        .with_transcript_path(transcript_path)
+        .with_theme(theme)
+        .with_platforms(vec![Platform::MacOS, Platform::Windows, Platform::Linux]);
+    if let (Some(code), true) = (exit_code, completed) {
+        command_notification = command_notification.with_exit_code(code);
+    }
 
     println!("=== NOTIFICATION TITLE ===");
-    println!("{}", title);
+    println!("{}", command_notification.title());
     println!();
-    println!("=== NOTIFICATION BODY (HTML) ===");
-    println!("{}", body_html);
+    println!("=== OUTPUT PREVIEW ===");
+    println!("{}", command_notification.output_preview());
     println!();
 
     // ========================================
     // BUILD AND SEND NOTIFICATION
     // ========================================
-    let mut builder = NotificationBuilder::new()
-        .with_title(&title)
-        .with_body(RichText::html(&body_html))
-        .with_platforms(vec![Platform::MacOS, Platform::Windows, Platform::Linux]);
-
-    // Add KODEGEN logo as app icon
-    if let Ok(logo_url) = Url::parse(LOGO_URL) {
-        builder = builder.with_media(MediaAttachment::Image {
-            data: ImageData::Url(logo_url),
-            placement: ImagePlacement::AppIcon,
-            alt_text: Some("KODEGEN".to_string()),
-            dimensions: Some((128, 128)),
-        });
-    }
-
-    let notification = builder.build()?;
+    let notification = command_notification.build()?;
 
     println!("=== SENDING NOTIFICATION ===");
-    let manager = NotificationManager::new();
+    let manager = NotificationManager::try_new()?;
     let handle = manager.send(notification).await?;
 
     // Wait a moment for delivery
@@ -129,51 +99,3 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
-
-/// HTML escape special characters
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-}
-
-/// Truncate string to max length with ellipsis
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max.saturating_sub(3)])
-    }
-}
-
-/// Format duration in human-readable form
-fn format_duration(ms: u64) -> String {
-    if ms >= 60000 {
-        format!("{}m {}s", ms / 60000, (ms % 60000) / 1000)
-    } else if ms >= 1000 {
-        format!("{:.1}s", ms as f64 / 1000.0)
-    } else {
-        format!("{}ms", ms)
-    }
-}
-
-/// Format transcript path as clickable file:// hyperlink
-fn format_transcript_link(path: &str) -> String {
-    format!(r#"<a href="file://{}">View Transcript</a>"#, path)
-}
-
-/// Truncate output to last N lines if more than max_lines
-fn truncate_output(output: &str, max_lines: usize) -> String {
-    let trimmed = output.trim();
-    let lines: Vec<&str> = trimmed.lines().collect();
-
-    if lines.len() <= max_lines {
-        return trimmed.to_string();
-    }
-
-    // Take last max_lines
-    let start = lines.len() - max_lines;
-    let last_lines = &lines[start..];
-    format!("...({} lines hidden)\n{}", start, last_lines.join("\n"))
-}
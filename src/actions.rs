@@ -0,0 +1,317 @@
+//! Executing notification actions
+//!
+//! Every [`NotificationManager`] owns an [`ActionHandlers`] registry. When the
+//! user invokes an action, the manager runs the handler registered for its
+//! [`ActionId`], else the one for the notification's category, else a built-in:
+//!
+//! - `Protocol` actions with a `url` open it with the default handler
+//! - [`OPEN_FILE_ACTION`] and [`REVEAL_FILE_ACTION`] open or reveal the
+//!   notification's [`MediaAttachment::File`] (or the payload's `path`)
+//!
+//! Actions with an [`ActionConfirmation`](crate::ActionConfirmation) run only
+//! after the user confirms them (see [`crate::confirmation`]).
+//!
+//! Text replies run the action whose ID matches the reply's input ID (or
+//! [`REPLY_ACTION`]), with the text in [`ActionContext::inputs`].
+//!
+//! An action whose payload has a `callback_url` additionally sends a signed,
+//! retried webhook there (see [`crate::webhook`]). Handlers run with a timeout
+//! and their result is recorded as the interaction's [`InteractionOutcome`].
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
+
+use crate::{
+    ActionId, ActionStyle, ActivationType, CorrelationId, InteractionOutcome, MediaAttachment,
+    NotificationAction, NotificationError, NotificationId, NotificationResult, Platform, TraceSpan,
+    Url, WebhookEnvelope, Webhooks, open_path, open_url, reveal_path,
+};
+
+/// Action ID that opens the notification's file attachment
+pub const OPEN_FILE_ACTION: &str = "file.open";
+/// Action ID that shows the notification's file attachment in the file manager
+pub const REVEAL_FILE_ACTION: &str = "file.reveal";
+/// Action ID handlers see for replies that don't name their input
+pub const REPLY_ACTION: &str = "reply";
+/// Payload key overriding which file the file actions use
+pub const FILE_PATH_KEY: &str = "path";
+
+/// Default time a handler may run
+const DEFAULT_HANDLER_TIMEOUT: Duration = Duration::from_secs(10);
+/// Default time a confirmation waits for an answer
+const DEFAULT_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Action invoked by the user, as passed to handlers
+#[derive(Debug, Clone)]
+pub struct ActionContext {
+    pub notification_id: NotificationId,
+    pub correlation_id: CorrelationId,
+    pub trace_span: Option<TraceSpan>,
+    pub platform: Platform,
+    /// The action as defined on the notification; `None` for actions the
+    /// notification doesn't define, such as a platform's default action
+    pub action: Option<NotificationAction>,
+    pub action_id: ActionId,
+    /// Identifier of the notification's category
+    pub category: Option<String>,
+    /// File attachments of the notification
+    pub files: Vec<PathBuf>,
+    /// Input values submitted with the action, by input ID
+    pub inputs: HashMap<String, String>,
+}
+
+impl ActionContext {
+    /// Payload data of the action
+    pub fn data(&self) -> Option<&HashMap<String, String>> {
+        self.action
+            .as_ref()
+            .and_then(|action| action.payload.as_ref())
+            .map(|payload| &payload.data)
+    }
+
+    /// File the file actions use: the payload's `path`, else the first attachment
+    pub fn file(&self) -> Option<PathBuf> {
+        self.data()
+            .and_then(|data| data.get(FILE_PATH_KEY))
+            .map(PathBuf::from)
+            .or_else(|| self.files.first().cloned())
+    }
+}
+
+type HandlerFuture = Pin<Box<dyn Future<Output = NotificationResult<()>> + Send>>;
+type Handler = Arc<dyn Fn(ActionContext) -> HandlerFuture + Send + Sync>;
+
+/// Registry of action handlers
+pub struct ActionHandlers {
+    by_action: DashMap<ActionId, Handler>,
+    by_category: DashMap<String, Handler>,
+    timeout: parking_lot::RwLock<Duration>,
+    confirmation_timeout: parking_lot::RwLock<Duration>,
+    webhooks: Webhooks,
+}
+
+impl Default for ActionHandlers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ActionHandlers {
+    pub fn new() -> Self {
+        Self {
+            by_action: DashMap::new(),
+            by_category: DashMap::new(),
+            timeout: parking_lot::RwLock::new(DEFAULT_HANDLER_TIMEOUT),
+            confirmation_timeout: parking_lot::RwLock::new(DEFAULT_CONFIRMATION_TIMEOUT),
+            webhooks: Webhooks::new(),
+        }
+    }
+
+    /// Run `handler` when an action with this ID is invoked
+    ///
+    /// Replaces an earlier handler for the same ID, including built-in behavior.
+    pub fn register<F, Fut>(&self, action_id: impl Into<String>, handler: F)
+    where
+        F: Fn(ActionContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = NotificationResult<()>> + Send + 'static,
+    {
+        self.by_action
+            .insert(ActionId::new(action_id), boxed(handler));
+    }
+
+    /// Run `handler` for actions of notifications in this category without their own handler
+    pub fn register_category<F, Fut>(&self, category: impl Into<String>, handler: F)
+    where
+        F: Fn(ActionContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = NotificationResult<()>> + Send + 'static,
+    {
+        self.by_category.insert(category.into(), boxed(handler));
+    }
+
+    pub fn unregister(&self, action_id: &ActionId) {
+        self.by_action.remove(action_id);
+    }
+
+    pub fn unregister_category(&self, category: &str) {
+        self.by_category.remove(category);
+    }
+
+    /// Time a handler may run before the action counts as failed
+    pub fn timeout(&self) -> Duration {
+        *self.timeout.read()
+    }
+
+    pub fn set_timeout(&self, timeout: Duration) {
+        *self.timeout.write() = timeout;
+    }
+
+    /// Time a confirmation waits for an answer before the action counts as cancelled
+    pub fn confirmation_timeout(&self) -> Duration {
+        *self.confirmation_timeout.read()
+    }
+
+    pub fn set_confirmation_timeout(&self, timeout: Duration) {
+        *self.confirmation_timeout.write() = timeout;
+    }
+
+    /// Webhook settings and dead letters for action callbacks
+    pub fn webhooks(&self) -> &Webhooks {
+        &self.webhooks
+    }
+
+    /// Whether invoking this action runs anything
+    pub fn handles(&self, context: &ActionContext) -> bool {
+        self.resolve(context).is_some() || callback_url(context).is_some()
+    }
+
+    /// Run the action's handler and callback, mapping the result to an outcome
+    ///
+    /// Returns `None` when nothing handles the action.
+    pub async fn dispatch(&self, context: ActionContext) -> Option<InteractionOutcome> {
+        let handler = self.resolve(&context);
+        let callback_url = callback_url(&context).cloned();
+        if handler.is_none() && callback_url.is_none() {
+            return None;
+        }
+
+        let timeout = self.timeout();
+        let handled = match handler {
+            Some(handler) => match tokio::time::timeout(timeout, handler(context.clone())).await {
+                Ok(result) => result,
+                Err(_) => Err(NotificationError::TimeoutError {
+                    operation: format!("action {}", context.action_id),
+                    timeout_duration: timeout,
+                }),
+            },
+            None => Ok(()),
+        };
+        let called_back = match &callback_url {
+            Some(url) => {
+                let mut envelope = WebhookEnvelope::new(
+                    context.notification_id,
+                    &context.correlation_id,
+                    context.action_id.as_str(),
+                );
+                envelope.inputs = context.inputs.clone();
+                envelope.data = context.data().cloned().unwrap_or_default();
+                self.webhooks
+                    .deliver(url, envelope, context.trace_span.as_ref())
+                    .await
+            }
+            None => Ok(()),
+        };
+
+        let outcome = match (handled, called_back) {
+            (Ok(()), Ok(())) => InteractionOutcome::Success,
+            // Already logged and dead-lettered by the webhook sender
+            (Ok(()), Err(_)) => InteractionOutcome::Partial,
+            (Err(e), _) => {
+                ::tracing::warn!("Handler for action {} failed: {}", context.action_id, e);
+                InteractionOutcome::Failed { reason: e.to_string() }
+            }
+        };
+        Some(outcome)
+    }
+
+    /// Registered handler for the action or its category, else a built-in
+    fn resolve(&self, context: &ActionContext) -> Option<Handler> {
+        if let Some(handler) = self.by_action.get(&context.action_id) {
+            return Some(Arc::clone(handler.value()));
+        }
+        if let Some(handler) = context
+            .category
+            .as_ref()
+            .and_then(|category| self.by_category.get(category))
+        {
+            return Some(Arc::clone(handler.value()));
+        }
+        builtin_handler(context)
+    }
+}
+
+fn boxed<F, Fut>(handler: F) -> Handler
+where
+    F: Fn(ActionContext) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = NotificationResult<()>> + Send + 'static,
+{
+    Arc::new(move |context| Box::pin(handler(context)) as HandlerFuture)
+}
+
+fn builtin_handler(context: &ActionContext) -> Option<Handler> {
+    match context.action_id.as_str() {
+        OPEN_FILE_ACTION => Some(boxed(|context: ActionContext| async move {
+            open_path(&attached_file(&context)?).await
+        })),
+        REVEAL_FILE_ACTION => Some(boxed(|context: ActionContext| async move {
+            reveal_path(&attached_file(&context)?).await
+        })),
+        _ => {
+            let action = context.action.as_ref()?;
+            if action.activation_type != ActivationType::Protocol {
+                return None;
+            }
+            let url = action.url.clone()?;
+            Some(boxed(move |_| {
+                let url = url.clone();
+                async move { open_url(&url).await }
+            }))
+        }
+    }
+}
+
+fn attached_file(context: &ActionContext) -> NotificationResult<PathBuf> {
+    context.file().ok_or_else(|| NotificationError::ValidationError {
+        field: "media".to_string(),
+        message: format!("Action {} needs a file attachment", context.action_id),
+    })
+}
+
+fn callback_url(context: &ActionContext) -> Option<&Url> {
+    context
+        .action
+        .as_ref()?
+        .payload
+        .as_ref()?
+        .callback_url
+        .as_ref()
+}
+
+/// Action that opens the notification's file attachment
+pub fn open_file_action(label: impl Into<String>) -> NotificationAction {
+    file_action(OPEN_FILE_ACTION, label.into())
+}
+
+/// Action that shows the notification's file attachment in the file manager
+pub fn reveal_file_action(label: impl Into<String>) -> NotificationAction {
+    file_action(REVEAL_FILE_ACTION, label.into())
+}
+
+fn file_action(id: &str, label: String) -> NotificationAction {
+    NotificationAction {
+        id: ActionId::new(id),
+        label,
+        icon: None,
+        style: ActionStyle::Default,
+        activation_type: ActivationType::Background,
+        url: None,
+        payload: None,
+        confirmation: None,
+    }
+}
+
+/// File paths of a notification's attachments
+pub(crate) fn attached_files(media: &[MediaAttachment]) -> Vec<PathBuf> {
+    media
+        .iter()
+        .filter_map(|attachment| match attachment {
+            MediaAttachment::File { path, .. } => Some(path.clone()),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,328 @@
+//! Asking the user a question through a notification
+//!
+//! [`NotificationManager::ask`] shows a notification whose actions are the
+//! answer choices, waits for the matching interaction and withdraws the
+//! notification once it is answered, times out or is answered elsewhere.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::components::lifecycle::{NotificationState as LifecycleState, TransitionReason};
+use crate::{
+    ActionId, ActionStyle, ActivationType, InputId, NotificationAction, NotificationBuilder,
+    NotificationBuildError, NotificationError, NotificationId, NotificationInput,
+    NotificationInteraction, NotificationManager, NotificationResult, NotificationState, Platform,
+    PlatformInteractionKind, PlatformManager, Priority, RichText,
+};
+
+/// Input ID of the free-text reply field added by [`Question::with_text_reply`]
+pub const ASK_REPLY_INPUT: &str = "ask.reply";
+
+/// How often a pending question checks whether delivery failed
+const DELIVERY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Question shown as the notification title and body
+#[derive(Debug, Clone)]
+pub struct Question {
+    pub title: String,
+    pub body: Option<RichText>,
+    /// Placeholder of the free-text reply field, `None` for choices only
+    pub reply_placeholder: Option<String>,
+    pub priority: Priority,
+    pub platforms: Option<Vec<Platform>>,
+}
+
+impl Question {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: None,
+            reply_placeholder: None,
+            priority: Priority::High,
+            platforms: None,
+        }
+    }
+
+    pub fn with_body(mut self, body: impl Into<RichText>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Accept a free-text answer; replies come back as [`Answer::Text`]
+    pub fn with_text_reply(mut self, placeholder: impl Into<String>) -> Self {
+        self.reply_placeholder = Some(placeholder.into());
+        self
+    }
+
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_platforms(mut self, platforms: Vec<Platform>) -> Self {
+        self.platforms = Some(platforms);
+        self
+    }
+}
+
+impl From<&str> for Question {
+    fn from(title: &str) -> Self {
+        Self::new(title)
+    }
+}
+
+impl From<String> for Question {
+    fn from(title: String) -> Self {
+        Self::new(title)
+    }
+}
+
+/// One possible answer, shown as a notification action
+#[derive(Debug, Clone)]
+pub struct Choice {
+    pub id: ActionId,
+    pub label: String,
+    pub style: ActionStyle,
+    /// Picking this choice answers [`Answer::Denied`]
+    pub denies: bool,
+}
+
+impl Choice {
+    pub fn new(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            id: ActionId::new(id),
+            label: label.into(),
+            style: ActionStyle::Default,
+            denies: false,
+        }
+    }
+
+    /// Shown as destructive where the platform styles actions
+    pub fn destructive(mut self) -> Self {
+        self.style = ActionStyle::Destructive;
+        self
+    }
+
+    /// Treat picking this choice as declining the question
+    pub fn deny(mut self) -> Self {
+        self.denies = true;
+        self
+    }
+
+    fn to_action(&self) -> NotificationAction {
+        NotificationAction {
+            id: self.id.clone(),
+            label: self.label.clone(),
+            icon: None,
+            style: self.style,
+            activation_type: ActivationType::Background,
+            url: None,
+            payload: None,
+            confirmation: None,
+        }
+    }
+}
+
+/// Outcome of [`NotificationManager::ask`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    /// A choice was picked
+    Choice(ActionId),
+    /// Free-text reply
+    Text(String),
+    /// A denying choice was picked, or the notification was dismissed (`None`)
+    Denied { choice: Option<ActionId> },
+    /// Nobody answered before the timeout
+    TimedOut,
+}
+
+impl NotificationManager {
+    /// Ask a question and wait for the answer
+    ///
+    /// The notification is withdrawn from every platform once an answer arrives,
+    /// on timeout, or when the returned future is dropped because the question
+    /// was answered through another channel. Delivery failures are returned as
+    /// errors; denial and timeout are regular [`Answer`]s.
+    pub async fn ask(
+        &self,
+        question: impl Into<Question>,
+        choices: Vec<Choice>,
+        timeout: Duration,
+    ) -> NotificationResult<Answer> {
+        let question = question.into();
+        let notification = build_question(&question, &choices).map_err(|e| match e {
+            NotificationBuildError::ValidationError(e) => e,
+            e => NotificationError::ValidationError {
+                field: "question".to_string(),
+                message: e.to_string(),
+            },
+        })?;
+
+        // Subscribe before sending so an immediate answer cannot be missed
+        let mut interactions = self.subscribe_interactions();
+        let handle = self.send(notification).await?;
+        let mut pending = PendingQuestion {
+            state: Arc::clone(&self.state),
+            platform_manager: Arc::clone(&self.platform_manager),
+            id: handle.id,
+            settled: false,
+        };
+
+        let wait = async {
+            let mut delivery_poll = tokio::time::interval(DELIVERY_POLL_INTERVAL);
+            loop {
+                tokio::select! {
+                    received = interactions.recv() => match received {
+                        Ok(event) if event.notification_id == handle.id => {
+                            if let Some(answer) = answer_for(event.kind, &choices) {
+                                return Ok(answer);
+                            }
+                        }
+                        Ok(_) | Err(RecvError::Lagged(_)) => {}
+                        Err(RecvError::Closed) => return Ok(Answer::TimedOut),
+                    },
+                    _ = delivery_poll.tick() => {
+                        if let Some(LifecycleState::Failed(details)) =
+                            self.state
+                                .get(&handle.id)
+                                .filter(|s| s.lifecycle.is_finished())
+                                .map(|s| s.lifecycle.state.clone())
+                        {
+                            return Err(NotificationError::DeliveryFailed {
+                                platform_errors: details.platform_errors,
+                                message: format!("Question could not be delivered: {}", details.message),
+                            });
+                        }
+                    }
+                }
+            }
+        };
+
+        let result = match tokio::time::timeout(timeout, wait).await {
+            Ok(result) => result,
+            Err(_) => Ok(Answer::TimedOut),
+        };
+
+        let (final_state, reason) = match &result {
+            Ok(Answer::TimedOut) => (LifecycleState::Expired, TransitionReason::Timeout),
+            Ok(_) => (LifecycleState::Completed, TransitionReason::UserInteraction),
+            // Failed notifications keep their failure state
+            Err(_) => {
+                pending.settled = true;
+                return result;
+            }
+        };
+        pending.settle(final_state, reason).await;
+        result
+    }
+}
+
+/// Notification showing the question with one action per choice
+fn build_question(
+    question: &Question,
+    choices: &[Choice],
+) -> Result<crate::Notification, NotificationBuildError> {
+    let mut builder = NotificationBuilder::new()
+        .with_title(question.title.clone())
+        .with_body(question.body.clone().unwrap_or_else(|| RichText::plain("")))
+        .with_priority(question.priority);
+    if let Some(platforms) = &question.platforms {
+        builder = builder.with_platforms(platforms.clone());
+    }
+    for choice in choices {
+        builder = builder.with_action(choice.to_action());
+    }
+
+    let mut notification = builder.build()?;
+    if let Some(placeholder) = &question.reply_placeholder {
+        notification.content = notification.content.with_interaction(NotificationInteraction::Input(
+            NotificationInput::Text {
+                id: InputId::new(ASK_REPLY_INPUT),
+                label: question.title.clone(),
+                placeholder: placeholder.clone(),
+                validation: None,
+                max_length: None,
+                multiline: false,
+            },
+        ));
+    }
+    Ok(notification)
+}
+
+/// Map an interaction to an answer; unknown actions are ignored
+fn answer_for(kind: PlatformInteractionKind, choices: &[Choice]) -> Option<Answer> {
+    match kind {
+        PlatformInteractionKind::Action(action_id) => {
+            let choice = choices.iter().find(|choice| choice.id == action_id)?;
+            Some(if choice.denies {
+                Answer::Denied { choice: Some(action_id) }
+            } else {
+                Answer::Choice(action_id)
+            })
+        }
+        PlatformInteractionKind::Reply { text, .. } => Some(Answer::Text(text)),
+        PlatformInteractionKind::Dismissed => Some(Answer::Denied { choice: None }),
+        // Nobody can answer any more, the timeout settles the question
+        PlatformInteractionKind::Withdrawn
+        | PlatformInteractionKind::Expired
+        | PlatformInteractionKind::ServerChanged => None,
+    }
+}
+
+/// Withdraws the question if `ask` is dropped before it settles
+struct PendingQuestion {
+    state: Arc<DashMap<NotificationId, NotificationState>>,
+    platform_manager: Arc<PlatformManager>,
+    id: NotificationId,
+    settled: bool,
+}
+
+impl PendingQuestion {
+    async fn settle(&mut self, final_state: LifecycleState, reason: TransitionReason) {
+        self.settled = true;
+        crate::withdraw_notification(&self.state, &self.platform_manager, self.id).await;
+        finish(&self.state, self.id, final_state, reason);
+    }
+}
+
+impl Drop for PendingQuestion {
+    fn drop(&mut self) {
+        if self.settled {
+            return;
+        }
+        // Answered through another channel: take the notification down in the background
+        let state = Arc::clone(&self.state);
+        let platform_manager = Arc::clone(&self.platform_manager);
+        let id = self.id;
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                crate::withdraw_notification(&state, &platform_manager, id).await;
+                finish(&state, id, LifecycleState::Completed, TransitionReason::Cancellation);
+            });
+        }
+    }
+}
+
+/// Move the question to its final lifecycle state if it has not reached one
+fn finish(
+    state: &DashMap<NotificationId, NotificationState>,
+    id: NotificationId,
+    final_state: LifecycleState,
+    reason: TransitionReason,
+) {
+    if let Some(mut entry) = state.get_mut(&id) {
+        let correlation_id = entry.identity.correlation_id.clone();
+        // Questions still queued or delivering cannot complete; they expire instead
+        let final_state = if entry.lifecycle.state.can_transition_to(&final_state) {
+            final_state
+        } else {
+            LifecycleState::Expired
+        };
+        let _ = entry
+            .lifecycle
+            .transition_to(final_state, reason, Some(correlation_id));
+    }
+}
@@ -0,0 +1,379 @@
+// Freedesktop icon theme lookup
+// Implements the XDG Icon Theme Specification lookup algorithm (theme inheritance,
+// size and scale matching, hicolor and pixmaps fallback) so named system icons can
+// be handed to backends that need a concrete file
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+use dashmap::DashMap;
+
+/// Theme every other theme implicitly falls back to
+pub const FALLBACK_THEME: &str = "hicolor";
+
+/// Icon size requested when a backend has no placement-specific size
+pub const DEFAULT_ICON_SIZE: u32 = 48;
+
+/// Icon file extensions in lookup order
+const ICON_EXTENSIONS: &[&str] = &["png", "svg", "xpm"];
+
+/// Lookup used by `lookup_icon` when callers don't supply their own
+static DEFAULT_ICON_LOOKUP: LazyLock<parking_lot::RwLock<IconLookup>> =
+    LazyLock::new(|| parking_lot::RwLock::new(IconLookup::new()));
+
+/// Get the process-wide icon lookup
+pub fn icon_lookup() -> IconLookup {
+    DEFAULT_ICON_LOOKUP.read().clone()
+}
+
+/// Replace the process-wide icon lookup, e.g. to pin a theme or add search paths
+pub fn set_icon_lookup(lookup: IconLookup) {
+    *DEFAULT_ICON_LOOKUP.write() = lookup;
+}
+
+/// Resolve an icon name with the process-wide lookup
+pub fn lookup_icon(name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+    icon_lookup().lookup(name, size, scale)
+}
+
+/// How a theme subdirectory matches requested sizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirectoryType {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+/// One `[subdir]` group from `index.theme`
+#[derive(Debug, Clone)]
+struct IconDirectory {
+    path: String,
+    size: u32,
+    scale: u32,
+    kind: DirectoryType,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+impl IconDirectory {
+    fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+        match self.kind {
+            DirectoryType::Fixed => self.size == size,
+            DirectoryType::Scalable => (self.min_size..=self.max_size).contains(&size),
+            DirectoryType::Threshold => {
+                (self.size.saturating_sub(self.threshold)..=self.size + self.threshold)
+                    .contains(&size)
+            }
+        }
+    }
+
+    fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let wanted = size * scale;
+        let (min, max) = match self.kind {
+            DirectoryType::Fixed => (self.size, self.size),
+            DirectoryType::Scalable => (self.min_size, self.max_size),
+            DirectoryType::Threshold => (
+                self.size.saturating_sub(self.threshold),
+                self.size + self.threshold,
+            ),
+        };
+        let (min, max) = (min * self.scale, max * self.scale);
+        if wanted < min {
+            min - wanted
+        } else {
+            wanted.saturating_sub(max)
+        }
+    }
+}
+
+/// A parsed icon theme and the base directories it was found in
+#[derive(Debug)]
+struct IconTheme {
+    base_dirs: Vec<PathBuf>,
+    inherits: Vec<String>,
+    directories: Vec<IconDirectory>,
+}
+
+impl IconTheme {
+    /// Load `name` from the first search path holding its `index.theme`
+    fn load(name: &str, search_paths: &[PathBuf]) -> Option<Self> {
+        let base_dirs: Vec<PathBuf> = search_paths
+            .iter()
+            .map(|path| path.join(name))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        let index = base_dirs
+            .iter()
+            .find_map(|dir| std::fs::read_to_string(dir.join("index.theme")).ok())?;
+        let groups = parse_desktop_groups(&index);
+        let theme = groups.get("Icon Theme")?;
+
+        let list = |key: &str| -> Vec<String> {
+            theme
+                .get(key)
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut seen = HashSet::new();
+        let directories = list("Directories")
+            .into_iter()
+            .chain(list("ScaledDirectories"))
+            .filter(|path| seen.insert(path.clone()))
+            .filter_map(|path| {
+                let group = groups.get(&path)?;
+                let number = |key: &str| group.get(key).and_then(|value| value.trim().parse::<u32>().ok());
+                let size = number("Size")?;
+                let kind = match group.get("Type").map(|value| value.trim()) {
+                    Some("Fixed") => DirectoryType::Fixed,
+                    Some("Scalable") => DirectoryType::Scalable,
+                    _ => DirectoryType::Threshold,
+                };
+                Some(IconDirectory {
+                    size,
+                    scale: number("Scale").unwrap_or(1).max(1),
+                    kind,
+                    min_size: number("MinSize").unwrap_or(size),
+                    max_size: number("MaxSize").unwrap_or(size),
+                    threshold: number("Threshold").unwrap_or(2),
+                    path,
+                })
+            })
+            .collect();
+
+        Some(Self {
+            base_dirs,
+            inherits: list("Inherits"),
+            directories,
+        })
+    }
+
+    /// Exact size match first, otherwise the closest size in this theme
+    fn lookup(&self, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        let candidates = || {
+            self.directories.iter().flat_map(move |directory| {
+                self.base_dirs.iter().flat_map(move |base| {
+                    ICON_EXTENSIONS.iter().map(move |ext| {
+                        (directory, base.join(&directory.path).join(format!("{}.{}", name, ext)))
+                    })
+                })
+            })
+        };
+
+        candidates()
+            .filter(|(directory, _)| directory.matches_size(size, scale))
+            .map(|(_, path)| path)
+            .find(|path| path.is_file())
+            .or_else(|| {
+                candidates()
+                    .filter(|(_, path)| path.is_file())
+                    .min_by_key(|(directory, _)| directory.size_distance(size, scale))
+                    .map(|(_, path)| path)
+            })
+    }
+}
+
+/// Icon theme lookup over a set of base directories
+///
+/// Clones share the parsed-theme cache.
+#[derive(Debug, Clone)]
+pub struct IconLookup {
+    search_paths: Vec<PathBuf>,
+    theme: String,
+    themes: Arc<DashMap<String, Option<Arc<IconTheme>>>>,
+}
+
+impl Default for IconLookup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IconLookup {
+    /// Lookup over the XDG base directories using the desktop's configured theme
+    pub fn new() -> Self {
+        Self {
+            search_paths: default_search_paths(),
+            theme: configured_theme().unwrap_or_else(|| FALLBACK_THEME.to_string()),
+            themes: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Search only `paths` (each holding `<theme>/index.theme` directories and loose icons)
+    pub fn with_search_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.search_paths = paths;
+        self.themes = Arc::new(DashMap::new());
+        self
+    }
+
+    /// Use `theme` instead of the desktop's configured theme
+    pub fn with_theme(mut self, theme: impl Into<String>) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    pub fn search_paths(&self) -> &[PathBuf] {
+        &self.search_paths
+    }
+
+    /// Resolve `name` to an icon file for `size` at `scale`
+    ///
+    /// Searches the selected theme and its parents, then hicolor, then loose
+    /// icons directly in the search paths. Absolute paths are returned as-is
+    /// when they exist.
+    pub fn lookup(&self, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        let as_path = Path::new(name);
+        if as_path.is_absolute() {
+            return as_path.is_file().then(|| as_path.to_path_buf());
+        }
+        if name.is_empty() || name.contains('/') {
+            return None;
+        }
+
+        let scale = scale.max(1);
+        let mut visited = HashSet::new();
+        self.lookup_in_theme(&self.theme, name, size, scale, &mut visited)
+            .or_else(|| self.lookup_in_theme(FALLBACK_THEME, name, size, scale, &mut visited))
+            .or_else(|| self.lookup_fallback(name))
+    }
+
+    fn lookup_in_theme(
+        &self,
+        theme_name: &str,
+        name: &str,
+        size: u32,
+        scale: u32,
+        visited: &mut HashSet<String>,
+    ) -> Option<PathBuf> {
+        if !visited.insert(theme_name.to_string()) {
+            return None;
+        }
+        let theme = self.load_theme(theme_name)?;
+        if let Some(path) = theme.lookup(name, size, scale) {
+            return Some(path);
+        }
+        theme
+            .inherits
+            .iter()
+            .find_map(|parent| self.lookup_in_theme(parent, name, size, scale, visited))
+    }
+
+    fn lookup_fallback(&self, name: &str) -> Option<PathBuf> {
+        self.search_paths.iter().find_map(|dir| {
+            ICON_EXTENSIONS
+                .iter()
+                .map(|ext| dir.join(format!("{}.{}", name, ext)))
+                .find(|path| path.is_file())
+        })
+    }
+
+    fn load_theme(&self, name: &str) -> Option<Arc<IconTheme>> {
+        if let Some(theme) = self.themes.get(name) {
+            return theme.clone();
+        }
+        let theme = IconTheme::load(name, &self.search_paths).map(Arc::new);
+        self.themes.insert(name.to_string(), theme.clone());
+        theme
+    }
+}
+
+/// `$HOME/.icons`, `$XDG_DATA_HOME/icons`, `$XDG_DATA_DIRS/icons` and `/usr/share/pixmaps`
+fn default_search_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(home) = std::env::var_os("HOME") {
+        paths.push(PathBuf::from(home).join(".icons"));
+    }
+    paths.extend(xdg_data_dirs().into_iter().map(|dir| dir.join("icons")));
+    paths.push(PathBuf::from("/usr/share/pixmaps"));
+    paths
+}
+
+/// Icon theme named in the user's GTK settings, if any
+fn configured_theme() -> Option<String> {
+    gtk_setting("gtk-icon-theme-name")
+}
+
+/// `$XDG_DATA_HOME` followed by `$XDG_DATA_DIRS`, with the spec's defaults
+pub(super) fn xdg_data_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+    dirs.extend(data_home);
+
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(
+        data_dirs
+            .split(':')
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute()),
+    );
+
+    dirs
+}
+
+/// Value of `key` in the user's GTK 4 or GTK 3 `settings.ini`
+pub(super) fn gtk_setting(key: &str) -> Option<String> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    ["gtk-4.0", "gtk-3.0"].iter().find_map(|dir| {
+        let settings = std::fs::read_to_string(config_home.join(dir).join("settings.ini")).ok()?;
+        parse_desktop_groups(&settings)
+            .get("Settings")?
+            .get(key)
+            .map(|value| value.trim().trim_matches('"').to_string())
+            .filter(|value| !value.is_empty())
+    })
+}
+
+/// Parse a desktop-entry style key file into `group -> key -> value`
+pub(super) fn parse_desktop_groups(contents: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut groups: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(group) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            current = Some(group.to_string());
+            groups.entry(group.to_string()).or_default();
+            continue;
+        }
+        if let Some(group) = &current
+            && let Some((key, value)) = line.split_once('=')
+        {
+            groups
+                .entry(group.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    groups
+}
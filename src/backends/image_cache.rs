@@ -0,0 +1,361 @@
+// Persistent, content-addressed cache for remote notification images
+// Lives under $XDG_CACHE_HOME so downloads survive restarts, revalidates with
+// ETag/Last-Modified, evicts least recently used entries to a byte budget, and
+// serializes access between processes with an advisory file lock
+
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::OnceCell;
+use url::Url;
+
+use super::image_utils::{FetchOutcome, FetchedImage, ImageFetchPolicy, fetch_image, image_fetch_policy};
+use crate::components::{ErrorKind, NotificationError};
+
+/// Default total size of cached image blobs (64MB)
+pub const DEFAULT_CACHE_BUDGET: u64 = 64 * 1024 * 1024;
+
+/// Default age after which a cached image is revalidated with the server
+pub const DEFAULT_REVALIDATE_AFTER: Duration = Duration::from_secs(3600);
+
+/// Process-wide disk cache used by `download_image`, `None` if no cache directory is usable
+static DISK_CACHE: OnceCell<Option<DiskImageCache>> = OnceCell::const_new();
+
+/// Get the process-wide disk cache, creating it at the default location on first use
+pub async fn disk_image_cache() -> Option<&'static DiskImageCache> {
+    DISK_CACHE
+        .get_or_init(|| async {
+            let root = DiskImageCache::default_root()?;
+            match fs::create_dir_all(&root) {
+                Ok(()) => Some(DiskImageCache::new(root, DEFAULT_CACHE_BUDGET)),
+                Err(e) => {
+                    tracing::warn!("Image cache directory {:?} unavailable: {}", root, e);
+                    None
+                }
+            }
+        })
+        .await
+        .as_ref()
+}
+
+/// Configure the process-wide disk cache before first use
+///
+/// Passing `None` disables the disk cache so images fall back to temp files.
+/// Returns `false` if the cache was already initialized.
+pub fn set_disk_image_cache(cache: Option<DiskImageCache>) -> bool {
+    DISK_CACHE.set(cache).is_ok()
+}
+
+/// Index record for one cached URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    content_hash: String,
+    extension: String,
+    size: u64,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Unix seconds when the content was last fetched or revalidated
+    validated_at: u64,
+    /// Unix seconds when the entry was last used, for LRU eviction
+    last_access: u64,
+}
+
+/// On-disk image cache
+///
+/// Layout under `root`: `index/<sha256(url)>.json` entries pointing at
+/// `blobs/<sha256(content)>.<ext>`, so URLs serving identical bytes share a blob.
+#[derive(Debug, Clone)]
+pub struct DiskImageCache {
+    root: PathBuf,
+    max_bytes: u64,
+    revalidate_after: Duration,
+    fetch_policy: Option<ImageFetchPolicy>,
+}
+
+impl DiskImageCache {
+    pub fn new(root: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            root: root.into(),
+            max_bytes,
+            revalidate_after: DEFAULT_REVALIDATE_AFTER,
+            fetch_policy: None,
+        }
+    }
+
+    pub fn with_revalidate_after(mut self, revalidate_after: Duration) -> Self {
+        self.revalidate_after = revalidate_after;
+        self
+    }
+
+    /// Fetch with `policy` instead of the process-wide default
+    pub fn with_fetch_policy(mut self, policy: ImageFetchPolicy) -> Self {
+        self.fetch_policy = Some(policy);
+        self
+    }
+
+    /// `$XDG_CACHE_HOME/kodegen-notify/images`, or the platform's cache directory
+    pub fn default_root() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| {
+                if cfg!(target_os = "windows") {
+                    std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+                } else if cfg!(target_os = "macos") {
+                    std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Caches"))
+                } else {
+                    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache"))
+                }
+            })?;
+        Some(base.join("kodegen-notify").join("images"))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolve `url` to a cached file, downloading or revalidating as needed
+    pub async fn fetch(&self, url: &Url) -> Result<PathBuf, NotificationError> {
+        let url_key = sha256_hex(url.as_str().as_bytes());
+
+        let cache = self.clone();
+        let key = url_key.clone();
+        let cached = self
+            .blocking(url, move || cache.locked(|| cache.touch_entry(&key)))
+            .await?;
+
+        if let Some(entry) = &cached
+            && unix_now().saturating_sub(entry.validated_at) < self.revalidate_after.as_secs()
+        {
+            return Ok(self.blob_path(entry));
+        }
+
+        // Network I/O happens without holding the lock
+        let policy = self.fetch_policy.clone().unwrap_or_else(image_fetch_policy);
+        let outcome = match &cached {
+            Some(entry) => {
+                fetch_image(url, entry.etag.as_deref(), entry.last_modified.as_deref(), &policy)
+                    .await?
+            }
+            None => fetch_image(url, None, None, &policy).await?,
+        };
+
+        let cache = self.clone();
+        let url_string = url.to_string();
+        self.blocking(url, move || {
+            cache.locked(|| match (outcome, cached) {
+                (FetchOutcome::NotModified, Some(mut entry)) => {
+                    entry.validated_at = unix_now();
+                    cache.write_entry(&url_key, &entry)?;
+                    Ok(cache.blob_path(&entry))
+                }
+                (FetchOutcome::NotModified, None) => Err(io::Error::other(
+                    "server returned 304 for an unconditional request",
+                )),
+                (FetchOutcome::Fetched(fetched), _) => cache.store(&url_key, url_string, fetched),
+            })
+        })
+        .await
+    }
+
+    /// Total size of all blobs currently referenced by the index
+    pub fn total_size(&self) -> io::Result<u64> {
+        let entries = self.read_entries()?;
+        let mut seen = HashSet::new();
+        Ok(entries
+            .iter()
+            .filter(|(_, entry)| seen.insert(entry.content_hash.clone()))
+            .map(|(_, entry)| entry.size)
+            .sum())
+    }
+
+    /// Run blocking cache work off the async workers, mapping I/O errors for `url`
+    async fn blocking<T: Send + 'static>(
+        &self,
+        url: &Url,
+        work: impl FnOnce() -> io::Result<T> + Send + 'static,
+    ) -> Result<T, NotificationError> {
+        let resource_error = |kind: ErrorKind, message: String, source: crate::components::ErrorSource| {
+            NotificationError::ResourceError {
+                resource_type: "image_cache".to_string(),
+                resource_id: url.to_string(),
+                kind,
+                message,
+                source: Some(source),
+            }
+        };
+
+        tokio::task::spawn_blocking(work)
+            .await
+            .map_err(|e| resource_error(ErrorKind::Other, format!("Image cache task failed: {}", e), e.into()))?
+            .map_err(|e| {
+                resource_error(super::io_error_kind(&e), format!("Image cache I/O failed: {}", e), e.into())
+            })
+    }
+
+    /// Hold the cross-process lock while running `work`
+    fn locked<T>(&self, work: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+        fs::create_dir_all(self.index_dir())?;
+        fs::create_dir_all(self.blobs_dir())?;
+
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.root.join(".lock"))?;
+        lock_file.lock()?;
+        let result = work();
+        lock_file.unlock()?;
+        result
+    }
+
+    /// Load an entry whose blob still exists and record the access
+    fn touch_entry(&self, url_key: &str) -> io::Result<Option<CacheEntry>> {
+        let Some(mut entry) = self.read_entry(url_key)? else {
+            return Ok(None);
+        };
+        if !self.blob_path(&entry).exists() {
+            let _ = fs::remove_file(self.entry_path(url_key));
+            return Ok(None);
+        }
+
+        entry.last_access = unix_now();
+        self.write_entry(url_key, &entry)?;
+        Ok(Some(entry))
+    }
+
+    /// Write fetched bytes as a content-addressed blob and index them under the URL
+    fn store(&self, url_key: &str, url: String, fetched: FetchedImage) -> io::Result<PathBuf> {
+        let now = unix_now();
+        let entry = CacheEntry {
+            url,
+            content_hash: sha256_hex(&fetched.bytes),
+            extension: fetched.extension,
+            size: fetched.bytes.len() as u64,
+            etag: fetched.etag,
+            last_modified: fetched.last_modified,
+            validated_at: now,
+            last_access: now,
+        };
+
+        let blob_path = self.blob_path(&entry);
+        if !blob_path.exists() {
+            write_atomic(&blob_path, &fetched.bytes)?;
+        }
+
+        let previous = self.read_entry(url_key)?;
+        self.write_entry(url_key, &entry)?;
+        if let Some(previous) = previous
+            && previous.content_hash != entry.content_hash
+        {
+            self.remove_unreferenced_blob(&previous)?;
+        }
+
+        self.evict_to_budget()?;
+        Ok(blob_path)
+    }
+
+    /// Drop least recently used entries until referenced blobs fit the budget
+    fn evict_to_budget(&self) -> io::Result<()> {
+        let mut entries = self.read_entries()?;
+        entries.sort_by_key(|(_, entry)| entry.last_access);
+
+        let mut total = self.total_size()?;
+        while total > self.max_bytes && entries.len() > 1 {
+            let (url_key, entry) = entries.remove(0);
+            fs::remove_file(self.entry_path(&url_key))?;
+
+            let still_referenced = entries
+                .iter()
+                .any(|(_, other)| other.content_hash == entry.content_hash);
+            if !still_referenced {
+                let _ = fs::remove_file(self.blob_path(&entry));
+                total = total.saturating_sub(entry.size);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn remove_unreferenced_blob(&self, entry: &CacheEntry) -> io::Result<()> {
+        let referenced = self
+            .read_entries()?
+            .iter()
+            .any(|(_, other)| other.content_hash == entry.content_hash);
+        if !referenced {
+            let _ = fs::remove_file(self.blob_path(entry));
+        }
+        Ok(())
+    }
+
+    fn read_entries(&self) -> io::Result<Vec<(String, CacheEntry)>> {
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(self.index_dir())? {
+            let path = dir_entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(url_key) = path.file_stem().and_then(|stem| stem.to_str())
+                && let Some(entry) = self.read_entry(url_key)?
+            {
+                entries.push((url_key.to_string(), entry));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Read an index entry, treating unreadable or corrupt entries as missing
+    fn read_entry(&self, url_key: &str) -> io::Result<Option<CacheEntry>> {
+        match fs::read(self.entry_path(url_key)) {
+            Ok(data) => Ok(serde_json::from_slice(&data).ok()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write_entry(&self, url_key: &str, entry: &CacheEntry) -> io::Result<()> {
+        let data = serde_json::to_vec(entry).map_err(io::Error::other)?;
+        write_atomic(&self.entry_path(url_key), &data)
+    }
+
+    fn index_dir(&self) -> PathBuf {
+        self.root.join("index")
+    }
+
+    fn blobs_dir(&self) -> PathBuf {
+        self.root.join("blobs")
+    }
+
+    fn entry_path(&self, url_key: &str) -> PathBuf {
+        self.index_dir().join(format!("{}.json", url_key))
+    }
+
+    fn blob_path(&self, entry: &CacheEntry) -> PathBuf {
+        self.blobs_dir()
+            .join(format!("{}.{}", entry.content_hash, entry.extension))
+    }
+}
+
+/// Write through a temp file in the same directory and rename into place
+fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let temp = tempfile::NamedTempFile::new_in(dir)?;
+    fs::write(temp.path(), data)?;
+    temp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
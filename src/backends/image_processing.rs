@@ -0,0 +1,424 @@
+// Image normalization for platform backends
+// Decodes, rasterizes, downscales and re-encodes notification images so they
+// fit each platform's accepted formats, placement dimensions and byte limits
+
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use dashmap::DashMap;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, RgbImage, RgbaImage};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+use tokio::sync::OnceCell;
+
+use super::image_utils::{ResolvedImage, resolve_image_to_path};
+use crate::components::{ErrorKind, ImageFormat, ImagePlacement, MediaAttachment, NotificationError, Platform};
+
+/// Smallest edge we will shrink an image to while trying to meet a byte limit
+const MIN_DIMENSION: u32 = 16;
+
+/// JPEG qualities tried in order when PNG output is too large
+const JPEG_QUALITIES: [u8; 4] = [85, 70, 55, 40];
+
+/// Maximum processed images kept before the oldest are evicted
+const MAX_PROCESSED_ENTRIES: usize = 100;
+
+/// Processed image file cached by content hash
+#[derive(Debug, Clone)]
+struct ProcessedEntry {
+    path: PathBuf,
+    cached_at: Instant,
+}
+
+/// Cache of processed images
+/// Key: hex SHA-256 of the source bytes and target, Value: processed temp file
+static PROCESSED_CACHE: OnceCell<Arc<DashMap<String, ProcessedEntry>>> = OnceCell::const_new();
+
+async fn get_processed_cache() -> Arc<DashMap<String, ProcessedEntry>> {
+    PROCESSED_CACHE
+        .get_or_init(|| async { Arc::new(DashMap::new()) })
+        .await
+        .clone()
+}
+
+/// Output constraints for an image on a specific platform and placement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageTarget {
+    /// Maximum width in pixels
+    pub max_width: u32,
+    /// Maximum height in pixels
+    pub max_height: u32,
+    /// Maximum encoded size in bytes
+    pub max_bytes: Option<usize>,
+    /// Formats the platform displays, in order of preference
+    pub accepted_formats: Vec<ImageFormat>,
+}
+
+impl ImageTarget {
+    /// Constraints for an image shown at `placement` on `platform`
+    pub fn for_placement(
+        platform: Platform,
+        placement: ImagePlacement,
+        max_bytes: Option<usize>,
+    ) -> Self {
+        let (max_width, max_height) = match (platform, placement) {
+            // Toast app logo and hero sizes at 200% scale
+            (Platform::Windows, ImagePlacement::AppIcon | ImagePlacement::Icon) => (256, 256),
+            (Platform::Windows, ImagePlacement::Hero) => (728, 364),
+            (Platform::Windows, _) => (728, 728),
+            // Notification daemons render icons small and images at most a few hundred pixels
+            (Platform::Linux, ImagePlacement::AppIcon | ImagePlacement::Icon) => (128, 128),
+            (Platform::Linux, _) => (512, 512),
+            (_, ImagePlacement::AppIcon | ImagePlacement::Icon) => (512, 512),
+            (_, _) => (1024, 1024),
+        };
+
+        let accepted_formats = match platform {
+            Platform::Linux => vec![ImageFormat::Png, ImageFormat::Jpeg],
+            _ => vec![ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::Gif],
+        };
+
+        Self {
+            max_width,
+            max_height,
+            max_bytes,
+            accepted_formats,
+        }
+    }
+
+    fn accepts(&self, format: ImageFormat) -> bool {
+        self.accepted_formats.contains(&format)
+    }
+
+    fn fits_bytes(&self, len: usize) -> bool {
+        self.max_bytes.is_none_or(|max| len <= max)
+    }
+
+    /// Stable key describing this target, mixed into the cache hash
+    fn cache_key(&self) -> String {
+        format!(
+            "{}x{}:{:?}:{:?}",
+            self.max_width, self.max_height, self.max_bytes, self.accepted_formats
+        )
+    }
+}
+
+/// Image re-encoded to satisfy an [`ImageTarget`]
+#[derive(Debug, Clone)]
+pub struct ProcessedImage {
+    pub data: Vec<u8>,
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ProcessedImage {
+    fn extension(&self) -> &'static str {
+        match self.format {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Gif => "gif",
+            _ => "png",
+        }
+    }
+}
+
+/// Detect the format of encoded image bytes
+pub fn sniff_format(bytes: &[u8]) -> Option<ImageFormat> {
+    if is_svg(bytes) {
+        return Some(ImageFormat::Svg);
+    }
+    match image::guess_format(bytes).ok()? {
+        image::ImageFormat::Png => Some(ImageFormat::Png),
+        image::ImageFormat::Jpeg => Some(ImageFormat::Jpeg),
+        image::ImageFormat::Gif => Some(ImageFormat::Gif),
+        image::ImageFormat::WebP => Some(ImageFormat::WebP),
+        image::ImageFormat::Ico => Some(ImageFormat::Ico),
+        _ => None,
+    }
+}
+
+/// SVG is text, so look for the root element near the start of the document
+fn is_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    let trimmed = head.trim_start_matches('\u{feff}').trim_start();
+    (trimmed.starts_with("<?xml") || trimmed.starts_with("<svg") || trimmed.starts_with("<!--"))
+        && head.contains("<svg")
+}
+
+/// Decode, rasterize, downscale and re-encode `bytes` to satisfy `target`
+///
+/// Returns `None` when the source already satisfies the target and can be used as-is.
+pub fn process_image(
+    bytes: &[u8],
+    target: &ImageTarget,
+) -> Result<Option<ProcessedImage>, NotificationError> {
+    process_image_bytes(bytes, target).map_err(|message| NotificationError::ResourceError {
+        resource_type: "image".to_string(),
+        resource_id: "in-memory".to_string(),
+        kind: ErrorKind::InvalidRequest,
+        message,
+        source: None,
+    })
+}
+
+fn process_image_bytes(
+    bytes: &[u8],
+    target: &ImageTarget,
+) -> Result<Option<ProcessedImage>, String> {
+    let format = sniff_format(bytes).ok_or_else(|| "Unrecognized image format".to_string())?;
+
+    let image = if format == ImageFormat::Svg {
+        rasterize_svg(bytes, target)?
+    } else {
+        let decoded = image::load_from_memory(bytes)
+            .map_err(|e| format!("Failed to decode {:?} image: {}", format, e))?;
+
+        let (width, height) = decoded.dimensions();
+        if target.accepts(format)
+            && width <= target.max_width
+            && height <= target.max_height
+            && target.fits_bytes(bytes.len())
+        {
+            return Ok(None);
+        }
+        decoded
+    };
+
+    let mut image = fit_within(image, target.max_width, target.max_height);
+    loop {
+        if let Some(processed) = encode_to_fit(&image, target)? {
+            return Ok(Some(processed));
+        }
+
+        // Nothing fit at this size, shrink and try again
+        let (width, height) = image.dimensions();
+        let (width, height) = (width * 3 / 4, height * 3 / 4);
+        if width < MIN_DIMENSION || height < MIN_DIMENSION {
+            return Err(format!(
+                "Image cannot be compressed below {} bytes",
+                target.max_bytes.unwrap_or_default()
+            ));
+        }
+        image = image.resize_exact(width, height, FilterType::Lanczos3);
+    }
+}
+
+/// Render an SVG document scaled to fill the target box
+fn rasterize_svg(bytes: &[u8], target: &ImageTarget) -> Result<DynamicImage, String> {
+    let tree = resvg::usvg::Tree::from_data(bytes, &resvg::usvg::Options::default())
+        .map_err(|e| format!("Failed to parse SVG: {}", e))?;
+
+    let size = tree.size();
+    let scale = (target.max_width as f32 / size.width()).min(target.max_height as f32 / size.height());
+    let width = ((size.width() * scale).round() as u32).max(1);
+    let height = ((size.height() * scale).round() as u32).max(1);
+
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| "Invalid SVG dimensions".to_string())?;
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // tiny-skia stores premultiplied alpha
+    let mut rgba = RgbaImage::new(width, height);
+    for (pixel, source) in rgba.pixels_mut().zip(pixmap.pixels()) {
+        let color = source.demultiply();
+        *pixel = image::Rgba([color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+    Ok(DynamicImage::ImageRgba8(rgba))
+}
+
+/// Downscale preserving aspect ratio; images are never upscaled
+fn fit_within(image: DynamicImage, max_width: u32, max_height: u32) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    if width <= max_width && height <= max_height {
+        image
+    } else {
+        image.resize(max_width, max_height, FilterType::Lanczos3)
+    }
+}
+
+/// Try each accepted encoding in turn and return the first that fits the byte limit
+fn encode_to_fit(image: &DynamicImage, target: &ImageTarget) -> Result<Option<ProcessedImage>, String> {
+    let (width, height) = image.dimensions();
+
+    if target.accepts(ImageFormat::Png) {
+        let mut data = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+        if target.fits_bytes(data.len()) {
+            return Ok(Some(ProcessedImage { data, format: ImageFormat::Png, width, height }));
+        }
+    }
+
+    if target.accepts(ImageFormat::Jpeg) {
+        let flattened = flatten_alpha(image);
+        for quality in JPEG_QUALITIES {
+            let mut data = Vec::new();
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, quality)
+                .encode_image(&flattened)
+                .map_err(|e| format!("Failed to encode JPEG: {}", e))?;
+            if target.fits_bytes(data.len()) {
+                return Ok(Some(ProcessedImage { data, format: ImageFormat::Jpeg, width, height }));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Composite transparent pixels onto white, since JPEG has no alpha channel
+fn flatten_alpha(image: &DynamicImage) -> RgbImage {
+    let rgba = image.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u16 * a as u16 + 255 * (255 - a as u16)) / 255) as u8;
+        image::Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+/// Normalize a resolved image file for `target`, caching the result by content hash
+///
+/// The original is returned unchanged when it already satisfies the target.
+pub async fn normalize_resolved_image(
+    resolved: ResolvedImage,
+    target: &ImageTarget,
+) -> Result<ResolvedImage, NotificationError> {
+    let resource_error = |kind: ErrorKind, message: String, source: Option<crate::components::ErrorSource>| {
+        NotificationError::ResourceError {
+            resource_type: "image".to_string(),
+            resource_id: resolved.original_url.clone(),
+            kind,
+            message,
+            source,
+        }
+    };
+
+    let bytes = tokio::fs::read(&resolved.path)
+        .await
+        .map_err(|e| {
+            resource_error(super::io_error_kind(&e), format!("Failed to read image: {}", e), Some(e.into()))
+        })?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    hasher.update(target.cache_key().as_bytes());
+    let key = format!("{:x}", hasher.finalize());
+
+    let cache = get_processed_cache().await;
+    if let Some(entry) = cache.get(&key)
+        && entry.path.exists()
+    {
+        return Ok(ResolvedImage {
+            path: entry.path.clone(),
+            is_temp: true,
+            original_url: resolved.original_url.clone(),
+        });
+    }
+
+    // Decoding and encoding are CPU bound, keep them off the async workers
+    let processing_target = target.clone();
+    let processed = tokio::task::spawn_blocking(move || process_image_bytes(&bytes, &processing_target))
+        .await
+        .map_err(|e| resource_error(ErrorKind::Other, format!("Image processing task failed: {}", e), Some(e.into())))?
+        .map_err(|message| resource_error(ErrorKind::InvalidRequest, message, None))?;
+
+    let Some(processed) = processed else {
+        return Ok(resolved);
+    };
+
+    let temp_file = NamedTempFile::with_suffix(format!(".{}", processed.extension()))
+        .map_err(|e| resource_error(ErrorKind::Other, format!("Failed to create temp file: {}", e), Some(e.into())))?;
+    tokio::fs::write(temp_file.path(), &processed.data)
+        .await
+        .map_err(|e| resource_error(ErrorKind::Other, format!("Failed to write temp file: {}", e), Some(e.into())))?;
+    let path = temp_file
+        .into_temp_path()
+        .keep()
+        .map_err(|e| resource_error(ErrorKind::Other, format!("Failed to persist temp file: {}", e), Some(e.into())))?;
+
+    tracing::debug!(
+        "Normalized image {} to {}x{} {:?} ({} bytes)",
+        resolved.original_url,
+        processed.width,
+        processed.height,
+        processed.format,
+        processed.data.len()
+    );
+
+    cache.insert(key, ProcessedEntry { path: path.clone(), cached_at: Instant::now() });
+    evict_oldest_entries(&cache);
+
+    Ok(ResolvedImage {
+        path,
+        is_temp: true,
+        original_url: resolved.original_url,
+    })
+}
+
+fn evict_oldest_entries(cache: &DashMap<String, ProcessedEntry>) {
+    while cache.len() > MAX_PROCESSED_ENTRIES {
+        let oldest = cache
+            .iter()
+            .min_by_key(|entry| entry.cached_at)
+            .map(|entry| entry.key().clone());
+
+        match oldest.and_then(|key| cache.remove(&key)) {
+            Some((_, entry)) => {
+                let _ = std::fs::remove_file(&entry.path);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Resolve image attachments and normalize each one for `platform`
+///
+/// Images that cannot be resolved or processed are skipped with a warning,
+/// matching [`super::image_utils::resolve_media_images`].
+pub async fn resolve_media_images_for(
+    media: &[MediaAttachment],
+    platform: Platform,
+    max_image_size: Option<usize>,
+) -> Vec<(ImagePlacement, ResolvedImage)> {
+    let mut resolved = Vec::new();
+
+    for attachment in media {
+        if let MediaAttachment::Image { data, placement, .. } = attachment {
+            let image = match resolve_image_to_path(data).await {
+                Ok(Some(image)) => image,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("Failed to resolve image: {}", e);
+                    continue;
+                }
+            };
+
+            let target = ImageTarget::for_placement(platform, *placement, max_image_size);
+            match normalize_resolved_image(image, &target).await {
+                Ok(image) => resolved.push((*placement, image)),
+                Err(e) => tracing::warn!("Failed to normalize image: {}", e),
+            }
+        }
+    }
+
+    resolved
+}
+
+/// Clean up all processed images
+pub fn cleanup_processed_images() {
+    if let Some(cache) = PROCESSED_CACHE.get() {
+        for entry in cache.iter() {
+            if let Err(e) = std::fs::remove_file(&entry.value().path) {
+                tracing::debug!("Failed to cleanup processed image {:?}: {}", entry.value().path, e);
+            }
+        }
+        cache.clear();
+    }
+}
@@ -1,8 +1,9 @@
 // Shared image utilities for all platform backends
 // Handles downloading remote images and converting to local temp files
 
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
@@ -10,7 +11,10 @@ use tempfile::NamedTempFile;
 use tokio::sync::OnceCell;
 use url::Url;
 
-use crate::components::{ImageData, MediaAttachment, NotificationError};
+use crate::components::{
+    ActionIcon, ErrorKind, ImageData, MediaAttachment, NotificationContent, NotificationError,
+    decode_image_data_url,
+};
 
 /// Cached image entry with TTL tracking
 #[derive(Debug, Clone)]
@@ -25,25 +29,223 @@ const CACHE_TTL_SECS: u64 = 3600;
 /// Maximum cache entries before forced eviction
 const MAX_CACHE_ENTRIES: usize = 100;
 
-/// Global HTTP client for image downloads (shared across all backends)
-static HTTP_CLIENT: OnceCell<reqwest::Client> = OnceCell::const_new();
+/// HTTP clients for image downloads, one per fetch policy (shared across all backends)
+static HTTP_CLIENTS: LazyLock<DashMap<ImageFetchPolicy, reqwest::Client>> =
+    LazyLock::new(DashMap::new);
 
-/// Cache of downloaded images with TTL tracking
-/// Key: URL string, Value: CachedImage with timestamp
+/// Fetch policy applied when callers don't supply their own
+static DEFAULT_FETCH_POLICY: LazyLock<parking_lot::RwLock<ImageFetchPolicy>> =
+    LazyLock::new(|| parking_lot::RwLock::new(ImageFetchPolicy::default()));
+
+/// Cache of downloaded and decoded images with TTL tracking
+/// Key: URL string, or `sha256:<hex>.<ext>` of written bytes; Value: CachedImage with timestamp
 static IMAGE_CACHE: OnceCell<Arc<DashMap<String, CachedImage>>> = OnceCell::const_new();
 
-/// Get or create the shared HTTP client
-async fn get_http_client() -> &'static reqwest::Client {
-    HTTP_CLIENT
-        .get_or_init(|| async {
-            reqwest::Client::builder()
-                .timeout(Duration::from_secs(30))
-                .connect_timeout(Duration::from_secs(10))
-                .user_agent("KODEGEN-Notifications/1.0")
-                .build()
-                .expect("Failed to create HTTP client")
+/// Restrictions applied to remote image downloads
+///
+/// Every violation surfaces as `NotificationError::ResourceError`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageFetchPolicy {
+    /// Maximum response body size, enforced while streaming
+    pub max_bytes: usize,
+    /// Maximum number of redirects followed
+    pub max_redirects: usize,
+    /// Allow hosts resolving to loopback, private, link-local or otherwise internal addresses
+    pub allow_private_addresses: bool,
+    /// If non-empty, only these hosts (and their subdomains) may be fetched
+    pub allowed_hosts: Vec<String>,
+    /// Hosts (and their subdomains) that may never be fetched
+    pub denied_hosts: Vec<String>,
+    /// Overall request timeout
+    pub timeout: Duration,
+}
+
+impl Default for ImageFetchPolicy {
+    fn default() -> Self {
+        Self {
+            max_bytes: 10 * 1024 * 1024,
+            max_redirects: 5,
+            allow_private_addresses: false,
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ImageFetchPolicy {
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    pub fn with_private_addresses(mut self, allow: bool) -> Self {
+        self.allow_private_addresses = allow;
+        self
+    }
+
+    pub fn allow_host(mut self, host: impl Into<String>) -> Self {
+        self.allowed_hosts.push(host.into().to_ascii_lowercase());
+        self
+    }
+
+    pub fn deny_host(mut self, host: impl Into<String>) -> Self {
+        self.denied_hosts.push(host.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Check scheme, host lists and literal IP addresses of a URL before connecting
+    pub fn check_url(&self, url: &Url) -> Result<(), String> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("Scheme '{}' is not allowed", url.scheme()));
+        }
+
+        let host = match url.host() {
+            Some(url::Host::Domain(domain)) => domain.trim_end_matches('.').to_ascii_lowercase(),
+            Some(url::Host::Ipv4(ip)) => ip.to_string(),
+            Some(url::Host::Ipv6(ip)) => ip.to_string(),
+            None => return Err("URL has no host".to_string()),
+        };
+
+        let matches_host = |pattern: &String| {
+            let pattern = pattern.trim_start_matches('.');
+            host == pattern || host.ends_with(&format!(".{}", pattern))
+        };
+        if self.denied_hosts.iter().any(matches_host) {
+            return Err(format!("Host '{}' is denied", host));
+        }
+        if !self.allowed_hosts.is_empty() && !self.allowed_hosts.iter().any(matches_host) {
+            return Err(format!("Host '{}' is not in the allowlist", host));
+        }
+
+        // IP literals never reach the resolver, so check them here
+        let literal = match url.host() {
+            Some(url::Host::Ipv4(ip)) => Some(IpAddr::V4(ip)),
+            Some(url::Host::Ipv6(ip)) => Some(IpAddr::V6(ip)),
+            _ => None,
+        };
+        if let Some(ip) = literal
+            && !self.allow_private_addresses
+            && is_internal_address(ip)
+        {
+            return Err(format!("Address {} is private, loopback or link-local", ip));
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the process-wide default fetch policy
+pub fn image_fetch_policy() -> ImageFetchPolicy {
+    DEFAULT_FETCH_POLICY.read().clone()
+}
+
+/// Replace the process-wide default fetch policy
+pub fn set_image_fetch_policy(policy: ImageFetchPolicy) {
+    *DEFAULT_FETCH_POLICY.write() = policy;
+}
+
+/// Whether an address is not publicly routable and must not be fetched from by default
+fn is_internal_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || (a == 100 && (64..128).contains(&b)) // Carrier-grade NAT 100.64.0.0/10
+                || a == 0
+        }
+        IpAddr::V6(v6) => {
+            // IPv4-mapped ::ffff:a.b.c.d and IPv4-compatible ::a.b.c.d
+            if let Some(v4) = v6.to_ipv4() {
+                return is_internal_address(IpAddr::V4(v4));
+            }
+            if let Some(v4) = embedded_ipv4(v6) {
+                return is_internal_address(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || (first & 0xfe00) == 0xfc00 // Unique local fc00::/7
+                || (first & 0xffc0) == 0xfe80 // Link-local fe80::/10
+        }
+    }
+}
+
+/// IPv4 address a translating prefix routes `v6` to: NAT64 64:ff9b::/96 or 6to4 2002::/16
+fn embedded_ipv4(v6: Ipv6Addr) -> Option<Ipv4Addr> {
+    match v6.segments() {
+        [0x64, 0xff9b, 0, 0, 0, 0, high, low] | [0x2002, high, low, ..] => {
+            Some(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low)))
+        }
+        _ => None,
+    }
+}
+
+/// DNS resolver that drops internal addresses so hostnames can't be used to reach them
+struct PublicOnlyResolver;
+
+impl reqwest::dns::Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| !is_internal_address(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!(
+                    "Host '{}' resolves only to private, loopback or link-local addresses",
+                    host
+                )
+                .into());
+            }
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
         })
-        .await
+    }
+}
+
+/// Get or create the HTTP client enforcing `policy`
+fn get_http_client(policy: &ImageFetchPolicy) -> Result<reqwest::Client, String> {
+    if let Some(client) = HTTP_CLIENTS.get(policy) {
+        return Ok(client.clone());
+    }
+
+    let redirect_policy = policy.clone();
+    let mut builder = reqwest::Client::builder()
+        .timeout(policy.timeout)
+        .connect_timeout(Duration::from_secs(10))
+        .user_agent("KODEGEN-Notifications/1.0")
+        .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() > redirect_policy.max_redirects {
+                let limit = redirect_policy.max_redirects;
+                attempt.error(format!("Too many redirects (limit {})", limit))
+            } else if let Err(message) = redirect_policy.check_url(attempt.url()) {
+                attempt.error(message)
+            } else {
+                attempt.follow()
+            }
+        }));
+    if !policy.allow_private_addresses {
+        // A proxy would resolve hosts itself and bypass the address filter
+        builder = builder.dns_resolver(Arc::new(PublicOnlyResolver)).no_proxy();
+    }
+
+    let client = builder
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    HTTP_CLIENTS.insert(policy.clone(), client.clone());
+    Ok(client)
 }
 
 /// Get or create the image cache
@@ -97,13 +299,175 @@ pub struct ResolvedImage {
     pub original_url: String,
 }
 
-/// Download a remote image to a temporary file
+/// Image bytes fetched over HTTP along with the validators needed to revalidate them
+#[derive(Debug, Clone)]
+pub(crate) struct FetchedImage {
+    pub bytes: Vec<u8>,
+    pub extension: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// Outcome of a (possibly conditional) image fetch
+#[derive(Debug, Clone)]
+pub(crate) enum FetchOutcome {
+    /// Server answered 304 for the supplied validators
+    NotModified,
+    Fetched(FetchedImage),
+}
+
+/// Fetch a remote image, sending `If-None-Match`/`If-Modified-Since` when validators are given
+///
+/// The body is streamed and aborted once it exceeds `policy.max_bytes`, and the
+/// result must sniff as a supported image format regardless of its Content-Type.
+pub(crate) async fn fetch_image(
+    url: &Url,
+    etag: Option<&str>,
+    last_modified: Option<&str>,
+    policy: &ImageFetchPolicy,
+) -> Result<FetchOutcome, NotificationError> {
+    let url_string = url.to_string();
+    let resource_error = |kind: ErrorKind, message: String| NotificationError::ResourceError {
+        resource_type: "image".to_string(),
+        resource_id: url_string.clone(),
+        kind,
+        message,
+        source: None,
+    };
+    let download_error = |context: &str, error: reqwest::Error| NotificationError::ResourceError {
+        resource_type: "image".to_string(),
+        resource_id: url_string.clone(),
+        kind: super::reqwest_error_kind(&error),
+        message: format!("{}: {}", context, error_chain(&error)),
+        source: Some(error.into()),
+    };
+
+    policy
+        .check_url(url)
+        .map_err(|message| resource_error(ErrorKind::InvalidRequest, message))?;
+    let client = get_http_client(policy).map_err(|message| resource_error(ErrorKind::Other, message))?;
+
+    let mut request = client.get(url.as_str());
+    if let Some(etag) = etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = last_modified {
+        request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+    }
+
+    // Download the image
+    let mut response = request
+        .send()
+        .await
+        .map_err(|e| download_error("Failed to download image", e))?;
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(FetchOutcome::NotModified);
+    }
+
+    // Check response status
+    if !response.status().is_success() {
+        return Err(resource_error(
+            super::http_status_kind(response.status()),
+            format!("HTTP {} downloading image", response.status()),
+        ));
+    }
+
+    // Reject early when the server announces an oversized body
+    if let Some(size) = response.content_length()
+        && size > policy.max_bytes as u64
+    {
+        return Err(resource_error(ErrorKind::InvalidRequest, format!(
+            "Image too large: {} bytes exceeds {} byte limit",
+            size, policy.max_bytes
+        )));
+    }
+
+    let header = |name: reqwest::header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let etag = header(reqwest::header::ETAG);
+    let last_modified = header(reqwest::header::LAST_MODIFIED);
+
+    // Stream the body so a missing or lying Content-Length can't exhaust memory
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| download_error("Failed to read image bytes", e))?
+    {
+        if bytes.len() + chunk.len() > policy.max_bytes {
+            return Err(resource_error(ErrorKind::InvalidRequest, format!(
+                "Image too large: exceeds {} byte limit",
+                policy.max_bytes
+            )));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    // Trust the bytes, not the server's Content-Type or the URL's extension
+    let format = super::image_processing::sniff_format(&bytes)
+        .ok_or_else(|| {
+            resource_error(
+                ErrorKind::InvalidRequest,
+                "Downloaded data is not a supported image format".to_string(),
+            )
+        })?;
+
+    Ok(FetchOutcome::Fetched(FetchedImage {
+        bytes,
+        extension: image_extension(format).to_string(),
+        etag,
+        last_modified,
+    }))
+}
+
+/// Describe a reqwest error including its sources, which carry redirect/DNS policy messages
+fn error_chain(error: &reqwest::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+/// File extension for an image format
+pub(crate) fn image_extension(format: crate::components::ImageFormat) -> &'static str {
+    match format {
+        crate::components::ImageFormat::Png => "png",
+        crate::components::ImageFormat::Jpeg => "jpg",
+        crate::components::ImageFormat::Gif => "gif",
+        crate::components::ImageFormat::WebP => "webp",
+        crate::components::ImageFormat::Svg => "svg",
+        crate::components::ImageFormat::Ico => "ico",
+    }
+}
+
+/// Download a remote image to a local file
 ///
-/// Supports http:// and https:// URLs. Returns the path to the downloaded temp file.
-/// Uses caching to avoid re-downloading the same image multiple times.
+/// Supports http:// and https:// URLs. Images go through the persistent disk cache
+/// (see [`super::image_cache`]) when one is available, which survives restarts and
+/// revalidates with the server. Otherwise they are downloaded to temp files cached
+/// in-process to avoid re-downloading the same image multiple times.
 pub async fn download_image(url: &Url) -> Result<ResolvedImage, NotificationError> {
     let url_string = url.to_string();
 
+    if let Some(disk_cache) = super::image_cache::disk_image_cache().await {
+        let path = disk_cache.fetch(url).await?;
+        return Ok(ResolvedImage {
+            path,
+            is_temp: false,
+            original_url: url_string,
+        });
+    }
+
     // Check cache first (with TTL validation)
     let cache = get_image_cache().await;
     evict_stale_entries(&cache);
@@ -122,83 +486,36 @@ pub async fn download_image(url: &Url) -> Result<ResolvedImage, NotificationErro
         }
     }
 
-    let client = get_http_client().await;
-
-    // Download the image
-    let response = client
-        .get(url.as_str())
-        .send()
-        .await
-        .map_err(|e| NotificationError::ResourceError {
-            resource_type: "image".to_string(),
-            resource_id: url_string.clone(),
-            message: format!("Failed to download image: {}", e),
-        })?;
-
-    // Check response status
-    if !response.status().is_success() {
-        return Err(NotificationError::ResourceError {
-            resource_type: "image".to_string(),
-            resource_id: url_string.clone(),
-            message: format!("HTTP {} downloading image", response.status()),
-        });
-    }
-
-    // Validate Content-Length before downloading (10MB max)
-    const MAX_IMAGE_SIZE: u64 = 10 * 1024 * 1024;
-    if let Some(content_length) = response.headers().get(reqwest::header::CONTENT_LENGTH)
-        && let Ok(length_str) = content_length.to_str()
-        && let Ok(size) = length_str.parse::<u64>()
-        && size > MAX_IMAGE_SIZE
-    {
+    let FetchOutcome::Fetched(fetched) = fetch_image(url, None, None, &image_fetch_policy()).await? else {
         return Err(NotificationError::ResourceError {
             resource_type: "image".to_string(),
-            resource_id: url_string.clone(),
-            message: format!(
-                "Image too large: {} bytes exceeds {} byte limit",
-                size, MAX_IMAGE_SIZE
-            ),
+            resource_id: url_string,
+            kind: ErrorKind::Other,
+            message: "Unexpected 304 for unconditional request".to_string(),
+            source: None,
         });
-    }
-
-    // Determine file extension from URL or content-type
-    let extension = determine_extension(url, response.headers());
-
-    // Get the bytes
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| NotificationError::ResourceError {
-            resource_type: "image".to_string(),
-            resource_id: url_string.clone(),
-            message: format!("Failed to read image bytes: {}", e),
-        })?;
-
-    // Validate we got actual image data (basic check)
-    if bytes.len() < 8 {
-        return Err(NotificationError::ResourceError {
-            resource_type: "image".to_string(),
-            resource_id: url_string.clone(),
-            message: "Downloaded image is too small to be valid".to_string(),
-        });
-    }
+    };
 
     // Create temp file with appropriate extension
-    let temp_file = NamedTempFile::with_suffix(format!(".{}", extension))
+    let temp_file = NamedTempFile::with_suffix(format!(".{}", fetched.extension))
         .map_err(|e| NotificationError::ResourceError {
             resource_type: "image".to_string(),
             resource_id: url_string.clone(),
+            kind: ErrorKind::Other,
             message: format!("Failed to create temp file: {}", e),
+            source: Some(e.into()),
         })?;
 
     // Write bytes to temp file
     let temp_path = temp_file.path().to_path_buf();
-    tokio::fs::write(&temp_path, &bytes)
+    tokio::fs::write(&temp_path, &fetched.bytes)
         .await
         .map_err(|e| NotificationError::ResourceError {
             resource_type: "image".to_string(),
             resource_id: url_string.clone(),
+            kind: ErrorKind::Other,
             message: format!("Failed to write temp file: {}", e),
+            source: Some(e.into()),
         })?;
 
     // Keep the temp file alive by persisting it (don't auto-delete on drop)
@@ -206,7 +523,9 @@ pub async fn download_image(url: &Url) -> Result<ResolvedImage, NotificationErro
         .map_err(|e| NotificationError::ResourceError {
             resource_type: "image".to_string(),
             resource_id: url_string.clone(),
+            kind: ErrorKind::Other,
             message: format!("Failed to persist temp file: {}", e),
+            source: Some(e.into()),
         })?;
 
     // Cache the path with timestamp
@@ -229,8 +548,9 @@ pub async fn download_image(url: &Url) -> Result<ResolvedImage, NotificationErro
 /// - File paths are returned as-is
 /// - file:// URLs are converted to paths
 /// - http:// and https:// URLs are downloaded to temp files
-/// - Embedded data is written to temp files
-/// - SystemIcon returns None (platform-specific handling needed)
+/// - data: URLs and embedded data are written to temp files
+/// - SystemIcon names are looked up in the freedesktop icon theme on Linux and
+///   return None elsewhere (platform-specific handling needed)
 pub async fn resolve_image_to_path(data: &ImageData) -> Result<Option<ResolvedImage>, NotificationError> {
     match data {
         ImageData::File(path) => {
@@ -238,7 +558,9 @@ pub async fn resolve_image_to_path(data: &ImageData) -> Result<Option<ResolvedIm
                 return Err(NotificationError::ResourceError {
                     resource_type: "image".to_string(),
                     resource_id: path.display().to_string(),
+                    kind: ErrorKind::NotFound,
                     message: "Image file does not exist".to_string(),
+                    source: None,
                 });
             }
             Ok(Some(ResolvedImage {
@@ -256,7 +578,9 @@ pub async fn resolve_image_to_path(data: &ImageData) -> Result<Option<ResolvedIm
                         return Err(NotificationError::ResourceError {
                             resource_type: "image".to_string(),
                             resource_id: url.to_string(),
+                            kind: ErrorKind::NotFound,
                             message: "Image file does not exist".to_string(),
+                            source: None,
                         });
                     }
                     Ok(Some(ResolvedImage {
@@ -269,6 +593,16 @@ pub async fn resolve_image_to_path(data: &ImageData) -> Result<Option<ResolvedIm
                     let resolved = download_image(url).await?;
                     Ok(Some(resolved))
                 }
+                "data" => {
+                    // Decoded in-process, never touches the network
+                    let (bytes, format) = decode_image_data_url(url)?;
+                    let path = write_temp_image(&bytes, image_extension(format), "data_url").await?;
+                    Ok(Some(ResolvedImage {
+                        path,
+                        is_temp: true,
+                        original_url: "data:".to_string(),
+                    }))
+                }
                 scheme => {
                     tracing::warn!("Unsupported URL scheme for image: {}", scheme);
                     Ok(None)
@@ -277,51 +611,144 @@ pub async fn resolve_image_to_path(data: &ImageData) -> Result<Option<ResolvedIm
         }
 
         ImageData::Embedded { data, format } => {
-            let extension = match format {
-                crate::components::ImageFormat::Png => "png",
-                crate::components::ImageFormat::Jpeg => "jpg",
-                crate::components::ImageFormat::Gif => "gif",
-                crate::components::ImageFormat::WebP => "webp",
-                crate::components::ImageFormat::Svg => "svg",
-                crate::components::ImageFormat::Ico => "ico",
-            };
-
-            let temp_file = NamedTempFile::with_suffix(format!(".{}", extension))
-                .map_err(|e| NotificationError::ResourceError {
-                    resource_type: "image".to_string(),
-                    resource_id: "embedded".to_string(),
-                    message: format!("Failed to create temp file: {}", e),
-                })?;
-
-            let temp_path = temp_file.path().to_path_buf();
-            tokio::fs::write(&temp_path, data)
-                .await
-                .map_err(|e| NotificationError::ResourceError {
-                    resource_type: "image".to_string(),
-                    resource_id: "embedded".to_string(),
-                    message: format!("Failed to write temp file: {}", e),
-                })?;
+            let path = write_temp_image(data, image_extension(*format), "embedded").await?;
+            Ok(Some(ResolvedImage {
+                path,
+                is_temp: true,
+                original_url: "embedded://data".to_string(),
+            }))
+        }
 
-            let persisted_path = temp_file.into_temp_path().keep()
-                .map_err(|e| NotificationError::ResourceError {
-                    resource_type: "image".to_string(),
-                    resource_id: "embedded".to_string(),
-                    message: format!("Failed to persist temp file: {}", e),
-                })?;
+        ImageData::SystemIcon(icon_name) => resolve_system_icon(icon_name),
+    }
+}
 
+/// Check that the system icons of `content` resolve, before it is sent to Linux
+///
+/// Image and action icon names are looked up in the icon theme, so an unknown
+/// name fails with a `ValidationError` instead of the icon silently going missing.
+/// The notification spec has no per-action images, so action icons are only checked.
+pub(crate) async fn validate_system_icons(content: &NotificationContent) -> Result<(), NotificationError> {
+    for media in &content.media {
+        if let MediaAttachment::Image { data: data @ ImageData::SystemIcon(_), .. } = media {
+            resolve_image_to_path(data).await?;
+        }
+    }
+    for action in &content.interactions.actions {
+        if let Some(icon @ ActionIcon::System(_)) = &action.icon {
+            resolve_action_icon(icon).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Resolve an action icon to a local file path
+///
+/// System icon names go through the same icon theme lookup as
+/// `ImageData::SystemIcon`; embedded bytes are sniffed for their format.
+pub async fn resolve_action_icon(icon: &ActionIcon) -> Result<Option<ResolvedImage>, NotificationError> {
+    match icon {
+        ActionIcon::System(name) => resolve_system_icon(name),
+        ActionIcon::File(path) => resolve_image_to_path(&ImageData::File(path.clone())).await,
+        ActionIcon::Url(url) => resolve_image_to_path(&ImageData::Url(url.clone())).await,
+        ActionIcon::Embedded(data) => {
+            let format = super::image_processing::sniff_format(data).ok_or_else(|| {
+                NotificationError::ValidationError {
+                    field: "action_icon".to_string(),
+                    message: "Embedded action icon is not a recognized image format".to_string(),
+                }
+            })?;
+            let path = write_temp_image(data, image_extension(format), "action_icon").await?;
             Ok(Some(ResolvedImage {
-                path: persisted_path,
+                path,
                 is_temp: true,
                 original_url: "embedded://data".to_string(),
             }))
         }
+    }
+}
+
+/// Look up a named icon in the freedesktop icon theme
+#[cfg(target_os = "linux")]
+fn resolve_system_icon(icon_name: &str) -> Result<Option<ResolvedImage>, NotificationError> {
+    let lookup = super::icon_theme::icon_lookup();
+    let path = lookup
+        .lookup(icon_name, super::icon_theme::DEFAULT_ICON_SIZE, 1)
+        .ok_or_else(|| NotificationError::ValidationError {
+            field: "icon".to_string(),
+            message: format!(
+                "Icon '{}' not found in icon theme '{}' or its fallbacks ({})",
+                icon_name,
+                lookup.theme(),
+                super::icon_theme::FALLBACK_THEME
+            ),
+        })?;
+
+    Ok(Some(ResolvedImage {
+        path,
+        is_temp: false,
+        original_url: format!("icon:{}", icon_name),
+    }))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resolve_system_icon(icon_name: &str) -> Result<Option<ResolvedImage>, NotificationError> {
+    // System icons need platform-specific handling
+    tracing::debug!("SystemIcon '{}' requires platform-specific handling", icon_name);
+    Ok(None)
+}
 
-        ImageData::SystemIcon(icon_name) => {
-            // System icons need platform-specific handling
-            tracing::debug!("SystemIcon '{}' requires platform-specific handling", icon_name);
-            Ok(None)
+/// Write image bytes to a persisted temp file with the given extension
+///
+/// Files are cached by content hash, so sending the same bytes again reuses
+/// the file and eviction bounds how many stay on disk.
+async fn write_temp_image(
+    data: &[u8],
+    extension: &str,
+    resource_id: &str,
+) -> Result<PathBuf, NotificationError> {
+    let key = format!("sha256:{}.{}", super::image_cache::sha256_hex(data), extension);
+    let cache = get_image_cache().await;
+    evict_stale_entries(&cache);
+
+    if let Some(cached) = cache.get(&key) {
+        if cached.path.exists() {
+            return Ok(cached.path.clone());
         }
+        // Cleaned up since, write it again
+        drop(cached);
+        cache.remove(&key);
     }
+
+    let resource_error = |message: String, source: crate::components::ErrorSource| {
+        NotificationError::ResourceError {
+            resource_type: "image".to_string(),
+            resource_id: resource_id.to_string(),
+            kind: ErrorKind::Other,
+            message,
+            source: Some(source),
+        }
+    };
+
+    let temp_file = NamedTempFile::with_suffix(format!(".{}", extension))
+        .map_err(|e| resource_error(format!("Failed to create temp file: {}", e), e.into()))?;
+
+    let temp_path = temp_file.path().to_path_buf();
+    tokio::fs::write(&temp_path, data)
+        .await
+        .map_err(|e| resource_error(format!("Failed to write temp file: {}", e), e.into()))?;
+
+    let path = temp_file
+        .into_temp_path()
+        .keep()
+        .map_err(|e| resource_error(format!("Failed to persist temp file: {}", e), e.into()))?;
+
+    cache.insert(key, CachedImage {
+        path: path.clone(),
+        cached_at: Instant::now(),
+    });
+    evict_stale_entries(&cache);
+    Ok(path)
 }
 
 /// Extract image attachments from media list and resolve them to local paths
@@ -351,41 +778,6 @@ pub async fn resolve_media_images(
     resolved
 }
 
-/// Determine file extension from URL path or Content-Type header
-fn determine_extension(url: &Url, headers: &reqwest::header::HeaderMap) -> String {
-    // Try to get extension from URL path first
-    if let Some(mut path_segments) = url.path_segments()
-        && let Some(last_segment) = path_segments.next_back()
-        && let Some(dot_pos) = last_segment.rfind('.')
-    {
-        let ext = &last_segment[dot_pos + 1..];
-        if !ext.is_empty() && ext.len() <= 4 {
-            return ext.to_lowercase();
-        }
-    }
-
-    // Fall back to Content-Type header
-    if let Some(content_type) = headers.get(reqwest::header::CONTENT_TYPE)
-        && let Ok(ct) = content_type.to_str()
-    {
-        return match ct {
-            ct if ct.contains("image/png") => "png",
-            ct if ct.contains("image/jpeg") || ct.contains("image/jpg") => "jpg",
-            ct if ct.contains("image/gif") => "gif",
-            ct if ct.contains("image/webp") => "webp",
-            ct if ct.contains("image/svg") => "svg",
-            ct if ct.contains("image/x-icon") || ct.contains("image/vnd.microsoft.icon") => "ico",
-            ct if ct.contains("image/bmp") => "bmp",
-            ct if ct.contains("image/tiff") => "tiff",
-            _ => "png", // Default to PNG
-        }
-        .to_string();
-    }
-
-    // Default to PNG if we can't determine
-    "png".to_string()
-}
-
 /// Clean up a temp file if it was downloaded
 pub fn cleanup_temp_image(image: &ResolvedImage) {
     if image.is_temp
@@ -395,8 +787,12 @@ pub fn cleanup_temp_image(image: &ResolvedImage) {
     }
 }
 
-/// Clean up all cached temp images
+/// Clean up all cached temp images, including normalized copies
+///
+/// The persistent disk cache is left in place so it survives restarts.
 pub fn cleanup_all_cached_images() {
+    super::image_processing::cleanup_processed_images();
+
     if let Some(cache) = IMAGE_CACHE.get() {
         for entry in cache.iter() {
             if let Err(e) = std::fs::remove_file(&entry.value().path) {
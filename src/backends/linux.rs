@@ -9,6 +9,9 @@ use std::sync::Arc;
 #[cfg(target_os = "linux")]
 use std::time::SystemTime;
 
+#[cfg(target_os = "linux")]
+use dashmap::DashMap;
+
 #[cfg(target_os = "linux")]
 use tokio::sync::OnceCell;
 
@@ -19,15 +22,29 @@ use kodegen_native_permissions::{PermissionManager, PermissionStatus, Permission
 
 use crate::components::NotificationResult;
 use crate::components::platform::{
-    DeliveryReceipt, NotificationRequest, NotificationUpdate, PlatformBackend, PlatformCapabilities,
+    AppIdentity, DeliveryReceipt, NotificationRequest, NotificationUpdate, PlatformBackend,
+    PlatformCapabilities, PlatformInteraction,
 };
 
+#[cfg(target_os = "linux")]
+use crate::components::platform::{ActionId, PlatformInteractionKind};
+
 #[cfg(target_os = "linux")]
 use crate::components::platform::{PermissionLevel, CompatibilityLevel};
 
 #[cfg(target_os = "linux")]
 use crate::components::Platform;
 
+#[cfg(target_os = "linux")]
+use crate::components::lifecycle::BackoffStrategy;
+
+#[cfg(target_os = "linux")]
+use super::linux_quirks::{ServerInfo, ServerQuirks};
+
+/// Largest image file handed to the platform (1MB conservative estimate)
+#[cfg(target_os = "linux")]
+const MAX_IMAGE_SIZE: usize = 1_048_576;
+
 #[cfg(target_os = "linux")]
 #[dbus_proxy(
     interface = "org.freedesktop.Notifications",
@@ -56,13 +73,88 @@ trait Notifications {
 
     /// Close a notification
     fn close_notification(&self, id: u32) -> ZbusResult<()>;
+
+    /// Emitted when the user invokes an action (or the default action)
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> ZbusResult<()>;
+
+    /// Emitted when a notification closes: 1 expired, 2 dismissed, 3 closed by call
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> ZbusResult<()>;
+
+    /// KDE extension: emitted for the `inline-reply` action with the typed text
+    #[zbus(signal)]
+    fn notification_replied(&self, id: u32, text: String) -> ZbusResult<()>;
+}
+
+/// Action key servers use for inline replies (KDE `inline-reply` capability)
+#[cfg(target_os = "linux")]
+const INLINE_REPLY_ACTION: &str = "inline-reply";
+
+/// `NotificationClosed` reason for a notification dismissed by the user
+#[cfg(target_os = "linux")]
+const CLOSED_BY_USER: u32 = 2;
+
+/// Requests kept as the base of updates before the oldest is dropped
+#[cfg(target_os = "linux")]
+const MAX_SHOWN_REQUESTS: usize = 512;
+
+/// Well-known bus name of the notification server
+#[cfg(target_os = "linux")]
+const NOTIFICATIONS_SERVICE: &str = "org.freedesktop.Notifications";
+
+/// Delay between attempts to reach the session bus again after losing it
+#[cfg(target_os = "linux")]
+const RECONNECT_BACKOFF: BackoffStrategy = BackoffStrategy::ExponentialWithJitter {
+    base_delay: std::time::Duration::from_millis(250),
+    max_delay: std::time::Duration::from_secs(30),
+    multiplier: 2.0,
+    jitter: 0.1,
+};
+
+/// Answers of the notification server currently owning the bus name
+///
+/// Replaced as a whole when the owner changes, since a restarted or different
+/// daemon may answer differently.
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+struct ServerCache {
+    /// Advertised capabilities adjusted by the server's quirks
+    capabilities: OnceCell<Vec<String>>,
+    profile: OnceCell<(ServerInfo, ServerQuirks)>,
+}
+
+/// Notification on screen
+#[cfg(target_os = "linux")]
+#[derive(Clone)]
+struct ShownNotification {
+    request: NotificationRequest,
+    /// When it was last shown or replaced, telling enforced timeouts apart
+    shown_at: std::time::Instant,
+}
+
+/// How a session listening to the notification server ended
+#[cfg(target_os = "linux")]
+enum ListenerExit {
+    /// The bus name changed owner, so the server's notifications and answers are gone
+    OwnerChanged { had_owner: bool },
+    /// The signal streams ended with the bus connection
+    Disconnected,
 }
 
 pub struct LinuxBackend {
+    /// Session bus connection, replaced by a fresh cell when the connection is lost
     #[cfg(target_os = "linux")]
-    connection: Arc<OnceCell<Connection>>,
+    connection: Arc<parking_lot::RwLock<Arc<OnceCell<Connection>>>>,
     #[cfg(target_os = "linux")]
-    capabilities: Arc<OnceCell<Vec<String>>>,
+    server: Arc<parking_lot::RwLock<Arc<ServerCache>>>,
+    /// Interactions read from the server's signals, fanned out to subscribers
+    interactions: tokio::sync::broadcast::Sender<PlatformInteraction>,
+    #[cfg(target_os = "linux")]
+    listening: Arc<std::sync::atomic::AtomicBool>,
+    /// Requests of the notifications on screen by native ID, the base of updates
+    #[cfg(target_os = "linux")]
+    shown: Arc<DashMap<u32, ShownNotification>>,
     permission_manager: PermissionManager,
 }
 
@@ -76,9 +168,14 @@ impl LinuxBackend {
     pub fn new() -> Self {
         Self {
             #[cfg(target_os = "linux")]
-            connection: Arc::new(OnceCell::new()),
+            connection: Arc::new(parking_lot::RwLock::new(Arc::new(OnceCell::new()))),
+            #[cfg(target_os = "linux")]
+            server: Arc::new(parking_lot::RwLock::new(Arc::default())),
+            interactions: tokio::sync::broadcast::channel(64).0,
             #[cfg(target_os = "linux")]
-            capabilities: Arc::new(OnceCell::new()),
+            listening: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            #[cfg(target_os = "linux")]
+            shown: Arc::new(DashMap::new()),
             permission_manager: PermissionManager::new(),
         }
     }
@@ -109,9 +206,11 @@ impl LinuxBackend {
                         Ok(false)
                     }
                     Err(e) => Err(crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
+                        platform: crate::components::Platform::Linux,
+                        kind: super::permission_error_kind(&e),
                         error_code: None,
                         message: format!("Permission request failed: {}", e),
+                        source: None,
                     })
                 }
             }
@@ -120,53 +219,298 @@ impl LinuxBackend {
                 Ok(false)
             }
             Err(e) => Err(crate::components::NotificationError::PlatformError {
-                platform: "Linux".to_string(),
+                platform: crate::components::Platform::Linux,
+                kind: super::permission_error_kind(&e),
                 error_code: None,
                 message: format!("Permission check failed: {}", e),
+                source: None,
             })
         }
     }
 
     #[cfg(target_os = "linux")]
     async fn get_connection(&self) -> Result<Connection, crate::components::NotificationError> {
-        self.connection
+        let connection = Arc::clone(&self.connection.read());
+        connection
             .get_or_try_init(|| async {
                 Connection::session().await.map_err(|e| {
-                    crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
-                        error_code: None,
-                        message: format!("Failed to connect to D-Bus session: {:?}", e),
-                    }
+                    super::linux_errors::from_zbus("Failed to connect to D-Bus session", e)
                 })
             })
             .await
             .cloned()
     }
 
+    /// Drop the connection so the next call connects to the session bus again
+    #[cfg(target_os = "linux")]
+    fn reset_connection(&self) {
+        *self.connection.write() = Arc::new(OnceCell::new());
+    }
+
+    /// Platform error for a failed D-Bus call, dropping the connection if it broke
+    #[cfg(target_os = "linux")]
+    fn dbus_error(&self, context: &str, error: zbus::Error) -> crate::components::NotificationError {
+        if matches!(error, zbus::Error::InputOutput(_)) {
+            self.reset_connection();
+        }
+        super::linux_errors::from_zbus(context, error)
+    }
+
     #[cfg(target_os = "linux")]
     async fn get_capabilities(&self) -> Result<Vec<String>, crate::components::NotificationError> {
-        self.capabilities
+        let server = Arc::clone(&self.server.read());
+        server
+            .capabilities
             .get_or_try_init(|| async {
                 let connection = self.get_connection().await?;
-                let proxy = NotificationsProxy::new(&connection).await.map_err(|e| {
-                    crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
-                        error_code: None,
-                        message: format!("Failed to create D-Bus proxy: {:?}", e),
-                    }
-                })?;
-                proxy.get_capabilities().await.map_err(|e| {
-                    crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
-                        error_code: None,
-                        message: format!("Failed to get capabilities: {:?}", e),
-                    }
-                })
+                let proxy = NotificationsProxy::new(&connection)
+                    .await
+                    .map_err(|e| self.dbus_error("Failed to create D-Bus proxy", e))?;
+                let advertised = proxy.get_capabilities()
+                    .await
+                    .map_err(|e| self.dbus_error("Failed to get capabilities", e))?;
+                let (_, quirks) = self.server_profile().await;
+                Ok(quirks.adjust_capabilities(advertised))
             })
             .await
             .cloned()
     }
 
+    /// Identity and quirks of the notification server
+    ///
+    /// A server that doesn't answer gets the profile of an unknown one, which
+    /// is not cached so the next call asks again.
+    #[cfg(target_os = "linux")]
+    async fn server_profile(&self) -> (ServerInfo, ServerQuirks) {
+        let server = Arc::clone(&self.server.read());
+        let profile = server
+            .profile
+            .get_or_try_init(|| async {
+                let connection = self.get_connection().await?;
+                let proxy = NotificationsProxy::new(&connection)
+                    .await
+                    .map_err(|e| self.dbus_error("Failed to create D-Bus proxy", e))?;
+                let (name, vendor, version, spec_version) =
+                    proxy.get_server_information()
+                        .await
+                        .map_err(|e| self.dbus_error("Failed to get server information", e))?;
+                let info = ServerInfo::new(name, vendor, version, spec_version);
+                let quirks = super::linux_quirks::server_quirks(&info);
+                tracing::debug!(server = ?info, quirks = ?quirks, "Identified notification server");
+                Ok::<_, crate::components::NotificationError>((info, quirks))
+            })
+            .await;
+        match profile {
+            Ok(profile) => profile.clone(),
+            Err(e) => {
+                tracing::debug!("Notification server not identified: {}", e);
+                let info = ServerInfo::unknown();
+                let quirks = super::linux_quirks::server_quirks(&info);
+                (info, quirks)
+            }
+        }
+    }
+
+    /// Close a notification and forget it
+    #[cfg(target_os = "linux")]
+    async fn close(&self, id: u32) -> NotificationResult<()> {
+        let connection = self.get_connection().await?;
+
+        let proxy = NotificationsProxy::new(&connection)
+            .await
+            .map_err(|e| self.dbus_error("Failed to create D-Bus proxy", e))?;
+
+        proxy
+            .close_notification(id)
+            .await
+            .map_err(|e| self.dbus_error("Failed to close D-Bus notification", e))?;
+        self.shown.remove(&id);
+        Ok(())
+    }
+
+    /// Forget what the previous notification server answered and showed
+    ///
+    /// Notifications it showed are reported as withdrawn when `withdraw_shown`,
+    /// since their native IDs mean nothing to the next owner of the bus name.
+    #[cfg(target_os = "linux")]
+    fn server_changed(&self, withdraw_shown: bool) {
+        *self.server.write() = Arc::default();
+
+        let mut withdrawn = Vec::new();
+        if withdraw_shown {
+            withdrawn = self.shown.iter().map(|entry| *entry.key()).collect();
+            self.shown.clear();
+        }
+        // No subscribers is fine, the caches are dropped either way
+        for id in withdrawn {
+            let _ = self.interactions.send(PlatformInteraction {
+                platform: Platform::Linux,
+                native_id: id.to_string(),
+                kind: PlatformInteractionKind::Withdrawn,
+            });
+        }
+        let _ = self.interactions.send(PlatformInteraction {
+            platform: Platform::Linux,
+            native_id: String::new(),
+            kind: PlatformInteractionKind::ServerChanged,
+        });
+    }
+
+    /// Show `request` through the notification server, replacing `replaces_id` unless 0
+    ///
+    /// Delivery and updates share this pipeline, so a replaced notification keeps
+    /// the icon, media, hints and markup of a fresh one. The request is cached
+    /// under its native ID as the base of later updates.
+    #[cfg(target_os = "linux")]
+    async fn show(&self, request: &NotificationRequest, replaces_id: u32) -> NotificationResult<u32> {
+        let connection = self.get_connection().await?;
+
+        let proxy = NotificationsProxy::new(&connection)
+            .await
+            .map_err(|e| self.dbus_error("Failed to create D-Bus proxy", e))?;
+
+        // Resolve all media images (downloads remote URLs to temp files)
+        let resolved_images = super::image_processing::resolve_media_images_for(
+            &request.content.media,
+            Platform::Linux,
+            Some(MAX_IMAGE_SIZE),
+        ).await;
+
+        // Extract app icon path from resolved images (first AppIcon or any image),
+        // else the app identity's icon
+        let app_icon_path = resolved_images.iter()
+            .find(|(placement, _)| *placement == crate::components::ImagePlacement::AppIcon)
+            .or_else(|| resolved_images.first())
+            .map(|(_, resolved)| resolved.path.to_string_lossy().to_string())
+            .or_else(|| request.app.icon.clone())
+            .unwrap_or_default();
+
+        let (_, quirks) = self.server_profile().await;
+        let capabilities = self.get_capabilities().await.unwrap_or_default();
+
+        // Create hints for the notification
+        let mut hints = self.create_hints(request);
+
+        // Attached files as links KDE Plasma offers to open or drag
+        if quirks.kde_urls {
+            let urls: Vec<String> = crate::actions::attached_files(&request.content.media)
+                .iter()
+                .filter_map(|path| url::Url::from_file_path(path).ok())
+                .map(String::from)
+                .collect();
+            if !urls.is_empty() {
+                hints.insert("x-kde-urls", zbus::zvariant::Value::from(urls));
+            }
+        }
+
+        // Convert actions to D-Bus format, as many as the server shows
+        let mut actions = Vec::new();
+        let max_actions = quirks.max_actions.unwrap_or(usize::MAX);
+        for action in request.content.interactions.actions.iter().take(max_actions) {
+            actions.push(action.id.as_str());
+            actions.push(&action.label);
+        }
+
+        // Text inputs become an inline reply on servers that support it
+        if let Some(crate::components::NotificationInput::Text { label, .. }) =
+            request.content.interactions.inputs.first()
+            && capabilities.iter().any(|capability| capability == INLINE_REPLY_ACTION)
+        {
+            actions.push(INLINE_REPLY_ACTION);
+            actions.push(label);
+        }
+
+        // Set expire timeout based on priority
+        let expire_timeout = match request.options.ttl {
+            Some(ttl) => ttl.as_millis() as i32,
+            None => match request.content.priority {
+                crate::components::Priority::Critical
+                | crate::components::Priority::Urgent => 0, // Never expire
+                crate::components::Priority::High => 10000, // 10 seconds
+                crate::components::Priority::Normal => 5000, // 5 seconds
+                crate::components::Priority::Low => 3000,   // 3 seconds
+            },
+        };
+
+        // Check if server supports body-markup capability for Pango rendering
+        let supports_markup = capabilities.contains(&"body-markup".to_string())
+            || capabilities.contains(&"markup".to_string());
+
+        let body_text = if supports_markup {
+            let markup = crate::components::PangoSubset {
+                hyperlinks: quirks.markup.hyperlinks
+                    && capabilities.iter().any(|capability| capability == "body-hyperlinks"),
+                ..quirks.markup
+            };
+            request.content.body.to_pango_markup_subset(markup)
+        } else {
+            request.content.body.to_structured_plain_text()
+        };
+
+        // Send the notification with resolved app icon
+        let notification_id = proxy
+            .notify(
+                &request.app.display_name,
+                replaces_id,
+                &app_icon_path, // app_icon - local file path (downloaded if remote)
+                &request.content.title,
+                &body_text,
+                actions,
+                hints,
+                expire_timeout,
+            )
+            .await
+            .map_err(|e| self.dbus_error("Failed to send D-Bus notification", e))?;
+
+        if replaces_id != 0 && replaces_id != notification_id {
+            self.shown.remove(&replaces_id);
+        }
+        if self.shown.len() >= MAX_SHOWN_REQUESTS {
+            // Servers hand out increasing IDs, so the smallest is the oldest
+            if let Some(oldest) = self.shown.iter().map(|entry| *entry.key()).min() {
+                self.shown.remove(&oldest);
+            }
+        }
+        let shown_at = std::time::Instant::now();
+        self.shown.insert(
+            notification_id,
+            ShownNotification {
+                request: request.clone(),
+                shown_at,
+            },
+        );
+
+        // Closed here when the server would leave it up regardless of the caller's TTL
+        if quirks.enforce_timeout && request.options.ttl.is_some() && expire_timeout > 0 {
+            let backend = self.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(expire_timeout as u64)).await;
+                let unchanged = backend
+                    .shown
+                    .get(&notification_id)
+                    .is_some_and(|shown| shown.shown_at == shown_at);
+                if !unchanged {
+                    return;
+                }
+                match backend.close(notification_id).await {
+                    // No subscribers is fine, the notification is closed either way
+                    Ok(()) => {
+                        let _ = backend.interactions.send(PlatformInteraction {
+                            platform: Platform::Linux,
+                            native_id: notification_id.to_string(),
+                            kind: PlatformInteractionKind::Expired,
+                        });
+                    }
+                    Err(e) => {
+                        tracing::debug!("Failed to close expired notification {}: {}", notification_id, e);
+                    }
+                }
+            });
+        }
+
+        Ok(notification_id)
+    }
+
     #[cfg(target_os = "linux")]
     fn create_hints(
         &self,
@@ -191,14 +535,154 @@ impl LinuxBackend {
             );
         }
 
-        // Set desktop entry if available
+        // Names the app's .desktop file (see `DesktopEntry::install`), for its name and icon
         hints.insert(
             "desktop-entry",
-            zbus::zvariant::Value::Str("ecs-notifications".into()),
+            zbus::zvariant::Value::Str(request.app.desktop_entry_id().into()),
         );
 
+        // Map the sound choice to the spec's sound hints
+        let custom_sound = request
+            .options
+            .platform_specific
+            .get(crate::components::CUSTOM_SOUND_OPTION)
+            .map(String::as_str);
+        match super::sound_theme::resolve_sound_hint(&request.content, custom_sound) {
+            Some(super::sound_theme::SoundHint::Name(name)) => {
+                hints.insert("sound-name", zbus::zvariant::Value::Str(name.into()));
+            }
+            Some(super::sound_theme::SoundHint::File(path)) => {
+                hints.insert(
+                    "sound-file",
+                    zbus::zvariant::Value::Str(path.to_string_lossy().into_owned().into()),
+                );
+            }
+            Some(super::sound_theme::SoundHint::Suppress) => {
+                hints.insert("suppress-sound", zbus::zvariant::Value::Bool(true));
+            }
+            None => {}
+        }
+
+        // Placeholder for the inline reply field (KDE)
+        if let Some(crate::components::NotificationInput::Text { placeholder, .. }) =
+            request.content.interactions.inputs.first()
+        {
+            hints.insert(
+                "x-kde-reply-placeholder-text",
+                zbus::zvariant::Value::Str(placeholder.as_str().into()),
+            );
+        }
+
         hints
     }
+
+    /// Forward the server's signals to subscribers for as long as anyone listens
+    ///
+    /// Subscribes again when the notification server changes owner, and
+    /// reconnects to the session bus with backoff when the connection is lost.
+    #[cfg(target_os = "linux")]
+    async fn watch_server(self) {
+        let mut attempt = 0;
+        while self.interactions.receiver_count() > 0 {
+            let exit = match self.get_connection().await {
+                Ok(connection) => self.listen_for_interactions(&connection, &mut attempt).await,
+                Err(e) => Err(e),
+            };
+            match exit {
+                Ok(ListenerExit::OwnerChanged { had_owner }) => {
+                    tracing::info!("Notification server changed, dropping its cached state");
+                    self.server_changed(had_owner);
+                    continue;
+                }
+                Ok(ListenerExit::Disconnected) => {
+                    tracing::warn!("Lost the D-Bus session connection, reconnecting");
+                    self.reset_connection();
+                    self.server_changed(true);
+                }
+                Err(e) => {
+                    tracing::warn!("Notification interaction listener stopped: {}", e);
+                    // Retrying on the cached connection would fail the same way
+                    if matches!(
+                        e.kind(),
+                        crate::components::ErrorKind::Connection | crate::components::ErrorKind::ServiceUnavailable
+                    ) {
+                        self.reset_connection();
+                    }
+                }
+            }
+
+            let delay = RECONNECT_BACKOFF.calculate_delay(attempt);
+            attempt = attempt.saturating_add(1);
+            tokio::time::sleep(delay).await;
+        }
+        self.listening.store(false, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Forward `ActionInvoked`, `NotificationClosed` and `NotificationReplied` to subscribers
+    /// until the server changes owner or the connection ends
+    ///
+    /// `attempt` is reset once the subscriptions are in place.
+    #[cfg(target_os = "linux")]
+    async fn listen_for_interactions(
+        &self,
+        connection: &Connection,
+        attempt: &mut u32,
+    ) -> NotificationResult<ListenerExit> {
+        use futures_util::StreamExt;
+
+        let subscribe_error = |e: zbus::Error| {
+            super::linux_errors::from_zbus("Failed to subscribe to notification signals", e)
+        };
+        let bus = zbus::fdo::DBusProxy::new(connection).await.map_err(subscribe_error)?;
+        let mut owner_changed = bus
+            .receive_name_owner_changed_with_args(&[(0, NOTIFICATIONS_SERVICE)])
+            .await
+            .map_err(subscribe_error)?;
+        let proxy = NotificationsProxy::new(connection).await.map_err(subscribe_error)?;
+        let mut invoked = proxy.receive_action_invoked().await.map_err(subscribe_error)?;
+        let mut closed = proxy.receive_notification_closed().await.map_err(subscribe_error)?;
+        let mut replied = proxy.receive_notification_replied().await.map_err(subscribe_error)?;
+        *attempt = 0;
+
+        loop {
+            // Signals that fail to parse are skipped
+            let (id, kind) = tokio::select! {
+                Some(signal) = owner_changed.next() => {
+                    let Ok(args) = signal.args() else { continue };
+                    return Ok(ListenerExit::OwnerChanged { had_owner: args.old_owner().is_some() });
+                }
+                Some(signal) = invoked.next() => {
+                    let Ok(args) = signal.args() else { continue };
+                    // The reply itself arrives as NotificationReplied
+                    if args.action_key() == INLINE_REPLY_ACTION {
+                        continue;
+                    }
+                    (*args.id(), PlatformInteractionKind::Action(ActionId::new(args.action_key().as_str())))
+                }
+                Some(signal) = closed.next() => {
+                    let Ok(args) = signal.args() else { continue };
+                    // Closed for any reason, so there is nothing left to update
+                    self.shown.remove(args.id());
+                    if *args.reason() != CLOSED_BY_USER {
+                        continue;
+                    }
+                    (*args.id(), PlatformInteractionKind::Dismissed)
+                }
+                Some(signal) = replied.next() => {
+                    let Ok(args) = signal.args() else { continue };
+                    (*args.id(), PlatformInteractionKind::Reply { input_id: None, text: args.text().clone() })
+                }
+                else => return Ok(ListenerExit::Disconnected),
+            };
+
+            // No subscribers is fine, the interaction is simply not observed
+            let _ = self.interactions.send(PlatformInteraction {
+                platform: Platform::Linux,
+                native_id: id.to_string(),
+                kind,
+            });
+        }
+    }
 }
 
 impl PlatformBackend for LinuxBackend {
@@ -222,34 +706,18 @@ impl PlatformBackend for LinuxBackend {
                 // Get server capabilities
                 let capabilities = self.get_capabilities().await?;
 
-                // Parse capabilities into platform features
-                let supports_actions = capabilities.contains(&"actions".to_string());
+                let (info, quirks) = self.server_profile().await;
+
+                // Parse capabilities into platform features; actions hidden in a
+                // menu don't count as shown
+                let supports_actions =
+                    capabilities.contains(&"actions".to_string()) && !quirks.actions_in_menu;
                 let supports_markup = capabilities.contains(&"body-markup".to_string())
                     || capabilities.contains(&"markup".to_string());
                 let supports_images = capabilities.contains(&"body-images".to_string());
                 let supports_sound = capabilities.contains(&"sound".to_string());
                 let supports_persistence = capabilities.contains(&"persistence".to_string());
 
-                // Get server information for version details
-                let connection = self.get_connection().await?;
-                let proxy = NotificationsProxy::new(&connection).await.map_err(|e| {
-                    crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
-                        error_code: None,
-                        message: format!("Failed to create D-Bus proxy: {:?}", e),
-                    }
-                })?;
-
-                let (server_name, vendor, version, spec_version) =
-                    proxy.get_server_information().await.unwrap_or_else(|_| {
-                        (
-                            "Unknown".to_string(),
-                            "Unknown".to_string(),
-                            "Unknown".to_string(),
-                            "1.2".to_string(),
-                        )
-                    });
-
                 Ok(PlatformCapabilities {
                     supports_actions,
                     supports_rich_media: supports_images,
@@ -259,7 +727,7 @@ impl PlatformBackend for LinuxBackend {
                                                  * scheduling */
                     supports_progress: false, // Basic D-Bus spec doesn't support progress
                     supports_categories: true, // Through hints
-                    supports_replies: false,  // Not in basic spec
+                    supports_replies: capabilities.iter().any(|c| c == INLINE_REPLY_ACTION), // KDE extension
                     supports_custom_ui: false,
                     supports_background_activation: supports_actions,
                     supports_update_content: true, // Through replaces_id
@@ -268,10 +736,10 @@ impl PlatformBackend for LinuxBackend {
                     supports_grouping: false,
                     supports_badges: false,
                     supports_vibration: false,
-                    max_actions: None,               // Server dependent
-                    max_title_length: Some(512),     // Conservative estimate
-                    max_body_length: Some(4096),     // Conservative estimate
-                    max_image_size: Some(1_048_576), // 1MB conservative estimate
+                    max_actions: quirks.max_actions, // Server dependent
+                    max_title_length: None,          // Not advertised by the spec
+                    max_body_length: None,           // Not advertised by the spec
+                    max_image_size: Some(MAX_IMAGE_SIZE),
                     max_sound_duration: None,
                     platform_features: {
                         let mut features = std::collections::HashMap::new();
@@ -284,8 +752,8 @@ impl PlatformBackend for LinuxBackend {
                     authorization_required: false, /* D-Bus notifications don't require
                                                     * authorization */
                     permission_levels: vec![PermissionLevel::Display],
-                    platform_version: Some(format!("{} {} by {}", server_name, version, vendor)),
-                    api_version: Some(format!("D-Bus Notifications Spec {}", spec_version)),
+                    platform_version: Some(format!("{} {} by {}", info.name, info.version, info.vendor)),
+                    api_version: Some(format!("D-Bus Notifications Spec {}", info.spec_version)),
                     compatibility_level: if supports_actions {
                         CompatibilityLevel::High
                     } else {
@@ -300,9 +768,11 @@ impl PlatformBackend for LinuxBackend {
             #[cfg(not(target_os = "linux"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "Linux".to_string(),
+                    platform: crate::components::Platform::Linux,
+                    kind: crate::components::ErrorKind::Unsupported,
                     error_code: None,
                     message: "Linux backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -318,78 +788,16 @@ impl PlatformBackend for LinuxBackend {
         Box::pin(async move {
             #[cfg(target_os = "linux")]
             {
-                let connection = self.get_connection().await?;
-
-                let proxy = NotificationsProxy::new(&connection).await.map_err(|e| {
-                    crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
-                        error_code: None,
-                        message: format!("Failed to create D-Bus proxy: {:?}", e),
-                    }
-                })?;
-
-                // Resolve all media images (downloads remote URLs to temp files)
-                let resolved_images = super::image_utils::resolve_media_images(&request.content.media).await;
-
-                // Extract app icon path from resolved images (first AppIcon or any image)
-                let app_icon_path = resolved_images.iter()
-                    .find(|(placement, _)| *placement == crate::components::ImagePlacement::AppIcon)
-                    .or_else(|| resolved_images.first())
-                    .map(|(_, resolved)| resolved.path.to_string_lossy().to_string())
-                    .unwrap_or_default();
+                // Replace the notification shown for the same dedup key, 0 for a new one
+                let replaces_id = request
+                    .options
+                    .replace_id
+                    .as_deref()
+                    .and_then(|id| id.parse::<u32>().ok())
+                    .unwrap_or(0);
 
-                // Create hints for the notification
-                let hints = self.create_hints(request);
-
-                // Convert actions to D-Bus format
-                let mut actions = Vec::new();
-                for action in &request.content.interactions.actions {
-                    actions.push(action.id.as_str());
-                    actions.push(&action.label);
-                }
-
-                // Set expire timeout based on priority
-                let expire_timeout = match request.options.ttl {
-                    Some(ttl) => ttl.as_millis() as i32,
-                    None => match request.content.priority {
-                        crate::components::Priority::Critical
-                        | crate::components::Priority::Urgent => 0, // Never expire
-                        crate::components::Priority::High => 10000, // 10 seconds
-                        crate::components::Priority::Normal => 5000, // 5 seconds
-                        crate::components::Priority::Low => 3000,   // 3 seconds
-                    },
-                };
-
-                // Check if server supports body-markup capability for Pango rendering
-                let capabilities = self.get_capabilities().await.unwrap_or_default();
-                let supports_markup = capabilities.contains(&"body-markup".to_string())
-                    || capabilities.contains(&"markup".to_string());
-
-                let body_text = if supports_markup {
-                    request.content.body.to_pango_markup()
-                } else {
-                    request.content.body.to_structured_plain_text()
-                };
-
-                // Send the notification with resolved app icon
                 let start_time = SystemTime::now();
-                let notification_id = proxy
-                    .notify(
-                        "KODEGEN",
-                        0,  // replaces_id - 0 for new notification
-                        &app_icon_path, // app_icon - local file path (downloaded if remote)
-                        &request.content.title,
-                        &body_text,
-                        actions.iter().map(|s| *s).collect(),
-                        hints,
-                        expire_timeout,
-                    )
-                    .await
-                    .map_err(|e| crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
-                        error_code: None,
-                        message: format!("Failed to send D-Bus notification: {:?}", e),
-                    })?;
+                let notification_id = self.show(request, replaces_id).await?;
 
                 // Create delivery receipt
                 let mut metadata = std::collections::HashMap::new();
@@ -402,10 +810,14 @@ impl PlatformBackend for LinuxBackend {
                 let delivery_latency = SystemTime::now().duration_since(start_time).unwrap_or_default();
                 
                 // Create delivery receipt using the builder pattern
-                let receipt = DeliveryReceipt::new(Platform::Linux, notification_id.to_string())
+                let mut receipt = DeliveryReceipt::new(Platform::Linux, notification_id.to_string())
                     .with_latency(delivery_latency)
                     .with_metadata("notification_id".to_string(), notification_id.to_string())
                     .with_metadata("delivery_latency_ms".to_string(), delivery_latency.as_millis().to_string());
+                let (server, _) = self.server_profile().await;
+                for (key, value) in server.receipt_metadata() {
+                    receipt = receipt.with_metadata(key, value);
+                }
 
                 Ok(receipt)
             }
@@ -413,9 +825,11 @@ impl PlatformBackend for LinuxBackend {
             #[cfg(not(target_os = "linux"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "Linux".to_string(),
+                    platform: crate::components::Platform::Linux,
+                    kind: crate::components::ErrorKind::Unsupported,
                     error_code: None,
                     message: "Linux backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -426,21 +840,13 @@ impl PlatformBackend for LinuxBackend {
         &self,
         id: &str,
         update: &NotificationUpdate,
+        app: &AppIdentity,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = NotificationResult<()>> + Send + '_>>
     {
+        let app = app.clone();
         Box::pin(async move {
             #[cfg(target_os = "linux")]
             {
-                let connection = self.get_connection().await?;
-
-                let proxy = NotificationsProxy::new(&connection).await.map_err(|e| {
-                    crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
-                        error_code: None,
-                        message: format!("Failed to create D-Bus proxy: {:?}", e),
-                    }
-                })?;
-
                 // Parse the notification ID to use as replaces_id
                 let replaces_id: u32 = id.parse().map_err(|e| {
                     crate::components::NotificationError::ValidationError {
@@ -449,78 +855,46 @@ impl PlatformBackend for LinuxBackend {
                     }
                 })?;
 
-                // Build content from either full content or content_changes map
-                let content = if let Some(ref full_content) = update.content {
-                    full_content.clone()
-                } else {
-                    // Build minimal content from content_changes
-                    let title = update.content_changes.get("title")
-                        .cloned()
-                        .unwrap_or_else(|| "Updated Notification".to_string());
-                    let body = update.content_changes.get("body")
-                        .cloned()
-                        .unwrap_or_default();
-                    crate::components::NotificationContent::new(title, body)
-                };
-
-                // Create updated notification request
-                let updated_request = NotificationRequest {
-                    notification_id: id.to_string(),
-                    content,
-                    options: update.options.clone().unwrap_or_default(),
-                    correlation_id: format!("update-{}", id),
-                };
-
-                // Create hints for the updated notification
-                let hints = self.create_hints(&updated_request);
-
-                // Convert actions to D-Bus format
-                let mut actions = Vec::new();
-                for action in &updated_request.content.interactions.actions {
-                    actions.push(action.id.as_str());
-                    actions.push(&action.title);
-                }
-
-                // Set expire timeout based on priority
-                let expire_timeout = match updated_request.options.ttl {
-                    Some(ttl) => ttl.as_millis() as i32,
-                    None => match updated_request.content.priority {
-                        crate::components::Priority::Critical
-                        | crate::components::Priority::Urgent => 0,
-                        crate::components::Priority::High => 10000,
-                        crate::components::Priority::Normal => 5000,
-                        crate::components::Priority::Low => 3000,
+                // Start from what is on screen so unchanged fields look the same
+                let shown = self.shown.get(&replaces_id).map(|shown| shown.request.clone());
+                let mut request = match shown {
+                    Some(shown) => shown,
+                    // Unknown to this backend: only a full replacement says what to show
+                    None if update.content.is_some() => NotificationRequest {
+                        notification_id: id.to_string(),
+                        content: crate::components::NotificationContent::new("", ""),
+                        options: crate::components::platform::DeliveryOptions::default(),
+                        correlation_id: format!("update-{}", id),
+                        app: app.clone(),
                     },
+                    None => {
+                        return Err(crate::components::NotificationError::ResourceError {
+                            resource_type: "notification".to_string(),
+                            resource_id: id.to_string(),
+                            kind: crate::components::ErrorKind::NotFound,
+                            message: "Not shown by this backend; updates need full content".to_string(),
+                            source: None,
+                        });
+                    }
                 };
+                update.apply_to(&mut request.content);
+                if let Some(options) = &update.options {
+                    request.options = options.clone();
+                }
+                request.app = app;
 
-                // Send the updated notification with replaces_id
-                let _new_id = proxy
-                    .notify(
-                        "ECS Notifications",
-                        replaces_id, // This replaces the existing notification
-                        "",          // app_icon
-                        &updated_request.content.title,
-                        &updated_request.content.body.to_plain_text(),
-                        actions.iter().map(|s| *s).collect(),
-                        hints,
-                        expire_timeout,
-                    )
-                    .await
-                    .map_err(|e| crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
-                        error_code: None,
-                        message: format!("Failed to update D-Bus notification: {:?}", e),
-                    })?;
-
+                self.show(&request, replaces_id).await?;
                 Ok(())
             }
 
             #[cfg(not(target_os = "linux"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "Linux".to_string(),
+                    platform: crate::components::Platform::Linux,
+                    kind: crate::components::ErrorKind::Unsupported,
                     error_code: None,
                     message: "Linux backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -530,21 +904,12 @@ impl PlatformBackend for LinuxBackend {
     fn cancel_notification(
         &self,
         id: &str,
+        app: &AppIdentity,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = NotificationResult<()>> + Send + '_>>
     {
         Box::pin(async move {
             #[cfg(target_os = "linux")]
             {
-                let connection = self.get_connection().await?;
-
-                let proxy = NotificationsProxy::new(&connection).await.map_err(|e| {
-                    crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
-                        error_code: None,
-                        message: format!("Failed to create D-Bus proxy: {:?}", e),
-                    }
-                })?;
-
                 // Parse the notification ID
                 let notification_id: u32 = id.parse().map_err(|e| {
                     crate::components::NotificationError::ValidationError {
@@ -554,24 +919,17 @@ impl PlatformBackend for LinuxBackend {
                 })?;
 
                 // Close the notification
-                proxy
-                    .close_notification(notification_id)
-                    .await
-                    .map_err(|e| crate::components::NotificationError::PlatformError {
-                        platform: "Linux".to_string(),
-                        error_code: None,
-                        message: format!("Failed to close D-Bus notification: {:?}", e),
-                    })?;
-
-                Ok(())
+                self.close(notification_id).await
             }
 
             #[cfg(not(target_os = "linux"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "Linux".to_string(),
+                    platform: crate::components::Platform::Linux,
+                    kind: crate::components::ErrorKind::Unsupported,
                     error_code: None,
                     message: "Linux backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -585,6 +943,22 @@ impl PlatformBackend for LinuxBackend {
             self.request_authorization().await
         })
     }
+
+    fn subscribe_interactions(&self) -> Option<tokio::sync::broadcast::Receiver<PlatformInteraction>> {
+        #[cfg(target_os = "linux")]
+        {
+            use std::sync::atomic::Ordering;
+
+            // Start the signal listener once, on first subscription
+            let receiver = self.interactions.subscribe();
+            if !self.listening.swap(true, Ordering::SeqCst) {
+                tokio::spawn(self.clone().watch_server());
+            }
+            Some(receiver)
+        }
+        #[cfg(not(target_os = "linux"))]
+        None
+    }
 }
 
 // Add Clone implementation for LinuxBackend
@@ -592,9 +966,14 @@ impl Clone for LinuxBackend {
     fn clone(&self) -> Self {
         Self {
             #[cfg(target_os = "linux")]
-            connection: Arc::clone(&self.connection), // Clone the Arc, share the OnceCell
+            connection: Arc::clone(&self.connection), // Clone the Arc, share the connection
+            #[cfg(target_os = "linux")]
+            server: Arc::clone(&self.server), // Clone the Arc, share the server cache
+            interactions: self.interactions.clone(),
+            #[cfg(target_os = "linux")]
+            listening: Arc::clone(&self.listening),
             #[cfg(target_os = "linux")]
-            capabilities: Arc::clone(&self.capabilities), // Clone the Arc, share the OnceCell
+            shown: Arc::clone(&self.shown),
             permission_manager: PermissionManager::new(),
         }
     }
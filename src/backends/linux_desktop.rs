@@ -0,0 +1,293 @@
+// Desktop entry and icon installation for Linux notification daemons
+// Daemons look up the app's name and icon from the `.desktop` file named by the
+// `desktop-entry` hint, so the entry is installed into $XDG_DATA_HOME/applications
+// with hicolor icons taken from the bundle assets. The entry can also claim a
+// `x-scheme-handler/<scheme>` MIME type so notification deep links open the app,
+// the Linux counterpart of the bundle setup in macos_bundle.rs
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// `identifier` from `[package.metadata.bundle]`, also the desktop entry ID
+pub const BUNDLE_IDENTIFIER: &str = "ai.kodegen.kodegen";
+/// `name` from `[package.metadata.bundle]`
+pub const BUNDLE_NAME: &str = "KODEGEN.ᴀɪ";
+/// Linux `category` from `[package.metadata.bundle]`
+const BUNDLE_CATEGORY: &str = "Development";
+
+/// Bundle icons (`assets/img/icon_*.png`) by pixel size
+const BUNDLE_ICONS: &[(u32, &[u8])] = &[
+    (16, include_bytes!("../../assets/img/icon_16x16.png")),
+    (32, include_bytes!("../../assets/img/icon_32x32.png")),
+    (128, include_bytes!("../../assets/img/icon_128x128.png")),
+    (256, include_bytes!("../../assets/img/icon_256x256.png")),
+    (512, include_bytes!("../../assets/img/icon_512x512.png")),
+];
+
+/// Application registration for the desktop and its notification daemon
+#[derive(Debug, Clone)]
+pub struct DesktopEntry {
+    /// Desktop entry ID: the file name without `.desktop`, and the icon name
+    pub app_id: String,
+    pub name: String,
+    pub comment: String,
+    /// Program launched by the entry and by deep links
+    pub exec: PathBuf,
+    pub categories: Vec<String>,
+    /// URL schemes routed to the app through `x-scheme-handler` MIME types
+    pub url_schemes: Vec<String>,
+    /// Icons by pixel size, installed under `hicolor/<size>x<size>/apps`
+    pub icons: Vec<(u32, &'static [u8])>,
+}
+
+/// Files written by [`DesktopEntry::install_into`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledDesktopEntry {
+    pub desktop_file: PathBuf,
+    pub icon_files: Vec<PathBuf>,
+    /// Whether any file was created or changed; `false` when already up to date
+    pub changed: bool,
+}
+
+impl DesktopEntry {
+    /// Entry described by the crate's bundle metadata, launching the current executable
+    pub fn from_bundle() -> Self {
+        Self {
+            app_id: BUNDLE_IDENTIFIER.to_string(),
+            name: BUNDLE_NAME.to_string(),
+            comment: env!("CARGO_PKG_DESCRIPTION").to_string(),
+            exec: std::env::current_exe().unwrap_or_else(|_| PathBuf::from(env!("CARGO_PKG_NAME"))),
+            categories: vec![BUNDLE_CATEGORY.to_string()],
+            url_schemes: Vec::new(),
+            icons: BUNDLE_ICONS.to_vec(),
+        }
+    }
+
+    /// Route `<scheme>:` links to the app
+    pub fn with_url_scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.url_schemes.push(scheme.into().to_ascii_lowercase());
+        self
+    }
+
+    pub fn with_exec(mut self, exec: impl Into<PathBuf>) -> Self {
+        self.exec = exec.into();
+        self
+    }
+
+    /// File name of the entry, e.g. `ai.kodegen.kodegen.desktop`
+    pub fn file_name(&self) -> String {
+        format!("{}.desktop", self.app_id)
+    }
+
+    /// Contents of the `.desktop` file
+    pub fn render(&self) -> String {
+        let mut exec = quote_exec_arg(&self.exec.to_string_lossy());
+        if !self.url_schemes.is_empty() {
+            // The link is passed as the first argument
+            exec.push_str(" %u");
+        }
+
+        let mut entry = String::from("[Desktop Entry]\nType=Application\nVersion=1.5\n");
+        entry.push_str(&format!("Name={}\n", escape_value(&self.name)));
+        if !self.comment.is_empty() {
+            entry.push_str(&format!("Comment={}\n", escape_value(&self.comment)));
+        }
+        entry.push_str(&format!("Exec={}\n", exec));
+        entry.push_str(&format!("Icon={}\n", self.app_id));
+        if !self.categories.is_empty() {
+            entry.push_str(&format!("Categories={};\n", self.categories.join(";")));
+        }
+        if !self.url_schemes.is_empty() {
+            let mime_types: Vec<_> = self
+                .url_schemes
+                .iter()
+                .map(|scheme| format!("x-scheme-handler/{};", scheme))
+                .collect();
+            entry.push_str(&format!("MimeType={}\n", mime_types.concat()));
+        }
+        // Launched by the daemon and by links, not meant for the app menu
+        entry.push_str("NoDisplay=true\nTerminal=false\n");
+        // Lets GNOME group and attribute notifications without a running window
+        entry.push_str("X-GNOME-UsesNotifications=true\n");
+        entry
+    }
+
+    /// Install into `$XDG_DATA_HOME` and refresh the desktop's caches
+    ///
+    /// Also makes the app the default handler of its URL schemes. Cache and
+    /// handler updates are best-effort, since their tools may not be installed.
+    pub fn install(&self) -> Result<InstalledDesktopEntry, DesktopEntryError> {
+        let data_home = data_home().ok_or(DesktopEntryError::NoDataHome)?;
+        let installed = self.install_into(&data_home)?;
+        if !installed.changed {
+            return Ok(installed);
+        }
+
+        refresh_caches(&data_home);
+        for scheme in &self.url_schemes {
+            let mime_type = format!("x-scheme-handler/{}", scheme);
+            if let Err(e) = run_tool(Command::new("xdg-mime").args(["default", &self.file_name(), &mime_type])) {
+                ::tracing::debug!("Could not make {} the handler of {}: {}", self.app_id, mime_type, e);
+            }
+        }
+        Ok(installed)
+    }
+
+    /// Write the entry and icons below `data_home`, leaving unchanged files alone
+    pub fn install_into(&self, data_home: &Path) -> Result<InstalledDesktopEntry, DesktopEntryError> {
+        self.validate()?;
+        let mut changed = false;
+
+        let desktop_file = data_home.join("applications").join(self.file_name());
+        changed |= write_if_changed(&desktop_file, self.render().as_bytes())?;
+
+        let mut icon_files = Vec::new();
+        for (size, png) in &self.icons {
+            let icon_file = data_home
+                .join("icons/hicolor")
+                .join(format!("{size}x{size}"))
+                .join("apps")
+                .join(format!("{}.png", self.app_id));
+            changed |= write_if_changed(&icon_file, png)?;
+            icon_files.push(icon_file);
+        }
+
+        Ok(InstalledDesktopEntry {
+            desktop_file,
+            icon_files,
+            changed,
+        })
+    }
+
+    fn validate(&self) -> Result<(), DesktopEntryError> {
+        // Desktop entry IDs become file names and D-Bus-style names
+        let valid_id = !self.app_id.is_empty()
+            && self
+                .app_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+        if !valid_id {
+            return Err(DesktopEntryError::InvalidAppId(self.app_id.clone()));
+        }
+        // RFC 3986: ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )
+        for scheme in &self.url_schemes {
+            let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+            if !valid_scheme {
+                return Err(DesktopEntryError::InvalidScheme(scheme.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `$XDG_DATA_HOME`, defaulting to `~/.local/share`
+fn data_home() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+}
+
+/// Write `contents` unless the file already has them; returns whether it wrote
+fn write_if_changed(path: &Path, contents: &[u8]) -> Result<bool, DesktopEntryError> {
+    if std::fs::read(path).is_ok_and(|existing| existing == contents) {
+        return Ok(false);
+    }
+    let io_error = |source| DesktopEntryError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    // Written beside the target and renamed so readers never see a partial file
+    let temporary = path.with_extension("tmp");
+    std::fs::write(&temporary, contents).map_err(io_error)?;
+    std::fs::rename(&temporary, path).map_err(io_error)?;
+    Ok(true)
+}
+
+/// Update the MIME handler and icon caches so the new files are picked up
+fn refresh_caches(data_home: &Path) {
+    let applications = data_home.join("applications");
+    if let Err(e) = run_tool(Command::new("update-desktop-database").arg(&applications)) {
+        ::tracing::debug!("Could not update desktop database: {}", e);
+    }
+    let hicolor = data_home.join("icons/hicolor");
+    if let Err(e) = run_tool(Command::new("gtk-update-icon-cache").args(["-f", "-t"]).arg(&hicolor)) {
+        ::tracing::debug!("Could not update icon cache: {}", e);
+    }
+}
+
+fn run_tool(command: &mut Command) -> std::io::Result<()> {
+    let status = command.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("exited with {}", status)))
+    }
+}
+
+/// Escape a string value per the Desktop Entry spec
+fn escape_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+}
+
+/// Quote an `Exec` argument if needed, escaping the spec's reserved characters
+fn quote_exec_arg(arg: &str) -> String {
+    let reserved = |c: char| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c);
+    // `%` introduces field codes anywhere, quoted or not
+    let arg = arg.replace('%', "%%");
+    let quoted = if arg.contains(reserved) {
+        let mut quoted = String::from("\"");
+        for c in arg.chars() {
+            if matches!(c, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    } else {
+        arg
+    };
+    // Exec is itself a string value, so its backslashes are escaped once more
+    escape_value(&quoted)
+}
+
+/// Errors that can occur while installing the desktop entry
+#[derive(Debug)]
+pub enum DesktopEntryError {
+    /// Neither `$XDG_DATA_HOME` nor `$HOME` is set
+    NoDataHome,
+    InvalidAppId(String),
+    InvalidScheme(String),
+    Io { path: PathBuf, source: std::io::Error },
+}
+
+impl std::fmt::Display for DesktopEntryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoDataHome => write!(f, "Neither XDG_DATA_HOME nor HOME is set"),
+            Self::InvalidAppId(id) => write!(f, "Invalid desktop entry ID: {}", id),
+            Self::InvalidScheme(scheme) => write!(f, "Invalid URL scheme: {}", scheme),
+            Self::Io { path, source } => write!(f, "Failed to write {}: {}", path.display(), source),
+        }
+    }
+}
+
+impl std::error::Error for DesktopEntryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
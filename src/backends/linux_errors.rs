@@ -0,0 +1,89 @@
+// Classification of D-Bus errors
+// Error replies carry a name such as `org.freedesktop.DBus.Error.ServiceUnknown`;
+// each known name maps to a stable `error_code` and an `ErrorKind`, so callers
+// can tell a missing notification daemon from a rejected request without
+// parsing messages. zbus errors keep the original as the error's `source()`.
+
+use crate::components::ErrorKind;
+
+/// Known D-Bus error names with their stable `error_code` and kind
+pub const DBUS_ERROR_CODES: &[(&str, i32, ErrorKind)] = &[
+    ("org.freedesktop.DBus.Error.Failed", 1000, ErrorKind::Other),
+    ("org.freedesktop.DBus.Error.ServiceUnknown", 1001, ErrorKind::ServiceUnavailable),
+    ("org.freedesktop.DBus.Error.NameHasNoOwner", 1002, ErrorKind::ServiceUnavailable),
+    ("org.freedesktop.DBus.Error.NoServer", 1003, ErrorKind::ServiceUnavailable),
+    ("org.freedesktop.DBus.Error.Spawn.Failed", 1004, ErrorKind::ServiceUnavailable),
+    ("org.freedesktop.DBus.Error.NoReply", 1010, ErrorKind::Timeout),
+    ("org.freedesktop.DBus.Error.Timeout", 1011, ErrorKind::Timeout),
+    ("org.freedesktop.DBus.Error.TimedOut", 1012, ErrorKind::Timeout),
+    ("org.freedesktop.DBus.Error.Disconnected", 1020, ErrorKind::Connection),
+    ("org.freedesktop.DBus.Error.NoNetwork", 1021, ErrorKind::Connection),
+    ("org.freedesktop.DBus.Error.IOError", 1022, ErrorKind::Connection),
+    ("org.freedesktop.DBus.Error.LimitsExceeded", 1030, ErrorKind::LimitsExceeded),
+    ("org.freedesktop.DBus.Error.NoMemory", 1031, ErrorKind::LimitsExceeded),
+    ("org.freedesktop.DBus.Error.AccessDenied", 1040, ErrorKind::PermissionDenied),
+    ("org.freedesktop.DBus.Error.AuthFailed", 1041, ErrorKind::PermissionDenied),
+    ("org.freedesktop.DBus.Error.InvalidArgs", 1050, ErrorKind::InvalidRequest),
+    ("org.freedesktop.DBus.Error.InvalidSignature", 1051, ErrorKind::InvalidRequest),
+    ("org.freedesktop.DBus.Error.UnknownMethod", 1060, ErrorKind::Unsupported),
+    ("org.freedesktop.DBus.Error.UnknownInterface", 1061, ErrorKind::Unsupported),
+    ("org.freedesktop.DBus.Error.UnknownObject", 1062, ErrorKind::Unsupported),
+    ("org.freedesktop.DBus.Error.UnknownProperty", 1063, ErrorKind::Unsupported),
+    ("org.freedesktop.DBus.Error.NotSupported", 1064, ErrorKind::Unsupported),
+    ("org.freedesktop.DBus.Error.FileNotFound", 1070, ErrorKind::NotFound),
+];
+
+/// Stable code and kind of a D-Bus error name, if it's a known one
+pub fn dbus_error_code(name: &str) -> Option<(i32, ErrorKind)> {
+    DBUS_ERROR_CODES
+        .iter()
+        .find(|(known, _, _)| *known == name)
+        .map(|(_, code, kind)| (*code, *kind))
+}
+
+/// `NotificationError` for a failed D-Bus call, classified by the error's name
+#[cfg(target_os = "linux")]
+pub(crate) fn from_zbus(context: &str, error: zbus::Error) -> crate::components::NotificationError {
+    use zbus::DBusError;
+
+    let name = match &error {
+        zbus::Error::MethodError(name, _, _) => Some(name.to_string()),
+        zbus::Error::FDO(fdo) => Some(fdo.name().to_string()),
+        _ => None,
+    };
+    let (error_code, kind) = match name.as_deref().and_then(dbus_error_code) {
+        Some((code, kind)) => (Some(code), kind),
+        None => (None, zbus_error_kind(&error)),
+    };
+    let message = match (&name, &error) {
+        (Some(name), zbus::Error::MethodError(_, Some(description), _)) => {
+            format!("{}: {} ({})", context, description, name)
+        }
+        (Some(name), _) => format!("{}: {}", context, name),
+        (None, _) => format!("{}: {}", context, error),
+    };
+    crate::components::NotificationError::PlatformError {
+        platform: crate::components::Platform::Linux,
+        kind,
+        error_code,
+        message,
+        source: Some(error.into()),
+    }
+}
+
+/// Kind of a zbus error that isn't a D-Bus error reply, or isn't a known one
+#[cfg(target_os = "linux")]
+fn zbus_error_kind(error: &zbus::Error) -> ErrorKind {
+    match error {
+        zbus::Error::InputOutput(_) | zbus::Error::Connection(_, _) | zbus::Error::Handshake(_) => {
+            ErrorKind::Connection
+        }
+        zbus::Error::Address(_) => ErrorKind::ServiceUnavailable,
+        zbus::Error::Unsupported | zbus::Error::InterfaceNotFound => ErrorKind::Unsupported,
+        zbus::Error::Variant(_)
+        | zbus::Error::Names(_)
+        | zbus::Error::ExcessData
+        | zbus::Error::MissingParameter(_) => ErrorKind::InvalidRequest,
+        _ => ErrorKind::Other,
+    }
+}
@@ -0,0 +1,200 @@
+// Notification server identification and quirk profiles
+// Servers implement the freedesktop spec unevenly: GNOME Shell ignores
+// `expire_timeout`, mako only shows actions through `makoctl menu`, dunst moves
+// links into its context menu and KDE Plasma takes attachments through
+// `x-kde-urls`. The profile picked from `GetServerInformation` adjusts what the
+// Linux backend advertises and sends, and can be overridden process-wide
+
+use std::sync::{Arc, LazyLock};
+
+use crate::components::PangoSubset;
+
+/// `GetServerInformation` reply of the notification server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerInfo {
+    pub name: String,
+    pub vendor: String,
+    pub version: String,
+    /// Version of the notification spec the server implements
+    pub spec_version: String,
+}
+
+impl ServerInfo {
+    pub fn new(
+        name: impl Into<String>,
+        vendor: impl Into<String>,
+        version: impl Into<String>,
+        spec_version: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            vendor: vendor.into(),
+            version: version.into(),
+            spec_version: spec_version.into(),
+        }
+    }
+
+    /// Stand-in for a server that didn't answer
+    pub fn unknown() -> Self {
+        Self::new("Unknown", "Unknown", "Unknown", "1.2")
+    }
+
+    /// Known server implementation, matched by name and vendor
+    pub fn kind(&self) -> ServerKind {
+        let name = self.name.to_ascii_lowercase();
+        let vendor = self.vendor.to_ascii_lowercase();
+        if name == "gnome-shell" || vendor == "gnome" {
+            ServerKind::Gnome
+        } else if name == "plasma" || vendor == "kde" {
+            ServerKind::Kde
+        } else if name == "mako" {
+            ServerKind::Mako
+        } else if name == "dunst" {
+            ServerKind::Dunst
+        } else {
+            ServerKind::Other
+        }
+    }
+
+    /// Entries describing the server in `DeliveryReceipt::metadata`
+    pub fn receipt_metadata(&self) -> [(String, String); 4] {
+        [
+            ("server_name".to_string(), self.name.clone()),
+            ("server_vendor".to_string(), self.vendor.clone()),
+            ("server_version".to_string(), self.version.clone()),
+            ("server_spec_version".to_string(), self.spec_version.clone()),
+        ]
+    }
+}
+
+/// Notification servers with a built-in quirk profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerKind {
+    Gnome,
+    Kde,
+    Mako,
+    Dunst,
+    Other,
+}
+
+/// How a notification server deviates from what it advertises
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerQuirks {
+    /// The server ignores `expire_timeout`, so the backend closes notifications itself
+    pub enforce_timeout: bool,
+    /// Tags the body is rendered with; links are dropped anyway without `body-hyperlinks`
+    pub markup: PangoSubset,
+    /// Most action buttons shown; further actions are not sent
+    pub max_actions: Option<usize>,
+    /// Actions are only reachable through a menu the user opens, not as buttons
+    pub actions_in_menu: bool,
+    /// Attached files are passed in the `x-kde-urls` hint
+    pub kde_urls: bool,
+    /// Capabilities the server honours without advertising them
+    pub extra_capabilities: Vec<String>,
+    /// Capabilities the server advertises without honouring them
+    pub ignored_capabilities: Vec<String>,
+}
+
+impl Default for ServerQuirks {
+    /// A server that does what it advertises
+    fn default() -> Self {
+        Self {
+            enforce_timeout: false,
+            markup: PangoSubset::FULL,
+            max_actions: None,
+            actions_in_menu: false,
+            kde_urls: false,
+            extra_capabilities: Vec::new(),
+            ignored_capabilities: Vec::new(),
+        }
+    }
+}
+
+impl ServerQuirks {
+    /// Built-in profile of the server
+    pub fn for_server(info: &ServerInfo) -> Self {
+        match info.kind() {
+            // Escapes everything but `b`, `i` and `u`, shows at most three buttons
+            ServerKind::Gnome => Self {
+                enforce_timeout: true,
+                markup: PangoSubset {
+                    hyperlinks: false,
+                    extended: false,
+                },
+                max_actions: Some(3),
+                ..Self::default()
+            },
+            // Qt rich text: spec tags and links, but not Pango's extensions
+            ServerKind::Kde => Self {
+                markup: PangoSubset::SPEC,
+                kde_urls: true,
+                ..Self::default()
+            },
+            // Actions need `makoctl menu` or a binding, links aren't rendered
+            ServerKind::Mako => Self {
+                markup: PangoSubset {
+                    hyperlinks: false,
+                    extended: true,
+                },
+                actions_in_menu: true,
+                ..Self::default()
+            },
+            // Strips `<a>` from the text and lists the URLs in its context menu,
+            // so the URL stays readable in the body
+            ServerKind::Dunst => Self {
+                markup: PangoSubset {
+                    hyperlinks: false,
+                    extended: true,
+                },
+                ..Self::default()
+            },
+            ServerKind::Other => Self::default(),
+        }
+    }
+
+    /// Capabilities as the server actually behaves
+    pub fn adjust_capabilities(&self, advertised: Vec<String>) -> Vec<String> {
+        let mut capabilities: Vec<String> = advertised
+            .into_iter()
+            .filter(|capability| !self.ignored_capabilities.contains(capability))
+            .collect();
+        for capability in &self.extra_capabilities {
+            if !capabilities.contains(capability) {
+                capabilities.push(capability.clone());
+            }
+        }
+        capabilities
+    }
+}
+
+/// Adjusts the detected profile of a server
+type QuirkOverride = Arc<dyn Fn(&ServerInfo, &mut ServerQuirks) + Send + Sync>;
+
+static QUIRK_OVERRIDE: LazyLock<parking_lot::RwLock<Option<QuirkOverride>>> =
+    LazyLock::new(|| parking_lot::RwLock::new(None));
+
+/// Adjust every server's profile process-wide, e.g. for a locally configured mako
+///
+/// Applies to servers identified afterwards; a server already identified keeps
+/// its profile until it restarts.
+pub fn set_quirk_override(
+    adjust: impl Fn(&ServerInfo, &mut ServerQuirks) + Send + Sync + 'static,
+) {
+    *QUIRK_OVERRIDE.write() = Some(Arc::new(adjust));
+}
+
+/// Go back to the built-in profiles
+pub fn clear_quirk_override() {
+    *QUIRK_OVERRIDE.write() = None;
+}
+
+/// Profile of the server: the built-in one with the process-wide override applied
+pub fn server_quirks(info: &ServerInfo) -> ServerQuirks {
+    let mut quirks = ServerQuirks::for_server(info);
+    let adjust = QUIRK_OVERRIDE.read().clone();
+    if let Some(adjust) = adjust {
+        adjust(info, &mut quirks);
+    }
+    quirks
+}
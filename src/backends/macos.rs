@@ -20,12 +20,16 @@ use objc2_user_notifications::{
 
 use kodegen_native_permissions::{PermissionManager, PermissionStatus, PermissionType};
 
-use crate::components::NotificationResult;
+use crate::components::{ErrorKind, NotificationResult};
 use crate::components::platform::{
-    DeliveryReceipt, NotificationRequest, NotificationUpdate, Platform, PlatformBackend,
-    PlatformCapabilities,
+    AppIdentity, DeliveryReceipt, NotificationRequest, NotificationUpdate, Platform,
+    PlatformBackend, PlatformCapabilities,
 };
 
+/// Largest image attachment handed to UserNotifications (10MB)
+#[cfg(target_os = "macos")]
+const MAX_IMAGE_SIZE: usize = 10_485_760;
+
 // =============================================================================
 // Block Helper Functions
 // =============================================================================
@@ -121,7 +125,9 @@ impl MacOSBackend {
         {
             // Ensure bundle ID is set BEFORE any UNUserNotificationCenter calls
             // This is a synchronous operation using private API (no async/await needed)
-            if let Err(e) = crate::backends::macos_bundle::ensure_bundle_identifier() {
+            // The bundle ID is per process, so other app identities only group separately
+            let bundle_id = AppIdentity::default().app_id;
+            if let Err(e) = crate::backends::macos_bundle::ensure_bundle_identifier(&bundle_id) {
                 tracing::warn!("Failed to ensure bundle identifier: {}", e);
             }
         }
@@ -192,9 +198,11 @@ impl MacOSBackend {
                 Ok(PermissionStatus::Authorized) => Ok(true),
                 Ok(_) => Ok(false),
                 Err(e) => Err(crate::components::NotificationError::PlatformError {
-                    platform: "macOS".to_string(),
+                    platform: Platform::MacOS,
+                    kind: super::permission_error_kind(&e),
                     error_code: None,
                     message: format!("Permission check failed: {}", e),
+                    source: None,
                 }),
             }
         }
@@ -220,9 +228,11 @@ impl MacOSBackend {
                         }
                         Err(e) => {
                             Err(crate::components::NotificationError::PlatformError {
-                                platform: "macOS".to_string(),
+                                platform: Platform::MacOS,
+                                kind: super::permission_error_kind(&e),
                                 error_code: None,
                                 message: format!("Permission request failed: {}", e),
+                                source: None,
                             })
                         }
                     }
@@ -234,9 +244,11 @@ impl MacOSBackend {
                 }
                 Err(e) => {
                     Err(crate::components::NotificationError::PlatformError {
-                        platform: "macOS".to_string(),
+                        platform: Platform::MacOS,
+                        kind: super::permission_error_kind(&e),
                         error_code: None,
                         message: format!("Permission check failed: {}", e),
+                        source: None,
                     })
                 }
             }
@@ -280,7 +292,7 @@ impl PlatformBackend for MacOSBackend {
                     max_actions: Some(4),
                     max_title_length: Some(256),
                     max_body_length: Some(2048),
-                    max_image_size: Some(10_485_760), // 10MB
+                    max_image_size: Some(MAX_IMAGE_SIZE),
                     max_sound_duration: Some(std::time::Duration::from_secs(30)),
                     platform_features: HashMap::new(),
                     platform_limits: HashMap::new(),
@@ -304,9 +316,11 @@ impl PlatformBackend for MacOSBackend {
             #[cfg(not(target_os = "macos"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "macOS".to_string(),
+                    platform: Platform::MacOS,
+                    kind: ErrorKind::Unsupported,
                     error_code: None,
                     message: "macOS backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -327,7 +341,7 @@ impl PlatformBackend for MacOSBackend {
                 let is_authorized = self.request_authorization().await?;
                 if !is_authorized {
                     return Err(crate::components::NotificationError::AuthorizationError {
-                        platform: "macOS".to_string(),
+                        platform: Platform::MacOS,
                         required_permission: "Notification permission denied or restricted. Please enable notifications in System Settings > Notifications.".to_string(),
                     });
                 }
@@ -349,8 +363,17 @@ impl PlatformBackend for MacOSBackend {
                 let (completion_tx, completion_rx) = oneshot::channel();
                 let completion_tx = Arc::new(Mutex::new(Some(completion_tx)));
 
+                // A newer notification for the same dedup key replaces the shown one
+                if let Some(replace_id) = &request.options.replace_id {
+                    Self::cancel_notification_sync(replace_id);
+                }
+
                 // Resolve images BEFORE entering the non-Send block (downloads remote URLs)
-                let resolved_images = super::image_utils::resolve_media_images(&request.content.media).await;
+                let resolved_images = super::image_processing::resolve_media_images_for(
+                    &request.content.media,
+                    Platform::MacOS,
+                    Some(MAX_IMAGE_SIZE),
+                ).await;
 
                 // No spawn needed - these are quick synchronous calls
                 // Scope the block so it's dropped before await (block is not Send)
@@ -374,6 +397,10 @@ impl PlatformBackend for MacOSBackend {
                     let body_ns = NSString::from_str(&request_body);
                     content.setBody(&body_ns);
 
+                    // Group by app identity, since all identities share the process's bundle ID
+                    let thread_ns = NSString::from_str(&request.app.app_id);
+                    content.setThreadIdentifier(&thread_ns);
+
                     // Set default sound
                     let default_sound = objc2_user_notifications::UNNotificationSound::defaultSound();
                     content.setSound(Some(&default_sound));
@@ -453,9 +480,11 @@ impl PlatformBackend for MacOSBackend {
                             // Notification delivery failed - cancel to prevent ghost notifications
                             Self::cancel_notification_sync(&notification_id);
                             return Err(crate::components::NotificationError::PlatformError {
-                                platform: "macOS".to_string(),
+                                platform: Platform::MacOS,
+                                kind: ErrorKind::Other,
                                 error_code: None,
                                 message: "Notification delivery failed - Apple API returned error".to_string(),
+                                source: None,
                             });
                         },
                         Ok(Err(_)) => {
@@ -463,9 +492,11 @@ impl PlatformBackend for MacOSBackend {
                             // Cancel to ensure no ghost notifications
                             Self::cancel_notification_sync(&notification_id);
                             return Err(crate::components::NotificationError::PlatformError {
-                                platform: "macOS".to_string(),
+                                platform: Platform::MacOS,
+                                kind: ErrorKind::Other,
                                 error_code: None,
                                 message: "Completion callback channel dropped - callback never fired or panicked".to_string(),
+                                source: None,
                             });
                         },
                         Err(_elapsed) => {
@@ -477,12 +508,14 @@ impl PlatformBackend for MacOSBackend {
                             );
                             Self::cancel_notification_sync(&notification_id);
                             return Err(crate::components::NotificationError::PlatformError {
-                                platform: "macOS".to_string(),
+                                platform: Platform::MacOS,
+                                kind: ErrorKind::Timeout,
                                 error_code: Some(408), // HTTP 408 Request Timeout semantics
                                 message: format!(
                                     "Notification delivery timeout after {}ms - request cancelled",
                                     timeout_duration.as_millis()
                                 ),
+                                source: None,
                             });
                         },
                     };
@@ -499,9 +532,11 @@ impl PlatformBackend for MacOSBackend {
                 } else {
                     // This should never be reached due to earlier error handling
                     Err(crate::components::NotificationError::PlatformError {
-                        platform: "macOS".to_string(),
+                        platform: Platform::MacOS,
+                        kind: ErrorKind::Other,
                         error_code: None,
                         message: "Unexpected delivery failure".to_string(),
+                        source: None,
                     })
                 }
             }
@@ -509,9 +544,11 @@ impl PlatformBackend for MacOSBackend {
             #[cfg(not(target_os = "macos"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "macOS".to_string(),
+                    platform: Platform::MacOS,
+                    kind: ErrorKind::Unsupported,
                     error_code: None,
                     message: "macOS backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -522,6 +559,7 @@ impl PlatformBackend for MacOSBackend {
         &self,
         id: &str,
         update: &NotificationUpdate,
+        _app: &AppIdentity,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = NotificationResult<()>> + Send + '_>>
     {
         let id = id.to_string();
@@ -632,18 +670,22 @@ impl PlatformBackend for MacOSBackend {
                         // Notification update failed - cancel to prevent ghost notifications
                         Self::cancel_notification_sync(&error_notification_id);
                         Err(crate::components::NotificationError::PlatformError {
-                            platform: "macOS".to_string(),
+                            platform: Platform::MacOS,
+                            kind: ErrorKind::Other,
                             error_code: None,
                             message: "Notification update failed - Apple API returned error".to_string(),
+                            source: None,
                         })
                     },
                     Ok(Err(_)) => {
                         // Channel dropped - callback never fired or panicked
                         Self::cancel_notification_sync(&error_notification_id);
                         Err(crate::components::NotificationError::PlatformError {
-                            platform: "macOS".to_string(),
+                            platform: Platform::MacOS,
+                            kind: ErrorKind::Other,
                             error_code: None,
                             message: "Completion callback channel dropped - callback never fired or panicked".to_string(),
+                            source: None,
                         })
                     },
                     Err(_elapsed) => {
@@ -655,12 +697,14 @@ impl PlatformBackend for MacOSBackend {
                         );
                         Self::cancel_notification_sync(&error_notification_id);
                         Err(crate::components::NotificationError::PlatformError {
-                            platform: "macOS".to_string(),
+                            platform: Platform::MacOS,
+                            kind: ErrorKind::Timeout,
                             error_code: Some(408), // HTTP 408 Request Timeout semantics
                             message: format!(
                                 "Notification update timeout after {}ms - request cancelled",
                                 timeout_duration.as_millis()
                             ),
+                            source: None,
                         })
                     },
                 }
@@ -669,9 +713,11 @@ impl PlatformBackend for MacOSBackend {
             #[cfg(not(target_os = "macos"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "macOS".to_string(),
+                    platform: Platform::MacOS,
+                    kind: ErrorKind::Unsupported,
                     error_code: None,
                     message: "macOS backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -681,6 +727,7 @@ impl PlatformBackend for MacOSBackend {
     fn cancel_notification(
         &self,
         id: &str,
+        _app: &AppIdentity,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = NotificationResult<()>> + Send + '_>>
     {
         let id = id.to_string();
@@ -704,9 +751,11 @@ impl PlatformBackend for MacOSBackend {
             #[cfg(not(target_os = "macos"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "macOS".to_string(),
+                    platform: Platform::MacOS,
+                    kind: ErrorKind::Unsupported,
                     error_code: None,
                     message: "macOS backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -18,10 +18,10 @@ use std::ffi::c_void;
 /// and this function returns it immediately.
 ///
 /// **Development**: If running from cargo run/test without a bundle, this uses the
-/// private API `_LSSetApplicationInformationItem` to set the bundle ID in memory.
+/// private API `_LSSetApplicationInformationItem` to set `bundle_id` in memory.
 ///
 /// Returns the bundle identifier string on success.
-pub fn ensure_bundle_identifier() -> Result<String, BundleError> {
+pub fn ensure_bundle_identifier(bundle_id: &str) -> Result<String, BundleError> {
     // Check if bundle ID already exists (production .app bundles)
     let main_bundle = NSBundle::mainBundle();
     if let Some(existing_id) = main_bundle.bundleIdentifier() {
@@ -29,7 +29,6 @@ pub fn ensure_bundle_identifier() -> Result<String, BundleError> {
     }
 
     // Development path: Set bundle ID using private API
-    let bundle_id = "ai.kodegen.notify";
     set_bundle_identifier_via_private_api(bundle_id)?;
 
     // Verify it worked
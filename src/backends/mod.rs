@@ -1,20 +1,86 @@
 // Real platform backends - complete implementations
 // Each backend provides actual platform integration
 
+pub mod icon_theme;
+pub mod image_cache;
+pub mod image_processing;
 pub mod image_utils;
 pub mod linux;
+pub mod linux_desktop;
+pub mod linux_errors;
+pub mod linux_quirks;
 pub mod macos;
+pub mod opener;
+pub mod sound_theme;
 pub mod windows;
 
 #[cfg(target_os = "macos")]
 pub mod macos_bundle;
 
-pub use image_utils::{resolve_image_to_path, resolve_media_images, ResolvedImage, cleanup_temp_image, cleanup_all_cached_images};
+pub use image_utils::{resolve_image_to_path, resolve_media_images, resolve_action_icon, ResolvedImage, cleanup_temp_image, cleanup_all_cached_images, ImageFetchPolicy, image_fetch_policy, set_image_fetch_policy};
+pub use icon_theme::{IconLookup, icon_lookup, lookup_icon, set_icon_lookup};
+pub use image_cache::{DiskImageCache, disk_image_cache, set_disk_image_cache};
+pub use sound_theme::{SoundHint, SoundLookup, lookup_sound, resolve_sound_hint, set_sound_lookup, sound_lookup, xdg_sound_name};
+pub use linux_desktop::{BUNDLE_IDENTIFIER, BUNDLE_NAME, DesktopEntry, DesktopEntryError, InstalledDesktopEntry};
+pub use linux_errors::{DBUS_ERROR_CODES, dbus_error_code};
+pub use linux_quirks::{ServerInfo, ServerKind, ServerQuirks, clear_quirk_override, server_quirks, set_quirk_override};
+pub use opener::{open_path, open_url, reveal_path};
+pub use image_processing::{ImageTarget, ProcessedImage, process_image, resolve_media_images_for};
 
 use std::collections::HashMap;
 
+use crate::components::ErrorKind;
 use crate::components::platform::{Platform, PlatformBackend};
 
+/// Kind of a failed permission check or request
+pub(crate) fn permission_error_kind(error: &kodegen_native_permissions::PermissionError) -> ErrorKind {
+    use kodegen_native_permissions::PermissionError;
+    match error {
+        PermissionError::Denied | PermissionError::Restricted | PermissionError::Cancelled => {
+            ErrorKind::PermissionDenied
+        }
+        PermissionError::SystemError(_) => ErrorKind::ServiceUnavailable,
+        PermissionError::PlatformError(_) | PermissionError::Unknown => ErrorKind::Other,
+    }
+}
+
+/// Kind of a failed file operation
+pub(crate) fn io_error_kind(error: &std::io::Error) -> ErrorKind {
+    match error.kind() {
+        std::io::ErrorKind::NotFound => ErrorKind::NotFound,
+        std::io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+        std::io::ErrorKind::StorageFull | std::io::ErrorKind::QuotaExceeded => ErrorKind::LimitsExceeded,
+        _ => ErrorKind::Other,
+    }
+}
+
+/// Kind of a failed HTTP request: timeouts and connection failures are worth retrying
+pub(crate) fn reqwest_error_kind(error: &reqwest::Error) -> ErrorKind {
+    if error.is_timeout() {
+        ErrorKind::Timeout
+    } else if error.is_connect() {
+        ErrorKind::Connection
+    } else if let Some(status) = error.status() {
+        http_status_kind(status)
+    } else if error.is_redirect() || error.is_builder() {
+        ErrorKind::InvalidRequest
+    } else {
+        ErrorKind::Other
+    }
+}
+
+/// Kind of an unsuccessful HTTP status
+pub(crate) fn http_status_kind(status: reqwest::StatusCode) -> ErrorKind {
+    match status {
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE => ErrorKind::NotFound,
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => ErrorKind::PermissionDenied,
+        reqwest::StatusCode::TOO_MANY_REQUESTS => ErrorKind::LimitsExceeded,
+        reqwest::StatusCode::REQUEST_TIMEOUT | reqwest::StatusCode::GATEWAY_TIMEOUT => ErrorKind::Timeout,
+        status if status.is_server_error() => ErrorKind::ServiceUnavailable,
+        _ => ErrorKind::InvalidRequest,
+    }
+}
+
 /// Factory for creating platform-specific backends
 pub struct PlatformBackendFactory;
 
@@ -0,0 +1,208 @@
+// Opening URLs and files with the desktop's default handlers
+// Linux prefers the xdg-desktop-portal OpenURI and FileManager1 D-Bus services
+// (which also work inside Flatpak/Snap sandboxes) and falls back to xdg-open
+
+use std::path::Path;
+
+use tokio::process::Command;
+use url::Url;
+
+use crate::components::{ErrorKind, NotificationError, NotificationResult, Platform};
+
+#[cfg(target_os = "linux")]
+use zbus::{Connection, Result as ZbusResult, dbus_proxy};
+
+#[cfg(target_os = "linux")]
+#[dbus_proxy(
+    interface = "org.freedesktop.portal.OpenURI",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait OpenUri {
+    /// Ask the portal to open a URI with the user's preferred application
+    #[zbus(name = "OpenURI")]
+    fn open_uri(
+        &self,
+        parent_window: &str,
+        uri: &str,
+        options: std::collections::HashMap<&str, zbus::zvariant::Value<'_>>,
+    ) -> ZbusResult<zbus::zvariant::OwnedObjectPath>;
+}
+
+#[cfg(target_os = "linux")]
+#[dbus_proxy(
+    interface = "org.freedesktop.FileManager1",
+    default_service = "org.freedesktop.FileManager1",
+    default_path = "/org/freedesktop/FileManager1"
+)]
+trait FileManager {
+    /// Open the containing folders with the given items selected
+    fn show_items(&self, uris: Vec<&str>, startup_id: &str) -> ZbusResult<()>;
+}
+
+/// Open a URL with the default handler for its scheme
+pub async fn open_url(url: &Url) -> NotificationResult<()> {
+    if url.scheme() == "file"
+        && let Ok(path) = url.to_file_path()
+    {
+        return open_path(&path).await;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        match portal_open_uri(url).await {
+            Ok(()) => return Ok(()),
+            Err(e) => ::tracing::debug!("OpenURI portal unavailable, using xdg-open: {}", e),
+        }
+    }
+
+    launch(url.as_str()).await
+}
+
+/// Open a file or directory with its default application
+pub async fn open_path(path: &Path) -> NotificationResult<()> {
+    ensure_exists(path)?;
+    launch(&path.display().to_string()).await
+}
+
+/// Show a file in the file manager, selected where the platform supports it
+pub async fn reveal_path(path: &Path) -> NotificationResult<()> {
+    ensure_exists(path)?;
+
+    #[cfg(target_os = "linux")]
+    {
+        match file_manager_show_item(path).await {
+            Ok(()) => return Ok(()),
+            Err(e) => ::tracing::debug!("FileManager1 unavailable, opening parent folder: {}", e),
+        }
+        let parent = path.parent().unwrap_or(path);
+        launch(&parent.display().to_string()).await
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        run(Command::new("open").arg("-R").arg(path)).await
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path.as_os_str());
+        // Explorer exits with 1 even on success
+        Command::new("explorer")
+            .arg(select)
+            .status()
+            .await
+            .map(|_| ())
+            .map_err(spawn_error)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        Err(unsupported("revealing files"))
+    }
+}
+
+fn ensure_exists(path: &Path) -> NotificationResult<()> {
+    if path.exists() {
+        Ok(())
+    } else {
+        Err(NotificationError::ResourceError {
+            resource_type: "file".to_string(),
+            resource_id: path.display().to_string(),
+            kind: ErrorKind::NotFound,
+            message: "File does not exist".to_string(),
+            source: None,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn portal_open_uri(url: &Url) -> ZbusResult<()> {
+    let connection = Connection::session().await?;
+    let proxy = OpenUriProxy::new(&connection).await?;
+    proxy
+        .open_uri("", url.as_str(), std::collections::HashMap::new())
+        .await?;
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+async fn file_manager_show_item(path: &Path) -> ZbusResult<()> {
+    let uri = Url::from_file_path(path)
+        .map_err(|_| zbus::Error::Failure(format!("Not an absolute path: {}", path.display())))?;
+    let connection = Connection::session().await?;
+    let proxy = FileManagerProxy::new(&connection).await?;
+    proxy.show_items(vec![uri.as_str()], "").await
+}
+
+/// Hand a URL or path to the platform's generic opener
+async fn launch(target: &str) -> NotificationResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        run(Command::new("xdg-open").arg(target)).await
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        run(Command::new("open").arg(target)).await
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // The empty argument is the window title `start` expects before the target
+        run(Command::new("cmd").args(["/C", "start", ""]).arg(target)).await
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        let _ = target;
+        Err(unsupported("opening URLs"))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+async fn run(command: &mut Command) -> NotificationResult<()> {
+    let status = command
+        .status()
+        .await
+        .map_err(spawn_error)?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(NotificationError::PlatformError {
+            platform: Platform::current(),
+            kind: ErrorKind::Other,
+            error_code: status.code(),
+            message: format!("Opener exited with {}", status),
+            source: None,
+        })
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn spawn_error(error: std::io::Error) -> NotificationError {
+    let kind = match error.kind() {
+        std::io::ErrorKind::NotFound => ErrorKind::Unsupported,
+        std::io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+        _ => ErrorKind::Other,
+    };
+    NotificationError::PlatformError {
+        platform: Platform::current(),
+        kind,
+        error_code: error.raw_os_error(),
+        message: format!("Failed to launch opener: {}", error),
+        source: Some(error.into()),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn unsupported(action: &str) -> NotificationError {
+    NotificationError::PlatformError {
+        platform: Platform::current(),
+        kind: ErrorKind::Unsupported,
+        error_code: None,
+        message: format!("Failed to launch opener: {} is not supported on this platform", action),
+        source: None,
+    }
+}
@@ -0,0 +1,285 @@
+// Freedesktop sound theme lookup
+// Maps cross-platform sounds to XDG Sound Naming Specification names and resolves
+// names and relative files through the XDG Sound Theme Specification directories
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use url::Url;
+
+use super::icon_theme::{gtk_setting, parse_desktop_groups, xdg_data_dirs};
+use crate::components::{AudioSource, MediaAttachment, NotificationContent, SystemSound};
+
+/// Theme every other sound theme implicitly falls back to
+pub const FALLBACK_SOUND_THEME: &str = "freedesktop";
+
+/// Output profile sounds are looked up for
+const OUTPUT_PROFILE: &str = "stereo";
+
+/// Sound file extensions in lookup order
+const SOUND_EXTENSIONS: &[&str] = &["oga", "ogg", "wav"];
+
+/// Lookup used by `lookup_sound` when callers don't supply their own
+static DEFAULT_SOUND_LOOKUP: LazyLock<parking_lot::RwLock<SoundLookup>> =
+    LazyLock::new(|| parking_lot::RwLock::new(SoundLookup::new()));
+
+/// Get the process-wide sound lookup
+pub fn sound_lookup() -> SoundLookup {
+    DEFAULT_SOUND_LOOKUP.read().clone()
+}
+
+/// Replace the process-wide sound lookup, e.g. to pin a theme or add search paths
+pub fn set_sound_lookup(lookup: SoundLookup) {
+    *DEFAULT_SOUND_LOOKUP.write() = lookup;
+}
+
+/// Resolve a sound name with the process-wide lookup
+pub fn lookup_sound(name: &str) -> Option<PathBuf> {
+    sound_lookup().lookup(name)
+}
+
+/// XDG sound naming spec name for a system sound
+pub fn xdg_sound_name(sound: SystemSound) -> &'static str {
+    match sound {
+        SystemSound::Default | SystemSound::Alert => "message-new-instant",
+        SystemSound::Critical | SystemSound::Warning => "dialog-warning",
+        SystemSound::Information => "dialog-information",
+        SystemSound::Question => "dialog-question",
+        SystemSound::Error => "dialog-error",
+        SystemSound::Success => "complete",
+    }
+}
+
+/// Sound to request from a freedesktop notification server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SoundHint {
+    /// `sound-name`: a themed sound the server resolves itself
+    Name(String),
+    /// `sound-file`: an absolute path to a sound file
+    File(PathBuf),
+    /// `suppress-sound`: play nothing, not even the server default
+    Suppress,
+}
+
+/// Pick the sound hint for `content`
+///
+/// A per-platform `custom_sound` (from `PlatformUserSettings`) wins over the
+/// content's audio attachment. It is treated as a file when it looks like a
+/// path and as a sound name otherwise. Unresolvable files are skipped with a
+/// warning so a missing sound never blocks delivery.
+pub fn resolve_sound_hint(
+    content: &NotificationContent,
+    custom_sound: Option<&str>,
+) -> Option<SoundHint> {
+    if let Some(custom) = custom_sound.map(str::trim).filter(|custom| !custom.is_empty()) {
+        let looks_like_file = custom.contains('/')
+            || Path::new(custom)
+                .extension()
+                .is_some_and(|ext| SOUND_EXTENSIONS.iter().any(|known| ext == *known));
+        return if looks_like_file {
+            resolve_file_hint(Path::new(custom))
+        } else {
+            Some(SoundHint::Name(custom.to_string()))
+        };
+    }
+
+    let source = content.media.iter().find_map(|attachment| match attachment {
+        MediaAttachment::Audio { source, .. } => Some(source),
+        _ => None,
+    })?;
+
+    match source {
+        AudioSource::System(sound) => Some(SoundHint::Name(xdg_sound_name(*sound).to_string())),
+        AudioSource::File(path) => resolve_file_hint(path),
+        AudioSource::Url(url) => resolve_url_hint(url),
+        AudioSource::Default => None,
+        AudioSource::Silent => Some(SoundHint::Suppress),
+    }
+}
+
+fn resolve_file_hint(path: &Path) -> Option<SoundHint> {
+    let resolved = sound_lookup().resolve_file(path);
+    if resolved.is_none() {
+        tracing::warn!("Sound file {:?} not found in the sound theme directories", path);
+    }
+    resolved.map(SoundHint::File)
+}
+
+fn resolve_url_hint(url: &Url) -> Option<SoundHint> {
+    match url.to_file_path() {
+        Ok(path) if url.scheme() == "file" => resolve_file_hint(&path),
+        _ => {
+            tracing::warn!("Sound URL {} is not a local file, skipping sound hint", url);
+            None
+        }
+    }
+}
+
+/// Sound theme lookup over a set of base directories
+#[derive(Debug, Clone)]
+pub struct SoundLookup {
+    search_paths: Vec<PathBuf>,
+    theme: String,
+}
+
+impl Default for SoundLookup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoundLookup {
+    /// Lookup over `$XDG_DATA_HOME/sounds` and `$XDG_DATA_DIRS/sounds` using the
+    /// desktop's configured theme
+    pub fn new() -> Self {
+        Self {
+            search_paths: xdg_data_dirs().into_iter().map(|dir| dir.join("sounds")).collect(),
+            theme: gtk_setting("gtk-sound-theme-name")
+                .unwrap_or_else(|| FALLBACK_SOUND_THEME.to_string()),
+        }
+    }
+
+    /// Search only `paths` (each holding `<theme>/index.theme` directories and loose sounds)
+    pub fn with_search_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.search_paths = paths;
+        self
+    }
+
+    /// Use `theme` instead of the desktop's configured theme
+    pub fn with_theme(mut self, theme: impl Into<String>) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    pub fn search_paths(&self) -> &[PathBuf] {
+        &self.search_paths
+    }
+
+    /// Resolve a sound name to a file
+    ///
+    /// Tries the full name and then each shorter `-` separated prefix
+    /// (`dialog-warning-urgent` falls back to `dialog-warning`), first in the
+    /// selected theme and its parents, then in `freedesktop`, then loose in the
+    /// search paths. A `.disabled` file in a theme turns the sound off.
+    pub fn lookup(&self, name: &str) -> Option<PathBuf> {
+        if name.is_empty() || name.contains('/') {
+            return None;
+        }
+
+        let mut candidate = name;
+        loop {
+            let mut visited = HashSet::new();
+            let found = self
+                .lookup_in_theme(&self.theme, candidate, &mut visited)
+                .or_else(|| self.lookup_in_theme(FALLBACK_SOUND_THEME, candidate, &mut visited))
+                .or_else(|| self.lookup_loose(candidate).map(Some));
+            if let Some(found) = found {
+                return found;
+            }
+
+            candidate = &candidate[..candidate.rfind('-')?];
+        }
+    }
+
+    /// Resolve a sound file path to an existing absolute file
+    ///
+    /// Absolute paths must exist. Relative paths are tried against the working
+    /// directory, then under each search path, then as a sound name by file stem.
+    pub fn resolve_file(&self, path: &Path) -> Option<PathBuf> {
+        if path.is_absolute() {
+            return path.is_file().then(|| path.to_path_buf());
+        }
+        if path.is_file() {
+            return std::path::absolute(path).ok();
+        }
+
+        self.search_paths
+            .iter()
+            .map(|dir| dir.join(path))
+            .find(|candidate| candidate.is_file())
+            .or_else(|| {
+                let stem = path.file_stem()?.to_str()?;
+                self.lookup(stem)
+            })
+    }
+
+    /// `Some(None)` when the theme disables the sound, `None` when not found
+    fn lookup_in_theme(
+        &self,
+        theme: &str,
+        name: &str,
+        visited: &mut HashSet<String>,
+    ) -> Option<Option<PathBuf>> {
+        if !visited.insert(theme.to_string()) {
+            return None;
+        }
+
+        let theme_dirs: Vec<PathBuf> = self
+            .search_paths
+            .iter()
+            .map(|path| path.join(theme))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        let groups = theme_dirs
+            .iter()
+            .find_map(|dir| std::fs::read_to_string(dir.join("index.theme")).ok())
+            .map(|index| parse_desktop_groups(&index))?;
+        let info = groups.get("Sound Theme")?;
+
+        let list = |key: &str| -> Vec<String> {
+            info.get(key)
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        // Subdirectories for the stereo profile first, then any others
+        let mut subdirs = list("Directories");
+        subdirs.sort_by_key(|subdir| {
+            groups
+                .get(subdir)
+                .and_then(|group| group.get("OutputProfile"))
+                .is_none_or(|profile| profile != OUTPUT_PROFILE)
+        });
+
+        for subdir in &subdirs {
+            for dir in &theme_dirs {
+                let base = dir.join(subdir);
+                if base.join(format!("{}.disabled", name)).exists() {
+                    return Some(None);
+                }
+                if let Some(found) = SOUND_EXTENSIONS
+                    .iter()
+                    .map(|ext| base.join(format!("{}.{}", name, ext)))
+                    .find(|path| path.is_file())
+                {
+                    return Some(Some(found));
+                }
+            }
+        }
+
+        list("Inherits")
+            .iter()
+            .find_map(|parent| self.lookup_in_theme(parent, name, visited))
+    }
+
+    fn lookup_loose(&self, name: &str) -> Option<PathBuf> {
+        self.search_paths.iter().find_map(|dir| {
+            SOUND_EXTENSIONS
+                .iter()
+                .map(|ext| dir.join(format!("{}.{}", name, ext)))
+                .find(|path| path.is_file())
+        })
+    }
+}
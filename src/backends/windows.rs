@@ -10,7 +10,7 @@ use std::sync::Arc;
 use std::time::SystemTime;
 
 #[cfg(target_os = "windows")]
-use tokio::sync::OnceCell;
+use dashmap::DashMap;
 
 #[cfg(target_os = "windows")]
 use windows::{
@@ -23,22 +23,23 @@ use windows::{
 
 use kodegen_native_permissions::{PermissionManager, PermissionStatus, PermissionType};
 
-use crate::components::NotificationResult;
+use crate::components::{ErrorKind, NotificationResult, Platform};
 use crate::components::platform::{
-    DeliveryReceipt, NotificationRequest, NotificationUpdate, PlatformBackend, PlatformCapabilities,
+    AppIdentity, DeliveryReceipt, NotificationRequest, NotificationUpdate, PlatformBackend,
+    PlatformCapabilities,
 };
 
 #[cfg(target_os = "windows")]
 use crate::components::platform::{CompatibilityLevel, PermissionLevel};
 
+/// Largest image file handed to the platform (200KB limit for toast images)
 #[cfg(target_os = "windows")]
-use crate::components::Platform;
+const MAX_IMAGE_SIZE: usize = 204_800;
 
 pub struct WindowsBackend {
+    /// Toast notifiers by AppUserModelID, one per app identity sending through this backend
     #[cfg(target_os = "windows")]
-    app_id: String,
-    #[cfg(target_os = "windows")]
-    notifier: Arc<OnceCell<ToastNotifier>>,
+    notifiers: Arc<DashMap<String, ToastNotifier>>,
     permission_manager: PermissionManager,
 }
 
@@ -52,28 +53,26 @@ impl WindowsBackend {
     pub fn new() -> Self {
         Self {
             #[cfg(target_os = "windows")]
-            app_id: "EcsNotifications.App".to_string(),
-            #[cfg(target_os = "windows")]
-            notifier: Arc::new(OnceCell::new()),
+            notifiers: Arc::new(DashMap::new()),
             permission_manager: PermissionManager::new(),
         }
     }
 
     #[cfg(target_os = "windows")]
-    async fn get_notifier(&self) -> Result<ToastNotifier, crate::components::NotificationError> {
-        self.notifier
-            .get_or_try_init(|| async {
-                let app_id = HSTRING::from(&self.app_id);
-                ToastNotificationManager::CreateToastNotifierWithId(&app_id).map_err(|e| {
-                    crate::components::NotificationError::PlatformError {
-                        platform: "Windows".to_string(),
-                        error_code: Some(e.code().0 as i32),
-                        message: format!("Failed to create toast notifier: {:?}", e),
-                    }
-                })
-            })
-            .await
-            .cloned()
+    async fn get_notifier(&self, app_id: &str) -> Result<ToastNotifier, crate::components::NotificationError> {
+        if let Some(notifier) = self.notifiers.get(app_id) {
+            return Ok(notifier.clone());
+        }
+        let notifier = ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id))
+            .map_err(|e| crate::components::NotificationError::PlatformError {
+                platform: Platform::Windows,
+                kind: ErrorKind::Other,
+                error_code: Some(e.code().0 as i32),
+                message: format!("Failed to create toast notifier: {}", e),
+                source: Some(e.into()),
+            })?;
+        self.notifiers.insert(app_id.to_string(), notifier.clone());
+        Ok(notifier)
     }
 
     #[cfg(target_os = "windows")]
@@ -81,12 +80,11 @@ impl WindowsBackend {
         &self,
         title: &str,
         subtitle: Option<&str>,
-        body: &str,
+        body_xml: &str,
         hero_image_url: Option<&str>,
         app_logo_url: Option<&str>,
     ) -> WindowsResult<XmlDocument> {
         let escaped_title = xml_escape(title);
-        let escaped_body = xml_escape(body);
         
         // Hero image (large banner at top)
         let hero_element = hero_image_url
@@ -120,7 +118,7 @@ impl WindowsBackend {
             {logo_element}
             <text hint-style="title">{escaped_title}</text>
             {subtitle_element}
-            <text hint-style="body">{escaped_body}</text>
+            <text hint-style="body">{body_xml}</text>
         </binding>
     </visual>
     <audio src="ms-winsoundevent:Notification.Default"/>
@@ -166,9 +164,11 @@ impl WindowsBackend {
                         Ok(false)
                     }
                     Err(e) => Err(crate::components::NotificationError::PlatformError {
-                        platform: "Windows".to_string(),
+                        platform: Platform::Windows,
+                        kind: super::permission_error_kind(&e),
                         error_code: None,
                         message: format!("Permission request failed: {}", e),
+                        source: None,
                     })
                 }
             }
@@ -177,9 +177,11 @@ impl WindowsBackend {
                 Ok(false)
             }
             Err(e) => Err(crate::components::NotificationError::PlatformError {
-                platform: "Windows".to_string(),
+                platform: Platform::Windows,
+                kind: super::permission_error_kind(&e),
                 error_code: None,
                 message: format!("Permission check failed: {}", e),
+                source: None,
             })
         }
     }
@@ -221,7 +223,7 @@ impl PlatformBackend for WindowsBackend {
                     max_actions: Some(5),
                     max_title_length: Some(128),
                     max_body_length: Some(1024),
-                    max_image_size: Some(204_800), // 200KB limit for toast images
+                    max_image_size: Some(MAX_IMAGE_SIZE),
                     max_sound_duration: Some(std::time::Duration::from_secs(10)),
                     platform_features: {
                         let mut features = HashMap::new();
@@ -261,9 +263,11 @@ impl PlatformBackend for WindowsBackend {
             #[cfg(not(target_os = "windows"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "Windows".to_string(),
+                    platform: Platform::Windows,
+                    kind: ErrorKind::Unsupported,
                     error_code: None,
                     message: "Windows backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -279,10 +283,14 @@ impl PlatformBackend for WindowsBackend {
         Box::pin(async move {
             #[cfg(target_os = "windows")]
             {
-                let notifier = self.get_notifier().await?;
+                let notifier = self.get_notifier(&request.app.app_id).await?;
 
                 // Resolve all media images (downloads remote URLs to temp files)
-                let resolved_images = super::image_utils::resolve_media_images(&request.content.media).await;
+                let resolved_images = super::image_processing::resolve_media_images_for(
+                    &request.content.media,
+                    Platform::Windows,
+                    Some(MAX_IMAGE_SIZE),
+                ).await;
 
                 // Extract hero image path from resolved images
                 let hero_image_path = resolved_images.iter()
@@ -304,25 +312,44 @@ impl PlatformBackend for WindowsBackend {
                     .create_toast_xml(
                         &request.content.title,
                         subtitle.as_deref(),
-                        &request.content.body.to_structured_plain_text(),
+                        &request.content.body.to_toast_text(),
                         hero_image_path.as_deref(),
                         app_logo_path.as_deref(),
                     )
                     .map_err(|e| crate::components::NotificationError::PlatformError {
-                        platform: "Windows".to_string(),
+                        platform: Platform::Windows,
+                        kind: ErrorKind::InvalidRequest,
                         error_code: Some(e.code().0 as i32),
-                        message: format!("Failed to create toast XML: {:?}", e),
+                        message: format!("Failed to create toast XML: {}", e),
+                        source: Some(e.into()),
                     })?;
 
                 // Create the toast notification
                 let toast = ToastNotification::CreateToastNotification(&xml_doc).map_err(|e| {
                     crate::components::NotificationError::PlatformError {
-                        platform: "Windows".to_string(),
+                        platform: Platform::Windows,
+                        kind: ErrorKind::InvalidRequest,
                         error_code: Some(e.code().0 as i32),
-                        message: format!("Failed to create toast notification: {:?}", e),
+                        message: format!("Failed to create toast notification: {}", e),
+                        source: Some(e.into()),
                     }
                 })?;
 
+                // Tag toasts with the notification ID so they can be removed or replaced
+                if let Err(e) = toast.SetTag(&HSTRING::from(&request.notification_id)) {
+                    eprintln!("Warning: Failed to set toast tag: {:?}", e);
+                }
+                if let Some(replace_id) = &request.options.replace_id {
+                    let app_id = HSTRING::from(&request.app.app_id);
+                    if let Ok(history) = ToastNotificationManager::History() {
+                        let _ = history.RemoveWithTagAndGroup(
+                            &HSTRING::from(replace_id),
+                            &HSTRING::from(""),
+                            &app_id,
+                        );
+                    }
+                }
+
                 // Set expiration time if specified
                 if let Some(ttl) = request.options.ttl {
                     let expiry_time = SystemTime::now() + ttl;
@@ -357,9 +384,11 @@ impl PlatformBackend for WindowsBackend {
                 let start_time = SystemTime::now();
                 notifier.Show(&toast).map_err(|e| {
                     crate::components::NotificationError::PlatformError {
-                        platform: "Windows".to_string(),
+                        platform: Platform::Windows,
+                        kind: ErrorKind::Other,
                         error_code: Some(e.code().0 as i32),
-                        message: format!("Failed to show toast notification: {:?}", e),
+                        message: format!("Failed to show toast notification: {}", e),
+                        source: Some(e.into()),
                     }
                 })?;
 
@@ -367,7 +396,7 @@ impl PlatformBackend for WindowsBackend {
                 let receipt = DeliveryReceipt::new(Platform::Windows, request.notification_id.clone())
                     .with_metadata("platform_api".to_string(), "WinRT".to_string())
                     .with_metadata("toast_template".to_string(), "ToastGeneric".to_string())
-                    .with_metadata("app_id".to_string(), self.app_id.clone());
+                    .with_metadata("app_id".to_string(), request.app.app_id.clone());
 
                 Ok(receipt)
             }
@@ -375,9 +404,11 @@ impl PlatformBackend for WindowsBackend {
             #[cfg(not(target_os = "windows"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "Windows".to_string(),
+                    platform: Platform::Windows,
+                    kind: ErrorKind::Unsupported,
                     error_code: None,
                     message: "Windows backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -388,8 +419,10 @@ impl PlatformBackend for WindowsBackend {
         &self,
         id: &str,
         update: &NotificationUpdate,
+        app: &AppIdentity,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = NotificationResult<()>> + Send + '_>>
     {
+        let app = app.clone();
         Box::pin(async move {
             #[cfg(target_os = "windows")]
             {
@@ -397,7 +430,7 @@ impl PlatformBackend for WindowsBackend {
                 // This is a platform limitation, so we implement the best possible solution
 
                 // First, try to remove the existing notification
-                let _ = self.cancel_notification(id).await;
+                let _ = self.cancel_notification(id, &app).await;
 
                 // Build updated content from content_changes map
                 let title = update.content_changes.get("title")
@@ -410,25 +443,31 @@ impl PlatformBackend for WindowsBackend {
                 // Create XML for updated toast
                 let xml_doc = self.create_toast_xml(&title, None, &body, None, None)
                     .map_err(|e| crate::components::NotificationError::PlatformError {
-                        platform: "Windows".to_string(),
+                        platform: Platform::Windows,
+                        kind: ErrorKind::InvalidRequest,
                         error_code: Some(e.code().0 as i32),
-                        message: format!("Failed to create updated toast XML: {:?}", e),
+                        message: format!("Failed to create updated toast XML: {}", e),
+                        source: Some(e.into()),
                     })?;
 
                 // Create and show the updated toast
-                let notifier = self.get_notifier().await?;
+                let notifier = self.get_notifier(&app.app_id).await?;
                 let toast = ToastNotification::CreateToastNotification(&xml_doc)
                     .map_err(|e| crate::components::NotificationError::PlatformError {
-                        platform: "Windows".to_string(),
+                        platform: Platform::Windows,
+                        kind: ErrorKind::InvalidRequest,
                         error_code: Some(e.code().0 as i32),
-                        message: format!("Failed to create updated toast notification: {:?}", e),
+                        message: format!("Failed to create updated toast notification: {}", e),
+                        source: Some(e.into()),
                     })?;
 
                 notifier.Show(&toast)
                     .map_err(|e| crate::components::NotificationError::PlatformError {
-                        platform: "Windows".to_string(),
+                        platform: Platform::Windows,
+                        kind: ErrorKind::Other,
                         error_code: Some(e.code().0 as i32),
-                        message: format!("Failed to show updated toast notification: {:?}", e),
+                        message: format!("Failed to show updated toast notification: {}", e),
+                        source: Some(e.into()),
                     })?;
 
                 Ok(())
@@ -437,9 +476,11 @@ impl PlatformBackend for WindowsBackend {
             #[cfg(not(target_os = "windows"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "Windows".to_string(),
+                    platform: Platform::Windows,
+                    kind: ErrorKind::Unsupported,
                     error_code: None,
                     message: "Windows backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -449,15 +490,17 @@ impl PlatformBackend for WindowsBackend {
     fn cancel_notification(
         &self,
         id: &str,
+        app: &AppIdentity,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = NotificationResult<()>> + Send + '_>>
     {
+        let app = app.clone();
         Box::pin(async move {
             #[cfg(target_os = "windows")]
             {
                 // Windows Toast API limitation: no direct cancellation by ID
                 // Best effort approach: use ToastNotificationHistory to remove
 
-                let app_id = HSTRING::from(&self.app_id);
+                let app_id = HSTRING::from(&app.app_id);
                 let history = ToastNotificationManager::History();
 
                 // Remove from history (this removes delivered notifications from Action Center)
@@ -465,9 +508,11 @@ impl PlatformBackend for WindowsBackend {
                 history
                     .RemoveWithTagAndGroup(&notification_id, &HSTRING::from(""), &app_id)
                     .map_err(|e| crate::components::NotificationError::PlatformError {
-                        platform: "Windows".to_string(),
+                        platform: Platform::Windows,
+                        kind: ErrorKind::Other,
                         error_code: Some(e.code().0 as i32),
-                        message: format!("Failed to remove notification from history: {:?}", e),
+                        message: format!("Failed to remove notification from history: {}", e),
+                        source: Some(e.into()),
                     })?;
 
                 // Note: This only removes already-delivered notifications from Action Center
@@ -478,9 +523,11 @@ impl PlatformBackend for WindowsBackend {
             #[cfg(not(target_os = "windows"))]
             {
                 Err(crate::components::NotificationError::PlatformError {
-                    platform: "Windows".to_string(),
+                    platform: Platform::Windows,
+                    kind: ErrorKind::Unsupported,
                     error_code: None,
                     message: "Windows backend not available on this platform".to_string(),
+                    source: None,
                 })
             }
         })
@@ -501,9 +548,7 @@ impl Clone for WindowsBackend {
     fn clone(&self) -> Self {
         Self {
             #[cfg(target_os = "windows")]
-            app_id: self.app_id.clone(),
-            #[cfg(target_os = "windows")]
-            notifier: Arc::clone(&self.notifier), // Clone the Arc, share the OnceCell
+            notifiers: Arc::clone(&self.notifiers), // Clone the Arc, share the notifiers
             permission_manager: PermissionManager::new(),
         }
     }
@@ -0,0 +1,171 @@
+//! Sending notifications from synchronous code
+//!
+//! [`NotificationManager`] here runs the async manager on a runtime of its own,
+//! with a dedicated worker thread, so git hooks and small CLIs can fire a
+//! notification and exit. Dropping it delivers what is still queued, for up to
+//! its flush timeout.
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::components::{DeliveryReceipt, ErrorKind};
+use crate::{
+    Notification, NotificationError, NotificationId, NotificationResult, NotificationUpdate,
+    PlatformBackendFactory, PlatformManager, ShutdownPolicy, ShutdownResult,
+};
+
+/// How long shutting down waits for queued notifications unless configured otherwise
+pub const DEFAULT_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Notification manager for callers without a tokio runtime
+pub struct NotificationManager {
+    /// Taken when shutting down, before the runtime its workers run on
+    manager: Option<crate::NotificationManager>,
+    runtime: Option<tokio::runtime::Runtime>,
+    flush_timeout: Duration,
+}
+
+impl NotificationManager {
+    /// Create a manager delivering through the backends supported here
+    pub fn new() -> NotificationResult<Self> {
+        Self::start(|| {
+            crate::NotificationManager::try_with_platform_manager(PlatformManager::with_backends(
+                PlatformBackendFactory::get_supported_backends(),
+            ))
+        })
+    }
+
+    /// Create a manager that delivers through the given backends
+    pub fn with_platform_manager(platform_manager: PlatformManager) -> NotificationResult<Self> {
+        Self::start(|| crate::NotificationManager::try_with_platform_manager(platform_manager))
+    }
+
+    /// Start the runtime and create the async manager on it
+    fn start(
+        create: impl FnOnce() -> NotificationResult<crate::NotificationManager>,
+    ) -> NotificationResult<Self> {
+        ensure_blocking_allowed()?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("native-notify")
+            .enable_all()
+            .build()
+            .map_err(|e| NotificationError::ResourceError {
+                resource_type: "runtime".to_string(),
+                resource_id: "tokio".to_string(),
+                kind: ErrorKind::Other,
+                message: format!("Failed to start the notification runtime: {}", e),
+                source: Some(e.into()),
+            })?;
+        let manager = {
+            let _entered = runtime.enter();
+            create()?
+        };
+        Ok(Self {
+            manager: Some(manager),
+            runtime: Some(runtime),
+            flush_timeout: DEFAULT_FLUSH_TIMEOUT,
+        })
+    }
+
+    /// Wait up to `timeout` for queued notifications when shutting down or dropped
+    pub fn with_flush_timeout(mut self, timeout: Duration) -> Self {
+        self.flush_timeout = timeout;
+        self
+    }
+
+    /// The async manager, for settings such as the app identity or action handlers
+    pub fn manager(&self) -> &crate::NotificationManager {
+        self.manager.as_ref().expect("manager is only taken on shutdown")
+    }
+
+    /// Queue a notification for delivery
+    pub fn send(&self, notification: Notification) -> NotificationResult<NotificationId> {
+        let handle = self.block_on(self.manager().send(notification))??;
+        Ok(handle.id)
+    }
+
+    /// Change a notification's content, on screen too if it's shown already
+    pub fn update(&self, id: NotificationId, update: NotificationUpdate) -> NotificationResult<()> {
+        self.block_on(self.manager().update(id, update))?
+    }
+
+    /// Cancel a notification: it's dropped from the queue, or taken down if shown
+    pub fn cancel(&self, id: NotificationId) -> NotificationResult<()> {
+        self.block_on(self.manager().cancel(id))?
+    }
+
+    /// Wait up to `timeout` until the notification is shown on every target platform
+    pub fn wait_delivered(
+        &self,
+        id: NotificationId,
+        timeout: Duration,
+    ) -> NotificationResult<Vec<DeliveryReceipt>> {
+        let handle = self.manager().handle(id).ok_or_else(|| crate::untracked(id))?;
+        // The timer has to be created on the runtime
+        match self.block_on(async { tokio::time::timeout(timeout, handle.delivered()).await })? {
+            Ok(delivered) => delivered,
+            Err(_) => Err(NotificationError::TimeoutError {
+                operation: format!("delivery of notification {}", id),
+                timeout_duration: timeout,
+            }),
+        }
+    }
+
+    /// Shut down, handling unfinished notifications as `policy` says
+    ///
+    /// `Drain` waits up to the flush timeout for queued notifications, like dropping does.
+    pub fn shutdown(mut self, policy: ShutdownPolicy) -> NotificationResult<ShutdownResult> {
+        ensure_blocking_allowed()?;
+        let (manager, runtime) = self.take();
+        let result = runtime.block_on(manager.shutdown_with_policy(policy, self.flush_timeout));
+        Ok(result)
+    }
+
+    /// Run `future` to completion on the manager's runtime
+    fn block_on<F: Future>(&self, future: F) -> NotificationResult<F::Output> {
+        ensure_blocking_allowed()?;
+        let runtime = self.runtime.as_ref().expect("runtime is only taken on shutdown");
+        Ok(runtime.block_on(future))
+    }
+
+    fn take(&mut self) -> (crate::NotificationManager, tokio::runtime::Runtime) {
+        (
+            self.manager.take().expect("manager is only taken on shutdown"),
+            self.runtime.take().expect("runtime is only taken on shutdown"),
+        )
+    }
+}
+
+impl Drop for NotificationManager {
+    fn drop(&mut self) {
+        if self.manager.is_none() {
+            return;
+        }
+        let (manager, runtime) = self.take();
+        if ensure_blocking_allowed().is_err() {
+            ::tracing::warn!("blocking::NotificationManager dropped inside a tokio runtime; queued notifications are not flushed");
+            drop(manager);
+            runtime.shutdown_background();
+            return;
+        }
+        let result = runtime.block_on(manager.shutdown_with_policy(ShutdownPolicy::Drain, self.flush_timeout));
+        ::tracing::debug!("Flushed notifications on drop: {:?}", result);
+    }
+}
+
+/// Fail when called from async code, where blocking would stall its runtime
+fn ensure_blocking_allowed() -> NotificationResult<()> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(_) => Err(NotificationError::ResourceError {
+            resource_type: "runtime".to_string(),
+            resource_id: "tokio".to_string(),
+            kind: ErrorKind::Unsupported,
+            message: "blocking::NotificationManager can't be used from async code; \
+                      use NotificationManager instead"
+                .to_string(),
+            source: None,
+        }),
+        Err(_) => Ok(()),
+    }
+}
@@ -0,0 +1,335 @@
+//! Command-completion notifications
+//!
+//! Turns the result of a terminal command (exit code, duration, output) into a
+//! ready-to-send [`Notification`] with a status icon, an output preview,
+//! priority by outcome and "Open transcript" / "Re-run" / "Copy command" actions.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::components::rich_text::html_escape;
+use crate::{
+    ActionId, ActionPayload, ActionStyle, ActivationType, BuildOutputSummarizer, ImageData,
+    ImagePlacement, MediaAttachment, Notification, NotificationAction, NotificationBuildError,
+    NotificationBuilder, Platform, Priority, RichText, TruncationPolicy, Url, strip_ansi,
+};
+
+/// Action ID for opening the command transcript
+pub const OPEN_TRANSCRIPT_ACTION: &str = "command.open_transcript";
+/// Action ID for running the command again
+pub const RERUN_ACTION: &str = "command.rerun";
+/// Action ID for copying the command line
+pub const COPY_COMMAND_ACTION: &str = "command.copy";
+
+/// Outcome of a command, derived from its exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandStatus {
+    Succeeded,
+    Failed(i32),
+    /// No exit code yet
+    Running,
+}
+
+/// Formatting for command notifications
+#[derive(Debug, Clone)]
+pub struct CommandTheme {
+    pub success_icon: String,
+    pub failure_icon: String,
+    pub running_icon: String,
+    /// Label before the terminal ID in the title ("✓ terminal 3: cargo build")
+    pub terminal_label: String,
+    /// Longest command shown in the title, in bytes
+    pub max_command_length: usize,
+    /// Output lines shown when no errors could be extracted from the output
+    pub max_output_lines: usize,
+    /// Errors and failed tests listed in the preview for failed commands
+    pub summarizer: BuildOutputSummarizer,
+    /// Attached as the app icon when set
+    pub logo_url: Option<Url>,
+    pub show_cwd: bool,
+    pub success_priority: Priority,
+    pub failure_priority: Priority,
+    pub open_transcript_label: String,
+    pub rerun_label: String,
+    pub copy_command_label: String,
+}
+
+impl Default for CommandTheme {
+    fn default() -> Self {
+        Self {
+            success_icon: "✓".to_string(),
+            failure_icon: "✗".to_string(),
+            running_icon: "⏳".to_string(),
+            terminal_label: "terminal".to_string(),
+            max_command_length: 40,
+            max_output_lines: 20,
+            summarizer: BuildOutputSummarizer::new(),
+            logo_url: None,
+            show_cwd: true,
+            success_priority: Priority::Normal,
+            failure_priority: Priority::High,
+            open_transcript_label: "Open transcript".to_string(),
+            rerun_label: "Re-run".to_string(),
+            copy_command_label: "Copy command".to_string(),
+        }
+    }
+}
+
+/// Notification for a finished (or still running) terminal command
+#[derive(Debug, Clone)]
+pub struct CommandNotification {
+    pub command: String,
+    pub cwd: Option<PathBuf>,
+    /// `None` while the command is still running
+    pub exit_code: Option<i32>,
+    pub duration: Option<Duration>,
+    pub output: String,
+    /// Notifications for the same terminal replace each other
+    pub terminal_id: Option<String>,
+    pub transcript_path: Option<PathBuf>,
+    pub theme: CommandTheme,
+    platforms: Option<Vec<Platform>>,
+}
+
+impl CommandNotification {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            cwd: None,
+            exit_code: None,
+            duration: None,
+            output: String::new(),
+            terminal_id: None,
+            transcript_path: None,
+            theme: CommandTheme::default(),
+            platforms: None,
+        }
+    }
+
+    pub fn with_cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    pub fn with_exit_code(mut self, exit_code: i32) -> Self {
+        self.exit_code = Some(exit_code);
+        self
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Combined stdout/stderr; ANSI escape codes are stripped
+    pub fn with_output(mut self, output: impl Into<String>) -> Self {
+        self.output = output.into();
+        self
+    }
+
+    pub fn with_terminal_id(mut self, terminal_id: impl ToString) -> Self {
+        self.terminal_id = Some(terminal_id.to_string());
+        self
+    }
+
+    pub fn with_transcript_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.transcript_path = Some(path.into());
+        self
+    }
+
+    pub fn with_theme(mut self, theme: CommandTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn with_platforms(mut self, platforms: Vec<Platform>) -> Self {
+        self.platforms = Some(platforms);
+        self
+    }
+
+    pub fn status(&self) -> CommandStatus {
+        match self.exit_code {
+            Some(0) => CommandStatus::Succeeded,
+            Some(code) => CommandStatus::Failed(code),
+            None => CommandStatus::Running,
+        }
+    }
+
+    /// Deduplication key: one notification per terminal
+    pub fn dedup_key(&self) -> Option<String> {
+        self.terminal_id.as_ref().map(|id| format!("terminal:{}", id))
+    }
+
+    /// Title such as "✗ terminal 3: cargo build --release"
+    pub fn title(&self) -> String {
+        let theme = &self.theme;
+        let icon = match self.status() {
+            CommandStatus::Succeeded => &theme.success_icon,
+            CommandStatus::Failed(_) => &theme.failure_icon,
+            CommandStatus::Running => &theme.running_icon,
+        };
+        let command = TruncationPolicy::new()
+            .with_prefer_boundaries(false)
+            .truncate_plain(&self.command, theme.max_command_length)
+            .unwrap_or_else(|| self.command.clone());
+
+        match &self.terminal_id {
+            Some(id) => format!("{} {} {}: {}", icon, theme.terminal_label, id, command),
+            None => format!("{} {}", icon, command),
+        }
+    }
+
+    /// Output excerpt: extracted errors for failures, else the last lines
+    pub fn output_preview(&self) -> String {
+        let output = strip_ansi(&self.output);
+        if matches!(self.status(), CommandStatus::Failed(_)) {
+            let summary = self.theme.summarizer.summarize(&output);
+            if summary.has_failures() {
+                return summary.to_plain_text();
+            }
+        }
+        tail_lines(&output, self.theme.max_output_lines)
+    }
+
+    /// Build the notification
+    pub fn build(self) -> Result<Notification, NotificationBuildError> {
+        let status = self.status();
+        let priority = match status {
+            CommandStatus::Failed(_) => self.theme.failure_priority,
+            CommandStatus::Succeeded | CommandStatus::Running => self.theme.success_priority,
+        };
+
+        let mut builder = NotificationBuilder::new()
+            .with_title(self.title())
+            .with_body(RichText::Html(self.body_html()))
+            .with_priority(priority)
+            .with_truncation(TruncationPolicy::new());
+        if let Some(platforms) = &self.platforms {
+            builder = builder.with_platforms(platforms.clone());
+        }
+        if let Some(key) = self.dedup_key() {
+            builder = builder.with_dedup_key(key);
+        }
+        if let Some(logo_url) = &self.theme.logo_url {
+            builder = builder.with_media(MediaAttachment::Image {
+                data: ImageData::Url(logo_url.clone()),
+                placement: ImagePlacement::AppIcon,
+                alt_text: None,
+                dimensions: None,
+            });
+        }
+        for action in self.actions() {
+            builder = builder.with_action(action);
+        }
+
+        builder.build()
+    }
+
+    fn body_html(&self) -> String {
+        let status = match self.status() {
+            CommandStatus::Succeeded => "success".to_string(),
+            CommandStatus::Failed(code) => format!("exit {}", code),
+            CommandStatus::Running => "running".to_string(),
+        };
+
+        let mut html = match self.duration {
+            Some(duration) => format!(
+                "<p><b>{}</b> in {}</p>",
+                status,
+                html_escape(&format_duration(duration))
+            ),
+            None => format!("<p><b>{}</b></p>", status),
+        };
+        if self.theme.show_cwd
+            && let Some(cwd) = &self.cwd
+        {
+            html.push_str(&format!(
+                "<p><i>cwd:</i> {}</p>",
+                html_escape(&cwd.display().to_string())
+            ));
+        }
+        let preview = self.output_preview();
+        if !preview.is_empty() {
+            html.push_str(&format!("<pre>{}</pre>", html_escape(&preview)));
+        }
+        html
+    }
+
+    fn actions(&self) -> Vec<NotificationAction> {
+        let theme = &self.theme;
+        let mut data = HashMap::from([("command".to_string(), self.command.clone())]);
+        if let Some(cwd) = &self.cwd {
+            data.insert("cwd".to_string(), cwd.display().to_string());
+        }
+        if let Some(id) = &self.terminal_id {
+            data.insert("terminal_id".to_string(), id.clone());
+        }
+        let action = |id: &str, label: &str, activation_type, url| NotificationAction {
+            id: ActionId::new(id),
+            label: label.to_string(),
+            icon: None,
+            style: ActionStyle::Default,
+            activation_type,
+            url,
+            payload: Some(ActionPayload {
+                data: data.clone(),
+                callback_url: None,
+            }),
+            confirmation: None,
+        };
+
+        let mut actions = Vec::new();
+        if let Some(url) = self
+            .transcript_path
+            .as_ref()
+            .and_then(|path| Url::from_file_path(path).ok())
+        {
+            actions.push(action(
+                OPEN_TRANSCRIPT_ACTION,
+                &theme.open_transcript_label,
+                ActivationType::Protocol,
+                Some(url),
+            ));
+        }
+        if self.status() != CommandStatus::Running {
+            actions.push(action(
+                RERUN_ACTION,
+                &theme.rerun_label,
+                ActivationType::Foreground,
+                None,
+            ));
+        }
+        actions.push(action(
+            COPY_COMMAND_ACTION,
+            &theme.copy_command_label,
+            ActivationType::Background,
+            None,
+        ));
+        actions
+    }
+}
+
+/// Format a duration as "850ms", "45.2s" or "3m 12s"
+pub fn format_duration(duration: Duration) -> String {
+    let ms = duration.as_millis();
+    if ms >= 60_000 {
+        format!("{}m {}s", ms / 60_000, (ms % 60_000) / 1000)
+    } else if ms >= 1000 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+/// Last `max_lines` lines of `output`, noting how many were hidden
+fn tail_lines(output: &str, max_lines: usize) -> String {
+    let trimmed = output.trim();
+    let lines: Vec<&str> = trimmed.lines().collect();
+    if lines.len() <= max_lines {
+        return trimmed.to_string();
+    }
+
+    let start = lines.len() - max_lines;
+    format!("...({} lines hidden)\n{}", start, lines[start..].join("\n"))
+}
@@ -593,7 +593,7 @@ pub enum InteractionType {
 
 
 /// Interaction outcome tracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[derive(Default)]
 pub enum InteractionOutcome {
     #[default]
@@ -0,0 +1,312 @@
+// ANSI terminal output support for notification bodies
+// SGR styling (bold, italic, underline, strikethrough, 16/256/truecolor foreground)
+// maps onto the shared rich text document; cursor movement, OSC and other control
+// sequences are dropped, and carriage returns overwrite the current line
+
+use super::rich_text::{RichBlock, RichDocument, RichInline};
+
+/// Standard 16-color palette (xterm defaults)
+const PALETTE_16: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x00, 0x00),
+    (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00),
+    (0x00, 0x00, 0xee),
+    (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00),
+    (0x00, 0xff, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff),
+    (0xff, 0x00, 0xff),
+    (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+/// Lexical pieces of terminal output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Text(&'a str),
+    /// Parameters of an SGR (`ESC [ ... m`) sequence
+    Sgr(&'a str),
+    Newline,
+    CarriageReturn,
+    /// Any other escape sequence or control character
+    Ignored,
+}
+
+/// Split terminal output into tokens with the byte offset each one ends at
+fn tokenize(text: &str) -> Vec<(Token<'_>, usize)> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let start = index;
+        let token = match bytes[index] {
+            0x1b => {
+                index += 1;
+                match bytes.get(index) {
+                    Some(b'[') => {
+                        index += 1;
+                        let params_start = index;
+                        while index < bytes.len() && (0x20..=0x3f).contains(&bytes[index]) {
+                            index += 1;
+                        }
+                        let final_byte = bytes.get(index).copied().filter(|byte| (0x40..=0x7e).contains(byte));
+                        let params = &text[params_start..index];
+                        if final_byte.is_some() {
+                            index += 1;
+                        }
+                        match final_byte {
+                            Some(b'm') => Token::Sgr(params),
+                            _ => Token::Ignored,
+                        }
+                    }
+                    // OSC and other string sequences run to BEL or ST (`ESC \`)
+                    Some(b']' | b'P' | b'X' | b'^' | b'_') => {
+                        index += 1;
+                        while index < bytes.len() {
+                            if bytes[index] == 0x07 {
+                                index += 1;
+                                break;
+                            }
+                            if bytes[index] == 0x1b && bytes.get(index + 1) == Some(&b'\\') {
+                                index += 2;
+                                break;
+                            }
+                            index += 1;
+                        }
+                        Token::Ignored
+                    }
+                    Some(_) => {
+                        // nF sequences (charset selection) carry intermediates before the final byte
+                        while index < bytes.len() && (0x20..=0x2f).contains(&bytes[index]) {
+                            index += 1;
+                        }
+                        if index < bytes.len() && bytes[index].is_ascii() {
+                            index += 1;
+                        }
+                        Token::Ignored
+                    }
+                    None => Token::Ignored,
+                }
+            }
+            b'\n' => {
+                index += 1;
+                Token::Newline
+            }
+            b'\r' => {
+                index += 1;
+                if bytes.get(index) == Some(&b'\n') {
+                    index += 1;
+                    Token::Newline
+                } else {
+                    Token::CarriageReturn
+                }
+            }
+            b'\t' => {
+                index += 1;
+                Token::Text("\t")
+            }
+            byte if byte < 0x20 || byte == 0x7f => {
+                index += 1;
+                Token::Ignored
+            }
+            _ => {
+                while index < bytes.len()
+                    && bytes[index] != 0x1b
+                    && bytes[index] != 0x7f
+                    && (bytes[index] >= 0x20 || bytes[index] == b'\t')
+                {
+                    index += 1;
+                }
+                Token::Text(&text[start..index])
+            }
+        };
+        tokens.push((token, index));
+    }
+
+    tokens
+}
+
+/// Remove all escape sequences and control characters, keeping layout whitespace
+///
+/// A carriage return not followed by a newline discards the line so far, the
+/// way a terminal shows progress output.
+pub fn strip_ansi(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut line_start = 0;
+
+    for (token, _) in tokenize(text) {
+        match token {
+            Token::Text(text) => output.push_str(text),
+            Token::Newline => {
+                output.push('\n');
+                line_start = output.len();
+            }
+            Token::CarriageReturn => output.truncate(line_start),
+            Token::Sgr(_) | Token::Ignored => {}
+        }
+    }
+
+    output
+}
+
+/// Visible text of `text` with the source offset each visible byte run ends at
+///
+/// Carriage returns are treated as plain text breaks here, so truncation never
+/// cuts inside an escape sequence.
+pub(crate) fn visible_offsets(text: &str) -> (String, Vec<(usize, usize)>) {
+    let mut visible = String::new();
+    let mut offsets = Vec::new();
+
+    for (token, source_end) in tokenize(text) {
+        match token {
+            Token::Text(text) => visible.push_str(text),
+            Token::Newline => visible.push('\n'),
+            _ => continue,
+        }
+        offsets.push((visible.len(), source_end));
+    }
+
+    (visible, offsets)
+}
+
+/// SGR reset, appended after truncated ANSI text
+pub(crate) const SGR_RESET: &str = "\x1b[0m";
+
+/// Text attributes set by SGR sequences
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct SgrState {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+    foreground: Option<(u8, u8, u8)>,
+}
+
+impl SgrState {
+    fn apply(&mut self, params: &str) {
+        // Colon-separated sub-parameters fail to parse and are skipped
+        let mut codes = params
+            .split(';')
+            .map(|code| if code.is_empty() { Some(0) } else { code.parse::<u32>().ok() });
+
+        while let Some(code) = codes.next() {
+            let Some(code) = code else {
+                continue;
+            };
+            match code {
+                0 => *self = SgrState::default(),
+                1 => self.bold = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                9 => self.strikethrough = true,
+                21 => self.underline = true,
+                22 => self.bold = false,
+                23 => self.italic = false,
+                24 => self.underline = false,
+                29 => self.strikethrough = false,
+                30..=37 => self.foreground = Some(PALETTE_16[(code - 30) as usize]),
+                39 => self.foreground = None,
+                90..=97 => self.foreground = Some(PALETTE_16[(code - 90 + 8) as usize]),
+                38 | 48 | 58 => {
+                    let color = match codes.next().flatten() {
+                        Some(5) => codes.next().flatten().map(palette_256),
+                        Some(2) => {
+                            let mut channel = || codes.next().flatten().unwrap_or(0).min(255) as u8;
+                            Some((channel(), channel(), channel()))
+                        }
+                        _ => None,
+                    };
+                    // Background (48) and underline (58) colors are consumed but not rendered
+                    if code == 38 {
+                        self.foreground = color.or(self.foreground);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn wrap(&self, text: String) -> RichInline {
+        let mut inline = RichInline::Text(text);
+        if self.underline {
+            inline = RichInline::Underline(vec![inline]);
+        }
+        if self.strikethrough {
+            inline = RichInline::Strikethrough(vec![inline]);
+        }
+        if self.italic {
+            inline = RichInline::Emphasis(vec![inline]);
+        }
+        if self.bold {
+            inline = RichInline::Strong(vec![inline]);
+        }
+        if let Some((r, g, b)) = self.foreground {
+            inline = RichInline::Colored {
+                foreground: format!("#{:02x}{:02x}{:02x}", r, g, b),
+                content: vec![inline],
+            };
+        }
+        inline
+    }
+}
+
+/// xterm 256-color palette entry
+fn palette_256(index: u32) -> (u8, u8, u8) {
+    match index.min(255) {
+        index @ 0..=15 => PALETTE_16[index as usize],
+        index @ 16..=231 => {
+            let level = |value: u32| if value == 0 { 0 } else { (55 + value * 40) as u8 };
+            let index = index - 16;
+            (level(index / 36), level((index / 6) % 6), level(index % 6))
+        }
+        index => {
+            let gray = (8 + (index - 232) * 10) as u8;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// Parse terminal output into a single paragraph of styled runs and line breaks
+pub fn parse_ansi(text: &str) -> RichDocument {
+    let mut state = SgrState::default();
+    let mut lines: Vec<Vec<(SgrState, String)>> = vec![Vec::new()];
+
+    for (token, _) in tokenize(text) {
+        let line = lines.last_mut().expect("current line");
+        match token {
+            Token::Text(text) => match line.last_mut() {
+                Some((run_state, run)) if *run_state == state => run.push_str(text),
+                _ => line.push((state.clone(), text.to_string())),
+            },
+            Token::Sgr(params) => state.apply(params),
+            Token::Newline => lines.push(Vec::new()),
+            Token::CarriageReturn => line.clear(),
+            Token::Ignored => {}
+        }
+    }
+
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return RichDocument::default();
+    }
+
+    let mut content = Vec::new();
+    for (index, line) in lines.into_iter().enumerate() {
+        if index > 0 {
+            content.push(RichInline::LineBreak);
+        }
+        content.extend(line.into_iter().map(|(state, run)| state.wrap(run)));
+    }
+
+    RichDocument {
+        blocks: vec![RichBlock::Paragraph(content)],
+    }
+}
@@ -0,0 +1,566 @@
+// Build and test output summarization
+// Recognizes rustc/cargo diagnostics, cargo test failures, npm/tsc errors and pytest
+// failure blocks, and reduces a full log to the first errors, counts and locations
+
+use std::fmt;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::ansi::strip_ansi;
+use super::content::RichText;
+use super::rich_text::html_escape;
+
+static RUSTC_DIAGNOSTIC: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(error|warning)(?:\[(E\d{4})\])?: (.+)$").expect("valid regex")
+});
+static RUSTC_LOCATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*--> (.+?):(\d+):(\d+)$").expect("valid regex"));
+static CARGO_TEST_FAILED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^test (\S+) \.\.\. FAILED$").expect("valid regex"));
+static CARGO_TEST_STDOUT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^---- (\S+) stdout ----$").expect("valid regex"));
+static CARGO_PANIC: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^thread '(.+?)' panicked at (.+?):(\d+):(\d+):$").expect("valid regex")
+});
+// Panic format used before Rust 1.73
+static CARGO_PANIC_LEGACY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^thread '(.+?)' panicked at '(.*)', (.+?):(\d+):(\d+)$").expect("valid regex")
+});
+static CARGO_TEST_RESULT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^test result: (?:ok|FAILED)\. (\d+) passed; (\d+) failed").expect("valid regex")
+});
+static TSC_DIAGNOSTIC: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(.+?)(?:\((\d+),(\d+)\):|:(\d+):(\d+) -) (error|warning) (TS\d+): (.+)$")
+        .expect("valid regex")
+});
+static NPM_ERROR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^npm (?:ERR!|error) ?(.*)$").expect("valid regex"));
+static PYTEST_BLOCK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^_{3,} (.+?) _{3,}$").expect("valid regex"));
+static PYTEST_LOCATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\S+\.py):(\d+): (\w+)$").expect("valid regex"));
+static PYTEST_SHORT_SUMMARY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:FAILED|ERROR) (\S+)(?: - (.+))?$").expect("valid regex"));
+static PYTEST_COUNTS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^=+ (.*\d+ (?:failed|passed|errors?).*) in [\d.]+s.* =+$").expect("valid regex")
+});
+static PYTEST_COUNT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d+) (failed|passed|errors?)\b").expect("valid regex"));
+
+/// Cargo summary lines that repeat what the individual diagnostics already say
+const CARGO_NOISE: &[&str] = &[
+    "could not compile",
+    "aborting due to",
+    "build failed",
+    "test failed, to rerun",
+    "warnings emitted",
+    "warning emitted",
+    ") generated ",
+];
+
+/// npm lines that describe the failed process rather than the failure
+const NPM_NOISE: &[&str] = &[
+    "code ",
+    "errno ",
+    "path ",
+    "syscall ",
+    "command ",
+    "signal ",
+    "workspace ",
+    "location ",
+    "Lifecycle script",
+    "Exit status",
+    "Failed at the",
+    "This is probably not a problem with npm",
+    "A complete log of this run",
+    "Log files were not written",
+    "You can rerun the command",
+    "There is likely additional logging",
+];
+
+/// Tool that produced a diagnostic or test failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BuildTool {
+    /// rustc diagnostics, usually via cargo
+    Cargo,
+    CargoTest,
+    Npm,
+    Tsc,
+    Pytest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// `file:line[:column]` reference into the source tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub path: String,
+    pub line: u32,
+    pub column: Option<u32>,
+}
+
+impl SourceLocation {
+    fn parse(path: &str, line: &str, column: Option<&str>) -> Option<Self> {
+        Some(Self {
+            path: path.to_string(),
+            line: line.parse().ok()?,
+            column: column.and_then(|column| column.parse().ok()),
+        })
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "{}:{}:{}", self.path, self.line, column),
+            None => write!(f, "{}:{}", self.path, self.line),
+        }
+    }
+}
+
+/// A compiler or tool error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildDiagnostic {
+    pub tool: BuildTool,
+    pub severity: DiagnosticSeverity,
+    /// Error code such as `E0308` or `TS2322`
+    pub code: Option<String>,
+    pub message: String,
+    pub location: Option<SourceLocation>,
+}
+
+impl fmt::Display for BuildDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            DiagnosticSeverity::Error => "error",
+            DiagnosticSeverity::Warning => "warning",
+        };
+        match &self.code {
+            Some(code) => write!(f, "{}[{}]: {}", severity, code, self.message)?,
+            None => write!(f, "{}: {}", severity, self.message)?,
+        }
+        if let Some(location) = &self.location {
+            write!(f, " ({})", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// A failed test with the first line of its failure message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestFailure {
+    pub tool: BuildTool,
+    pub name: String,
+    pub message: Option<String>,
+    pub location: Option<SourceLocation>,
+}
+
+impl fmt::Display for TestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FAILED {}", self.name)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, " ({})", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// Meaningful lines extracted from a build or test log
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildSummary {
+    /// Tools recognized in the output, in order of first appearance
+    pub tools: Vec<BuildTool>,
+    /// First errors, up to the summarizer's limit
+    pub errors: Vec<BuildDiagnostic>,
+    pub error_count: usize,
+    pub warning_count: usize,
+    /// First failed tests, up to the summarizer's limit
+    pub failed_tests: Vec<TestFailure>,
+    pub tests_failed: usize,
+    pub tests_passed: usize,
+}
+
+impl BuildSummary {
+    /// Nothing recognizable was found in the output
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    pub fn has_failures(&self) -> bool {
+        self.error_count > 0 || self.tests_failed > 0
+    }
+
+    /// Counts line such as "3 errors, 12 warnings, 1 test failed"
+    pub fn counts(&self) -> String {
+        let mut parts = Vec::new();
+        if self.error_count > 0 {
+            parts.push(plural(self.error_count, "error", "errors"));
+        }
+        if self.warning_count > 0 {
+            parts.push(plural(self.warning_count, "warning", "warnings"));
+        }
+        if self.tests_failed > 0 {
+            parts.push(format!("{} failed", plural(self.tests_failed, "test", "tests")));
+        } else if self.tests_passed > 0 {
+            parts.push(format!("{} passed", plural(self.tests_passed, "test", "tests")));
+        }
+        parts.join(", ")
+    }
+
+    /// Compact plain-text body: counts, then one line per error and failed test
+    pub fn to_plain_text(&self) -> String {
+        let mut lines = vec![self.counts()];
+        lines.extend(self.errors.iter().map(ToString::to_string));
+        if let Some(more) = self.more_errors() {
+            lines.push(more);
+        }
+        lines.extend(self.failed_tests.iter().map(ToString::to_string));
+        if let Some(more) = self.more_tests() {
+            lines.push(more);
+        }
+        lines.retain(|line| !line.is_empty());
+        lines.join("\n")
+    }
+
+    /// Body with the counts in bold and locations as code
+    pub fn to_rich_text(&self) -> RichText {
+        let mut html = String::new();
+        let counts = self.counts();
+        if !counts.is_empty() {
+            html.push_str(&format!("<p><b>{}</b></p>", html_escape(&counts)));
+        }
+
+        let mut items = Vec::new();
+        for error in &self.errors {
+            let label = match &error.code {
+                Some(code) => format!("error[{}]", code),
+                None => "error".to_string(),
+            };
+            items.push(list_item(&label, &error.message, error.location.as_ref()));
+        }
+        items.extend(self.more_errors().map(|more| html_escape(&more)));
+        for test in &self.failed_tests {
+            let message = test.message.as_deref().unwrap_or("failed");
+            items.push(list_item(&test.name, message, test.location.as_ref()));
+        }
+        items.extend(self.more_tests().map(|more| html_escape(&more)));
+
+        if !items.is_empty() {
+            html.push_str("<ul>");
+            for item in items {
+                html.push_str(&format!("<li>{}</li>", item));
+            }
+            html.push_str("</ul>");
+        }
+        RichText::Html(html)
+    }
+
+    fn more_errors(&self) -> Option<String> {
+        let hidden = self.error_count.saturating_sub(self.errors.len());
+        (hidden > 0).then(|| format!("… and {}", plural(hidden, "more error", "more errors")))
+    }
+
+    fn more_tests(&self) -> Option<String> {
+        let hidden = self.tests_failed.saturating_sub(self.failed_tests.len());
+        (hidden > 0).then(|| format!("… and {}", plural(hidden, "more failed test", "more failed tests")))
+    }
+}
+
+fn plural(count: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", count, if count == 1 { singular } else { plural })
+}
+
+fn list_item(label: &str, message: &str, location: Option<&SourceLocation>) -> String {
+    let mut item = format!("<b>{}</b>: {}", html_escape(label), html_escape(message));
+    if let Some(location) = location {
+        item.push_str(&format!(" <code>{}</code>", html_escape(&location.to_string())));
+    }
+    item
+}
+
+/// Extracts errors, test failures and counts from build and test logs
+#[derive(Debug, Clone)]
+pub struct BuildOutputSummarizer {
+    max_errors: usize,
+    max_failed_tests: usize,
+}
+
+impl Default for BuildOutputSummarizer {
+    fn default() -> Self {
+        Self {
+            max_errors: 3,
+            max_failed_tests: 3,
+        }
+    }
+}
+
+impl BuildOutputSummarizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `max` errors in the summary (all are still counted)
+    pub fn with_max_errors(mut self, max: usize) -> Self {
+        self.max_errors = max;
+        self
+    }
+
+    /// Keep at most `max` failed tests in the summary (all are still counted)
+    pub fn with_max_failed_tests(mut self, max: usize) -> Self {
+        self.max_failed_tests = max;
+        self
+    }
+
+    /// Summarize a log, which may contain ANSI escape codes
+    pub fn summarize(&self, output: &str) -> BuildSummary {
+        let output = strip_ansi(output);
+        let mut parser = Parser::default();
+        for line in output.lines() {
+            parser.line(line.trim_end());
+        }
+        parser.finish(self)
+    }
+}
+
+/// Line-by-line state for `BuildOutputSummarizer::summarize`
+#[derive(Default)]
+struct Parser {
+    summary: BuildSummary,
+    errors: Vec<BuildDiagnostic>,
+    failures: Vec<TestFailure>,
+    /// The last rustc error is still waiting for its `-->` line
+    awaiting_location: bool,
+    /// Index of the failure whose panic or pytest block is being read
+    current_failure: Option<usize>,
+    awaiting_panic_message: bool,
+    saw_npm_error: bool,
+    reported_tests_failed: usize,
+}
+
+impl Parser {
+    fn line(&mut self, line: &str) {
+        if self.awaiting_panic_message && !line.trim().is_empty() {
+            self.awaiting_panic_message = false;
+            if let Some(failure) = self.current_failure.map(|index| &mut self.failures[index])
+                && failure.message.is_none()
+            {
+                failure.message = Some(line.trim().to_string());
+            }
+            return;
+        }
+
+        if let Some(captures) = RUSTC_LOCATION.captures(line) {
+            if self.awaiting_location
+                && let Some(error) = self.errors.last_mut()
+            {
+                error.location = SourceLocation::parse(&captures[1], &captures[2], Some(&captures[3]));
+            }
+            self.awaiting_location = false;
+            return;
+        }
+
+        if let Some(captures) = RUSTC_DIAGNOSTIC.captures(line) {
+            let message = &captures[3];
+            self.awaiting_location = false;
+            if CARGO_NOISE.iter().any(|noise| message.contains(noise)) {
+                return;
+            }
+            self.see(BuildTool::Cargo);
+            if &captures[1] == "error" {
+                self.errors.push(BuildDiagnostic {
+                    tool: BuildTool::Cargo,
+                    severity: DiagnosticSeverity::Error,
+                    code: captures.get(2).map(|code| code.as_str().to_string()),
+                    message: message.to_string(),
+                    location: None,
+                });
+                self.awaiting_location = true;
+            } else {
+                self.summary.warning_count += 1;
+            }
+            return;
+        }
+
+        if let Some(captures) = TSC_DIAGNOSTIC.captures(line) {
+            self.see(BuildTool::Tsc);
+            if &captures[6] == "error" {
+                let (line_number, column) = match (captures.get(2), captures.get(4)) {
+                    (Some(line_number), _) => (line_number, captures.get(3)),
+                    (None, Some(line_number)) => (line_number, captures.get(5)),
+                    (None, None) => return,
+                };
+                self.errors.push(BuildDiagnostic {
+                    tool: BuildTool::Tsc,
+                    severity: DiagnosticSeverity::Error,
+                    code: Some(captures[7].to_string()),
+                    message: captures[8].to_string(),
+                    location: SourceLocation::parse(
+                        &captures[1],
+                        line_number.as_str(),
+                        column.map(|column| column.as_str()),
+                    ),
+                });
+            } else {
+                self.summary.warning_count += 1;
+            }
+            return;
+        }
+
+        if let Some(captures) = NPM_ERROR.captures(line) {
+            let message = captures[1].trim();
+            // npm repeats the failure many ways; the first real line is the useful one
+            // Older npm also echoes the script as `pkg@1.0.0 build: `tsc``
+            let echoed_script = message.contains('@') && message.contains(": `");
+            if self.saw_npm_error
+                || message.is_empty()
+                || echoed_script
+                || NPM_NOISE.iter().any(|noise| message.starts_with(noise))
+            {
+                return;
+            }
+            self.see(BuildTool::Npm);
+            self.saw_npm_error = true;
+            self.errors.push(BuildDiagnostic {
+                tool: BuildTool::Npm,
+                severity: DiagnosticSeverity::Error,
+                code: None,
+                message: message.to_string(),
+                location: None,
+            });
+            return;
+        }
+
+        if let Some(captures) = CARGO_TEST_FAILED.captures(line) {
+            self.failure(BuildTool::CargoTest, &captures[1]);
+            return;
+        }
+        if let Some(captures) = CARGO_TEST_STDOUT.captures(line) {
+            self.current_failure = Some(self.failure(BuildTool::CargoTest, &captures[1]));
+            return;
+        }
+        if let Some(captures) = CARGO_PANIC.captures(line) {
+            let index = self.failure(BuildTool::CargoTest, &captures[1]);
+            self.failures[index].location =
+                SourceLocation::parse(&captures[2], &captures[3], Some(&captures[4]));
+            self.current_failure = Some(index);
+            self.awaiting_panic_message = true;
+            return;
+        }
+        if let Some(captures) = CARGO_PANIC_LEGACY.captures(line) {
+            let index = self.failure(BuildTool::CargoTest, &captures[1]);
+            let failure = &mut self.failures[index];
+            failure.message = Some(captures[2].to_string());
+            failure.location = SourceLocation::parse(&captures[3], &captures[4], Some(&captures[5]));
+            return;
+        }
+        if let Some(captures) = CARGO_TEST_RESULT.captures(line) {
+            self.see(BuildTool::CargoTest);
+            self.summary.tests_passed += captures[1].parse::<usize>().unwrap_or(0);
+            self.reported_tests_failed += captures[2].parse::<usize>().unwrap_or(0);
+            return;
+        }
+
+        if let Some(captures) = PYTEST_BLOCK.captures(line) {
+            self.current_failure = Some(self.failure(BuildTool::Pytest, &captures[1]));
+            return;
+        }
+        if let Some(message) = line.strip_prefix("E ").map(str::trim)
+            && let Some(failure) = self.pytest_failure()
+            && failure.message.is_none()
+        {
+            failure.message = Some(message.to_string());
+            return;
+        }
+        if let Some(captures) = PYTEST_LOCATION.captures(line)
+            && let Some(failure) = self.pytest_failure()
+        {
+            failure.location = SourceLocation::parse(&captures[1], &captures[2], None);
+            return;
+        }
+        if let Some(captures) = PYTEST_SHORT_SUMMARY.captures(line) {
+            self.pytest_summary(&captures[1], captures.get(2).map(|message| message.as_str()));
+            return;
+        }
+        if let Some(captures) = PYTEST_COUNTS.captures(line) {
+            self.see(BuildTool::Pytest);
+            for count in PYTEST_COUNT.captures_iter(&captures[1]) {
+                let value = count[1].parse::<usize>().unwrap_or(0);
+                match &count[2] {
+                    "passed" => self.summary.tests_passed += value,
+                    _ => self.reported_tests_failed += value,
+                }
+            }
+        }
+    }
+
+    fn see(&mut self, tool: BuildTool) {
+        if !self.summary.tools.contains(&tool) {
+            self.summary.tools.push(tool);
+        }
+    }
+
+    /// Index of the failure named `name`, recording it if new
+    fn failure(&mut self, tool: BuildTool, name: &str) -> usize {
+        self.see(tool);
+        if let Some(index) = self.failures.iter().position(|failure| failure.name == name) {
+            return index;
+        }
+        self.failures.push(TestFailure {
+            tool,
+            name: name.to_string(),
+            message: None,
+            location: None,
+        });
+        self.failures.len() - 1
+    }
+
+    fn pytest_failure(&mut self) -> Option<&mut TestFailure> {
+        self.current_failure
+            .map(|index| &mut self.failures[index])
+            .filter(|failure| failure.tool == BuildTool::Pytest)
+    }
+
+    /// Merge a `FAILED path::Class::test - message` line into its failure block
+    fn pytest_summary(&mut self, node_id: &str, message: Option<&str>) {
+        self.current_failure = None;
+        // Failure blocks are headed by the node id without the path, `::` written as `.`
+        let block_name = node_id
+            .split_once("::")
+            .map(|(_, name)| name.replace("::", "."))
+            .unwrap_or_default();
+        let existing = self.failures.iter().position(|failure| {
+            failure.tool == BuildTool::Pytest
+                && (failure.name == block_name || failure.name == node_id)
+        });
+        let index = match existing {
+            Some(index) => index,
+            None => self.failure(BuildTool::Pytest, node_id),
+        };
+
+        let failure = &mut self.failures[index];
+        failure.name = node_id.to_string();
+        if failure.message.is_none() {
+            failure.message = message.map(str::to_string);
+        }
+    }
+
+    fn finish(mut self, summarizer: &BuildOutputSummarizer) -> BuildSummary {
+        self.summary.error_count = self.errors.len();
+        self.summary.tests_failed = self.reported_tests_failed.max(self.failures.len());
+
+        self.errors.truncate(summarizer.max_errors);
+        self.failures.truncate(summarizer.max_failed_tests);
+        self.summary.errors = self.errors;
+        self.summary.failed_tests = self.failures;
+        self.summary
+    }
+}
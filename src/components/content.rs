@@ -8,7 +8,16 @@ use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-use super::{NotificationCategory, NotificationError, NotificationResult, Priority};
+use super::ansi::{parse_ansi, strip_ansi};
+use super::platform::{ActionFallback, FeatureDegradation, MarkupFallback, MediaFallback};
+use super::rich_text::{PangoSubset, RichDocument};
+use super::{ErrorKind, NotificationCategory, NotificationError, NotificationResult, Priority};
+
+/// `custom_data` key holding the deduplication key set by `with_dedup_key`
+pub const DEDUP_KEY: &str = "dedup_key";
+
+/// `custom_data` key holding the ID of the notification set by `with_replaces`
+pub const REPLACES_KEY: &str = "replaces";
 
 /// Comprehensive notification content supporting rich media and complex interactions
 /// Incorporates patterns from Slack's rich messaging, Discord's media handling,
@@ -97,6 +106,24 @@ impl NotificationContent {
         self
     }
 
+    /// Replace the last shown notification with the same key instead of stacking a new one
+    pub fn with_dedup_key(self, key: impl Into<String>) -> Self {
+        self.with_custom_data(DEDUP_KEY, key)
+    }
+
+    pub fn dedup_key(&self) -> Option<&str> {
+        self.custom_data.get(DEDUP_KEY).map(String::as_str)
+    }
+
+    /// Show in place of an earlier notification, on platforms that can replace notifications
+    pub fn with_replaces(self, id: impl ToString) -> Self {
+        self.with_custom_data(REPLACES_KEY, id.to_string())
+    }
+
+    pub fn replaces(&self) -> Option<&str> {
+        self.custom_data.get(REPLACES_KEY).map(String::as_str)
+    }
+
     /// Validate content against platform constraints and security requirements
     pub fn validate(&mut self, platform_limits: &HashMap<String, usize>) -> NotificationResult<()> {
         // Title validation
@@ -142,6 +169,57 @@ impl NotificationContent {
         Ok(())
     }
 
+    /// Features this content relies on, named as in `PlatformCapabilities::supports_feature`
+    pub fn requested_features(&self) -> Vec<String> {
+        let mut features = Vec::new();
+        if !self.interactions.actions.is_empty() {
+            features.push("actions".to_string());
+        }
+        if !self.media.is_empty() {
+            features.push("rich_media".to_string());
+        }
+        if !matches!(self.body, RichText::Plain(_)) {
+            features.push("markup".to_string());
+        }
+        if !self.interactions.quick_replies.is_empty() {
+            features.push("replies".to_string());
+        }
+        features
+    }
+
+    /// Drop or flatten what a degradation from `PlatformIntegration::apply_degradation` names
+    ///
+    /// Unsupported actions and replies are removed, markup is flattened to plain
+    /// text unless it can be converted, and media is dropped (all but images
+    /// when simplified).
+    pub fn degrade(&mut self, degradation: &FeatureDegradation) {
+        match degradation {
+            FeatureDegradation::ActionFallback(
+                ActionFallback::RemoveActions
+                | ActionFallback::SimplifyActions
+                | ActionFallback::ConvertToUrls
+                | ActionFallback::BatchIntoMenu,
+            ) => self.interactions.actions.clear(),
+            FeatureDegradation::MarkupFallback(MarkupFallback::ConvertMarkup) => {}
+            // Lists and paragraphs stay readable as plain text
+            FeatureDegradation::MarkupFallback(MarkupFallback::StripMarkup | MarkupFallback::FormattingHints) => {
+                self.body = RichText::plain(self.body.to_structured_plain_text());
+            }
+            FeatureDegradation::MediaFallback(MediaFallback::SimplifyMedia) => {
+                self.media.retain(|media| matches!(media, MediaAttachment::Image { .. }));
+            }
+            FeatureDegradation::MediaFallback(
+                MediaFallback::RemoveMedia | MediaFallback::TextDescription | MediaFallback::UsePlaceholder,
+            ) => self.media.clear(),
+            FeatureDegradation::FeatureRemoved(feature) if feature == "replies" => {
+                self.interactions.quick_replies.clear();
+            }
+            FeatureDegradation::FeatureRemoved(_)
+            | FeatureDegradation::FeatureSubstitution { .. }
+            | FeatureDegradation::CriticalUnsupported { .. } => {}
+        }
+    }
+
     /// Sanitize content for security (XSS prevention, injection protection)
     fn sanitize_content(&mut self) -> NotificationResult<()> {
         // HTML sanitization for body content
@@ -189,6 +267,8 @@ pub enum RichText {
     Markdown(String),
     /// HTML content (sanitized, platform support varies)
     Html(String),
+    /// Terminal output with ANSI escape codes (SGR styling is kept, other sequences dropped)
+    Ansi(String),
     /// Platform-specific formatted text
     PlatformSpecific(HashMap<String, String>),
 }
@@ -206,35 +286,47 @@ impl RichText {
         Self::Html(text.into())
     }
 
+    pub fn ansi(text: impl Into<String>) -> Self {
+        Self::Ansi(text.into())
+    }
+
+    /// Parse into the shared document model every renderer works from
+    ///
+    /// Plain text maps blank lines to paragraphs and newlines to line breaks.
+    pub fn to_document(&self) -> RichDocument {
+        match self {
+            RichText::Plain(text) => RichDocument::from_plain(text),
+            RichText::Markdown(md) => RichDocument::parse_markdown(md),
+            RichText::Html(html) => RichDocument::parse_html(html),
+            RichText::Ansi(text) => parse_ansi(text),
+            RichText::PlatformSpecific(map) => map
+                .get("plain")
+                .map(|plain| RichDocument::from_plain(plain))
+                .unwrap_or_default(),
+        }
+    }
+
     /// Convert to plain text for platforms that don't support markup
     pub fn to_plain_text(&self) -> String {
         match self {
             RichText::Plain(text) => text.clone(),
-            RichText::Markdown(md) => convert_markdown_to_plain(md),
-            RichText::Html(html) => convert_html_to_plain(html),
+            RichText::Ansi(text) => strip_ansi(text),
             RichText::PlatformSpecific(map) => map
                 .get("plain")
                 .or_else(|| map.values().next())
                 .cloned()
                 .unwrap_or_default(),
+            _ => self.to_document().to_plain_text(),
         }
     }
 
     /// Convert to HTML for platforms that support it
     pub fn to_html(&self) -> String {
         match self {
-            RichText::Plain(text) => html_escape(text),
-            RichText::Markdown(md) => convert_markdown_to_html(md),
-            RichText::Html(html) => html.clone(),
-            RichText::PlatformSpecific(map) => {
-                if let Some(html) = map.get("html") {
-                    html.clone()
-                } else if let Some(plain) = map.get("plain") {
-                    html_escape(plain)
-                } else {
-                    String::default()
-                }
-            },
+            RichText::PlatformSpecific(map) if map.contains_key("html") => {
+                RichDocument::parse_html(&map["html"]).to_html()
+            }
+            _ => self.to_document().to_html(),
         }
     }
 
@@ -260,6 +352,7 @@ impl RichText {
             RichText::Plain(text) => text.len(),
             RichText::Markdown(md) => md.len(),
             RichText::Html(html) => html.len(),
+            RichText::Ansi(text) => text.len(),
             RichText::PlatformSpecific(map) => map.values().map(|s| s.len()).sum(),
         }
     }
@@ -268,15 +361,17 @@ impl RichText {
     /// Supports: <b>, <i>, <u>, <s>, <tt>, <a href="...">
     pub fn to_pango_markup(&self) -> String {
         match self {
-            RichText::Plain(text) => pango_escape(text),
-            RichText::Markdown(md) => convert_markdown_to_pango(md),
-            RichText::Html(html) => convert_html_to_pango(html),
-            RichText::PlatformSpecific(map) => {
-                map.get("pango")
-                    .or_else(|| map.get("plain"))
-                    .map(|s| pango_escape(s))
-                    .unwrap_or_default()
-            }
+            RichText::PlatformSpecific(map) if map.contains_key("pango") => map["pango"].clone(),
+            _ => self.to_document().to_pango_markup(),
+        }
+    }
+
+    /// Pango markup limited to the tags a server renders; platform-specific
+    /// `pango` markup is passed through as written
+    pub fn to_pango_markup_subset(&self, subset: PangoSubset) -> String {
+        match self {
+            RichText::PlatformSpecific(map) if map.contains_key("pango") => map["pango"].clone(),
+            _ => self.to_document().to_pango_markup_subset(subset),
         }
     }
 
@@ -286,13 +381,17 @@ impl RichText {
     pub fn to_structured_plain_text(&self) -> String {
         match self {
             RichText::Plain(text) => text.clone(),
-            RichText::Markdown(md) => convert_markdown_to_structured_plain(md),
-            RichText::Html(html) => convert_html_to_structured_plain(html),
-            RichText::PlatformSpecific(map) => {
-                map.get("plain")
-                    .cloned()
-                    .unwrap_or_default()
-            }
+            RichText::Ansi(text) => strip_ansi(text),
+            RichText::PlatformSpecific(map) => map.get("plain").cloned().unwrap_or_default(),
+            _ => self.to_document().to_structured_plain_text(),
+        }
+    }
+
+    /// Convert to escaped text for a Windows toast `<text>` element
+    pub fn to_toast_text(&self) -> String {
+        match self {
+            RichText::PlatformSpecific(map) if map.contains_key("toast") => map["toast"].clone(),
+            _ => self.to_document().to_toast_text(),
         }
     }
 
@@ -367,18 +466,9 @@ impl MediaAttachment {
     pub fn validate(&self, platform_limits: &HashMap<String, usize>) -> NotificationResult<()> {
         match self {
             MediaAttachment::Image { data, .. } => {
+                // Oversized images are downscaled and re-encoded to `max_image_size`
+                // by the backends' image processing stage, so size is not checked here
                 data.validate(platform_limits)?;
-
-                if let Some(&max_image_size) = platform_limits.get("max_image_size")
-                    && data.estimated_size() > max_image_size {
-                        return Err(NotificationError::ValidationError {
-                            field: "image_size".to_string(),
-                            message: format!(
-                                "Image exceeds maximum size of {} bytes",
-                                max_image_size
-                            ),
-                        });
-                    }
             },
             MediaAttachment::Audio { source, .. } => {
                 source.validate()?;
@@ -393,7 +483,9 @@ impl MediaAttachment {
                     return Err(NotificationError::ResourceError {
                         resource_type: "file".to_string(),
                         resource_id: path.display().to_string(),
+                        kind: ErrorKind::NotFound,
                         message: "File does not exist".to_string(),
+                        source: None,
                     });
                 }
 
@@ -444,18 +536,24 @@ impl ImageData {
                     return Err(NotificationError::ResourceError {
                         resource_type: "image_file".to_string(),
                         resource_id: path.display().to_string(),
+                        kind: ErrorKind::NotFound,
                         message: "Image file does not exist".to_string(),
+                        source: None,
                     });
                 }
             },
             ImageData::Url(url) => {
-                if url.scheme() == "file" {
+                if url.scheme() == "data" {
+                    decode_image_data_url(url)?;
+                } else if url.scheme() == "file" {
                     let path = PathBuf::from(url.path());
                     if !path.exists() {
                         return Err(NotificationError::ResourceError {
                             resource_type: "image_url".to_string(),
                             resource_id: url.to_string(),
+                            kind: ErrorKind::NotFound,
                             message: "Image file does not exist".to_string(),
+                            source: None,
                         });
                     }
                 }
@@ -477,7 +575,7 @@ impl ImageData {
                 }
             },
             ImageData::SystemIcon(_) => {
-                // System icons are always valid
+                // Looked up in the icon theme when sent to Linux, see `validate_system_icons`
             },
         }
         Ok(())
@@ -501,6 +599,64 @@ impl ImageData {
     }
 }
 
+/// Decode a `data:image/...` URL into its bytes and format without any I/O
+///
+/// Both `;base64` and percent-encoded payloads are accepted. The media type
+/// must name one of the [`ImageFormat`]s.
+pub fn decode_image_data_url(url: &Url) -> NotificationResult<(Vec<u8>, ImageFormat)> {
+    use base64::Engine as _;
+    use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+
+    const BASE64: GeneralPurpose = GeneralPurpose::new(
+        &base64::alphabet::STANDARD,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+
+    let invalid = |message: String| NotificationError::ValidationError {
+        field: "image_url".to_string(),
+        message,
+    };
+
+    let rest = url
+        .as_str()
+        .strip_prefix("data:")
+        .ok_or_else(|| invalid("Not a data: URL".to_string()))?;
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (header, payload) = rest
+        .split_once(',')
+        .ok_or_else(|| invalid("data: URL is missing the ',' before its payload".to_string()))?;
+
+    let mut params = header.split(';');
+    let mime = params.next().unwrap_or_default().trim().to_ascii_lowercase();
+    let is_base64 = params.any(|param| param.trim().eq_ignore_ascii_case("base64"));
+
+    let format = ImageFormat::from_mime_type(&mime).ok_or_else(|| {
+        invalid(format!(
+            "data: URL media type '{}' is not a supported image type",
+            if mime.is_empty() { "text/plain" } else { &mime }
+        ))
+    })?;
+
+    let decoded: Vec<u8> = percent_encoding::percent_decode_str(payload).collect();
+    let data = if is_base64 {
+        let compact: Vec<u8> = decoded
+            .into_iter()
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .collect();
+        BASE64
+            .decode(compact)
+            .map_err(|e| invalid(format!("Invalid base64 in data: URL: {}", e)))?
+    } else {
+        decoded
+    };
+
+    if data.is_empty() {
+        return Err(invalid("data: URL image payload is empty".to_string()));
+    }
+
+    Ok((data, format))
+}
+
 /// Image placement options for different UI contexts
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ImagePlacement {
@@ -535,6 +691,18 @@ impl ImageFormat {
         )
     }
 
+    pub fn from_mime_type(mime: &str) -> Option<Self> {
+        match mime {
+            "image/png" => Some(ImageFormat::Png),
+            "image/jpeg" | "image/jpg" => Some(ImageFormat::Jpeg),
+            "image/gif" => Some(ImageFormat::Gif),
+            "image/webp" => Some(ImageFormat::WebP),
+            "image/svg+xml" => Some(ImageFormat::Svg),
+            "image/x-icon" | "image/vnd.microsoft.icon" => Some(ImageFormat::Ico),
+            _ => None,
+        }
+    }
+
     pub fn mime_type(&self) -> &'static str {
         match self {
             ImageFormat::Png => "image/png",
@@ -566,11 +734,15 @@ impl AudioSource {
     pub fn validate(&self) -> NotificationResult<()> {
         match self {
             AudioSource::File(path) => {
-                if !path.exists() {
+                // Relative paths may name a file in the sound theme directories,
+                // which the backends resolve at delivery time
+                if path.is_absolute() && !path.exists() {
                     return Err(NotificationError::ResourceError {
                         resource_type: "audio_file".to_string(),
                         resource_id: path.display().to_string(),
+                        kind: ErrorKind::NotFound,
                         message: "Audio file does not exist".to_string(),
+                        source: None,
                     });
                 }
             },
@@ -581,7 +753,9 @@ impl AudioSource {
                         return Err(NotificationError::ResourceError {
                             resource_type: "audio_url".to_string(),
                             resource_id: url.to_string(),
+                            kind: ErrorKind::NotFound,
                             message: "Audio file does not exist".to_string(),
+                            source: None,
                         });
                     }
                 }
@@ -643,7 +817,9 @@ impl VideoSource {
                     return Err(NotificationError::ResourceError {
                         resource_type: "video_file".to_string(),
                         resource_id: path.display().to_string(),
+                        kind: ErrorKind::NotFound,
                         message: "Video file does not exist".to_string(),
+                        source: None,
                     });
                 }
             },
@@ -1031,7 +1207,7 @@ pub enum ValidationState {
 
 // Utility functions for content processing
 
-fn sanitize_html(html: &str) -> NotificationResult<String> {
+pub(crate) fn sanitize_html(html: &str) -> NotificationResult<String> {
     use ammonia::Builder;
 
     // ammonia::Builder.clean() returns a Cow<str> (not Result)
@@ -1060,117 +1236,7 @@ fn sanitize_string(input: &str) -> String {
     input.replace(['<', '>', '"', '\''], "")
 }
 
-fn convert_markdown_to_plain(markdown: &str) -> String {
-    use pulldown_cmark::{Parser, Event, Options};
-
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_STRIKETHROUGH);
-    options.insert(Options::ENABLE_TABLES);
-    
-    let parser = Parser::new_ext(markdown, options);
-    let mut plain_text = String::new();
-
-    // Extract only text content, ignore all formatting
-    for event in parser {
-        match event {
-            Event::Text(text) | Event::Code(text) => {
-                plain_text.push_str(&text);
-            }
-            Event::SoftBreak => {
-                plain_text.push(' ');
-            }
-            Event::HardBreak => {
-                plain_text.push('\n');
-            }
-            // Ignore all other events (tags, HTML, etc.)
-            _ => {}
-        }
-    }
-
-    plain_text
-}
-
-fn convert_markdown_to_html(markdown: &str) -> String {
-    use pulldown_cmark::{Parser, html, Options, Event, Tag};
-
-    // Enable safe CommonMark features
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_STRIKETHROUGH);
-    options.insert(Options::ENABLE_TABLES);
-    
-    let parser = Parser::new_ext(markdown, options);
-
-    // Filter events to remove dangerous content
-    let safe_parser = parser.filter_map(|event| {
-        match event {
-            // Block ALL raw HTML from markdown
-            Event::Html(_) | Event::InlineHtml(_) => None,
-
-            // Sanitize link URLs
-            Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
-                // Only allow http/https links
-                if dest_url.starts_with("http://") || dest_url.starts_with("https://") {
-                    Some(Event::Start(Tag::Link { link_type, dest_url, title, id }))
-                } else if dest_url.starts_with("#") {
-                    // Allow anchor links
-                    Some(Event::Start(Tag::Link { link_type, dest_url, title, id }))
-                } else {
-                    // Block javascript:, data:, and other dangerous protocols
-                    None
-                }
-            }
-
-            // Block potentially dangerous image sources
-            Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
-                // Only allow http/https images
-                if dest_url.starts_with("http://") || dest_url.starts_with("https://") {
-                    Some(Event::Start(Tag::Image { link_type, dest_url, title, id }))
-                } else {
-                    // Block data: URIs and other potentially dangerous sources
-                    None
-                }
-            }
-
-            // Pass through all other safe markdown elements
-            _ => Some(event),
-        }
-    });
-
-    // Generate HTML from filtered events
-    let mut html_output = String::new();
-    html::push_html(&mut html_output, safe_parser);
-
-    // Double-sanitize: run through ammonia to catch any edge cases
-    // This handles any HTML that might have been generated by the markdown parser
-    sanitize_html(&html_output).unwrap_or(html_output)
-}
-
-fn convert_html_to_plain(html: &str) -> String {
-    // Decode HTML entities first
-    let decoded = decode_html_entities(html);
-    
-    // Handle block-level elements: convert to newlines for structure preservation
-    // Case-insensitive regex handles attributes, self-closing variants (e.g., <br/>, <BR>)
-    let step1 = match regex::Regex::new(r"(?i)</?(?:p|div|br\s*/?\s*|h[1-6]|li|ul|ol|table|tr|td|th|blockquote|pre)[^>]*>") {
-        Ok(re) => re.replace_all(&decoded, "\n").to_string(),
-        Err(_) => decoded.clone(), // Fallback: return decoded string if regex compilation fails
-    };
-    
-    // Remove all remaining HTML tags (inline elements like strong, em, a, span, etc.)
-    let step2 = match regex::Regex::new(r"<[^>]+>") {
-        Ok(re) => re.replace_all(&step1, "").to_string(),
-        Err(_) => step1, // Fallback: return as-is if regex compilation fails
-    };
-    
-    // Normalize whitespace: trim lines, remove empty lines, collapse multiple newlines
-    step2.lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .collect::<Vec<_>>()
-        .join("\n")
-}
-
-fn decode_html_entities(html: &str) -> String {
+pub(crate) fn decode_html_entities(html: &str) -> String {
     let mut result = html.to_string();
     
     // Common named entities (ordered by frequency and importance)
@@ -1193,268 +1259,3 @@ fn decode_html_entities(html: &str) -> String {
     
     result
 }
-
-fn html_escape(text: &str) -> String {
-    text.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&#x27;")
-}
-
-/// Escape text for Pango markup (XML-like escaping)
-fn pango_escape(text: &str) -> String {
-    text.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-}
-
-/// Convert HTML to Pango markup format
-fn convert_html_to_pango(html: &str) -> String {
-    let decoded = decode_html_entities(html);
-    let mut result = decoded;
-
-    // Bold: <strong>, <b> -> <b>
-    if let Ok(re) = regex::Regex::new(r"(?i)<strong[^>]*>") {
-        result = re.replace_all(&result, "<b>").to_string();
-    }
-    result = result.replace("</strong>", "</b>").replace("</STRONG>", "</b>");
-
-    // Italic: <em>, <i> -> <i>
-    if let Ok(re) = regex::Regex::new(r"(?i)<em[^>]*>") {
-        result = re.replace_all(&result, "<i>").to_string();
-    }
-    result = result.replace("</em>", "</i>").replace("</EM>", "</i>");
-
-    // Code: <code>, <pre> -> <tt>
-    if let Ok(re) = regex::Regex::new(r"(?i)<code[^>]*>") {
-        result = re.replace_all(&result, "<tt>").to_string();
-    }
-    result = result.replace("</code>", "</tt>").replace("</CODE>", "</tt>");
-    if let Ok(re) = regex::Regex::new(r"(?i)<pre[^>]*>") {
-        result = re.replace_all(&result, "<tt>").to_string();
-    }
-    result = result.replace("</pre>", "</tt>").replace("</PRE>", "</tt>");
-
-    // Underline: <u> stays as <u>
-    // Strikethrough: <s>, <del>, <strike> -> <s>
-    if let Ok(re) = regex::Regex::new(r"(?i)<(del|strike)[^>]*>") {
-        result = re.replace_all(&result, "<s>").to_string();
-    }
-    if let Ok(re) = regex::Regex::new(r"(?i)</(del|strike)>") {
-        result = re.replace_all(&result, "</s>").to_string();
-    }
-
-    // Links: preserve <a href="..."> but strip other attributes
-    if let Ok(re) = regex::Regex::new(r#"(?i)<a\s+[^>]*href="([^"]+)"[^>]*>"#) {
-        result = re.replace_all(&result, r#"<a href="$1">"#).to_string();
-    }
-
-    // Convert block elements to newlines
-    if let Ok(re) = regex::Regex::new(r"(?i)</?(div|p|li|tr)[^>]*>") {
-        result = re.replace_all(&result, "\n").to_string();
-    }
-    if let Ok(re) = regex::Regex::new(r"(?i)<br\s*/?\s*>") {
-        result = re.replace_all(&result, "\n").to_string();
-    }
-
-    // Strip remaining unsupported tags (img, span, table, etc.)
-    // Keep: b, i, u, s, tt, a
-    if let Ok(re) = regex::Regex::new(r"</?([a-zA-Z][a-zA-Z0-9]*)[^>]*>") {
-        let allowed_tags = ["b", "i", "u", "s", "tt", "a"];
-        result = re.replace_all(&result, |caps: &regex::Captures| {
-            let tag_name = caps.get(1).map_or("", |m| m.as_str()).to_lowercase();
-            if allowed_tags.contains(&tag_name.as_str()) {
-                caps.get(0).map_or("", |m| m.as_str()).to_string()
-            } else {
-                String::new()
-            }
-        }).to_string();
-    }
-
-    // Clean up whitespace
-    if let Ok(re) = regex::Regex::new(r"\n{3,}") {
-        result = re.replace_all(&result, "\n\n").to_string();
-    }
-
-    result.trim().to_string()
-}
-
-/// Convert Markdown to Pango markup
-fn convert_markdown_to_pango(markdown: &str) -> String {
-    use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
-
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_STRIKETHROUGH);
-
-    let parser = Parser::new_ext(markdown, options);
-    let mut pango = String::new();
-    let mut tag_stack: Vec<&str> = Vec::new();
-
-    for event in parser {
-        match event {
-            Event::Start(tag) => match tag {
-                Tag::Strong => {
-                    pango.push_str("<b>");
-                    tag_stack.push("b");
-                }
-                Tag::Emphasis => {
-                    pango.push_str("<i>");
-                    tag_stack.push("i");
-                }
-                Tag::Strikethrough => {
-                    pango.push_str("<s>");
-                    tag_stack.push("s");
-                }
-                Tag::CodeBlock(_) => {
-                    pango.push_str("<tt>");
-                    tag_stack.push("tt");
-                }
-                Tag::Link { dest_url, .. } => {
-                    pango.push_str(&format!(r#"<a href="{}">"#, pango_escape(&dest_url)));
-                    tag_stack.push("a");
-                }
-                Tag::Paragraph => {}
-                _ => {}
-            },
-            Event::End(tag_end) => match tag_end {
-                TagEnd::Strong | TagEnd::Emphasis | TagEnd::Strikethrough
-                | TagEnd::CodeBlock | TagEnd::Link => {
-                    if let Some(t) = tag_stack.pop() {
-                        pango.push_str(&format!("</{}>", t));
-                    }
-                }
-                TagEnd::Paragraph => pango.push_str("\n\n"),
-                _ => {}
-            },
-            Event::Text(text) => pango.push_str(&pango_escape(&text)),
-            Event::Code(code) => pango.push_str(&format!("<tt>{}</tt>", pango_escape(&code))),
-            Event::SoftBreak => pango.push(' '),
-            Event::HardBreak => pango.push('\n'),
-            _ => {}
-        }
-    }
-
-    pango.trim().to_string()
-}
-
-/// Convert HTML to structured plain text preserving semantic structure
-fn convert_html_to_structured_plain(html: &str) -> String {
-    let decoded = decode_html_entities(html);
-    let mut result = decoded;
-
-    // Convert <pre>/<code> blocks to indented text
-    if let Ok(re) = regex::Regex::new(r"(?is)<pre[^>]*>(.*?)</pre>") {
-        result = re.replace_all(&result, |caps: &regex::Captures| {
-            let code = caps.get(1).map_or("", |m| m.as_str());
-            let code_plain = regex::Regex::new(r"<[^>]+>")
-                .map(|re| re.replace_all(code, "").to_string())
-                .unwrap_or_else(|_| code.to_string());
-            // Indent each line with 2 spaces
-            let indented: String = code_plain
-                .lines()
-                .map(|line| format!("  {}", line))
-                .collect::<Vec<_>>()
-                .join("\n");
-            format!("\n{}\n", indented)
-        }).to_string();
-    }
-
-    // Convert links to "text (url)" format
-    if let Ok(re) = regex::Regex::new(r#"(?i)<a\s+[^>]*href="([^"]+)"[^>]*>([^<]*)</a>"#) {
-        result = re.replace_all(&result, "$2 ($1)").to_string();
-    }
-
-    // Convert lists to bullet points
-    if let Ok(re) = regex::Regex::new(r"(?i)<li[^>]*>") {
-        result = re.replace_all(&result, "\n• ").to_string();
-    }
-
-    // Block elements to newlines
-    if let Ok(re) = regex::Regex::new(r"(?i)</?(div|p|tr|ul|ol)[^>]*>") {
-        result = re.replace_all(&result, "\n").to_string();
-    }
-    if let Ok(re) = regex::Regex::new(r"(?i)<br\s*/?\s*>") {
-        result = re.replace_all(&result, "\n").to_string();
-    }
-
-    // Strip all remaining HTML tags
-    if let Ok(re) = regex::Regex::new(r"<[^>]+>") {
-        result = re.replace_all(&result, "").to_string();
-    }
-
-    // Clean up whitespace
-    if let Ok(re) = regex::Regex::new(r"\n{3,}") {
-        result = re.replace_all(&result, "\n\n").to_string();
-    }
-
-    result.trim().to_string()
-}
-
-/// Convert Markdown to structured plain text
-fn convert_markdown_to_structured_plain(markdown: &str) -> String {
-    use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
-
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_STRIKETHROUGH);
-
-    let parser = Parser::new_ext(markdown, options);
-    let mut output = String::new();
-    let mut in_code_block = false;
-    let mut list_depth: usize = 0;
-
-    for event in parser {
-        match event {
-            Event::Start(tag) => match tag {
-                Tag::CodeBlock(_) => {
-                    in_code_block = true;
-                    output.push('\n');
-                }
-                Tag::List(_) => list_depth += 1,
-                Tag::Item => {
-                    output.push('\n');
-                    output.push_str(&"  ".repeat(list_depth.saturating_sub(1)));
-                    output.push_str("• ");
-                }
-                Tag::Link { .. } => {
-                    // Will capture text and append URL after
-                }
-                _ => {}
-            },
-            Event::End(tag_end) => match tag_end {
-                TagEnd::CodeBlock => {
-                    in_code_block = false;
-                    output.push('\n');
-                }
-                TagEnd::List(_) => list_depth = list_depth.saturating_sub(1),
-                TagEnd::Paragraph => output.push_str("\n\n"),
-                TagEnd::Link => {
-                    // Link URL already captured via Text events
-                }
-                _ => {}
-            },
-            Event::Text(text) => {
-                if in_code_block {
-                    // Indent code lines
-                    for line in text.lines() {
-                        output.push_str("  ");
-                        output.push_str(line);
-                        output.push('\n');
-                    }
-                } else {
-                    output.push_str(&text);
-                }
-            }
-            Event::Code(code) => {
-                output.push('`');
-                output.push_str(&code);
-                output.push('`');
-            }
-            Event::SoftBreak => output.push(' '),
-            Event::HardBreak => output.push('\n'),
-            _ => {}
-        }
-    }
-
-    output.trim().to_string()
-}
@@ -12,6 +12,8 @@ use super::{CorrelationId, DeliveryReceipt, NotificationError, NotificationResul
 
 // Capacity limits for bounded vectors to prevent unbounded memory growth
 const MAX_STATE_HISTORY: usize = 50;
+/// Transitions a watcher may fall behind by before it misses some
+const TRANSITION_FEED_CAPACITY: usize = 32;
 const MAX_STATE_TRANSITIONS: usize = 50;
 
 /// Comprehensive notification lifecycle management component
@@ -34,6 +36,9 @@ pub struct NotificationLifecycle {
     pub state_history: VecDeque<StateTransition>,
     /// Performance metrics collection
     pub performance_metrics: PerformanceMetrics,
+    /// Watchers of this notification's transitions
+    #[serde(skip)]
+    pub transitions: TransitionFeed,
 }
 
 impl NotificationLifecycle {
@@ -56,6 +61,7 @@ impl NotificationLifecycle {
             delivery_receipt: None,
             state_history,
             performance_metrics: PerformanceMetrics::new(),
+            transitions: TransitionFeed::default(),
         }
     }
 
@@ -143,7 +149,8 @@ impl NotificationLifecycle {
         if self.state_history.len() >= MAX_STATE_HISTORY {
             self.state_history.pop_front();
         }
-        self.state_history.push_back(transition);
+        self.state_history.push_back(transition.clone());
+        self.transitions.publish(transition);
 
         // Update performance metrics
         self.performance_metrics
@@ -190,11 +197,58 @@ impl NotificationLifecycle {
         false
     }
 
+    /// Check if a delivered notification has waited longer than `interaction_timeout`
+    ///
+    /// Only meaningful for notifications the user is expected to act on, so
+    /// [`Self::is_expired`] leaves the decision to the caller.
+    pub fn interaction_timed_out(&self) -> bool {
+        matches!(
+            self.state,
+            NotificationState::Delivered | NotificationState::InteractionPending
+        ) && self
+            .timing
+            .delivered_at
+            .is_some_and(|delivered| delivered.elapsed() > self.expiration.interaction_timeout)
+    }
+
     /// Check if notification should be retried based on policy
+    ///
+    /// Failures every platform reported as permanent aren't retried.
     pub fn should_retry(&self) -> bool {
-        matches!(self.state, NotificationState::Failed(_))
-            && self.retry_policy.current_attempt < self.retry_policy.max_attempts
+        matches!(self.state, NotificationState::Failed(_)) && self.retries_left()
+    }
+
+    /// Whether the retry policy allows another attempt after a failure
+    pub fn retries_left(&self) -> bool {
+        self.retry_policy.current_attempt < self.retry_policy.max_attempts
             && self.retry_policy.circuit_breaker_state == CircuitBreakerState::Closed
+            && !self.failed_permanently()
+    }
+
+    /// Whether a retry is allowed and its backoff delay has passed since the last attempt
+    pub fn retry_due(&self) -> bool {
+        self.should_retry()
+            && self
+                .retry_policy
+                .last_attempt
+                .is_none_or(|last| last.elapsed() >= self.next_retry_delay())
+    }
+
+    /// Whether nothing more will happen: a terminal state, or a failure that won't be retried
+    pub fn is_finished(&self) -> bool {
+        self.state.is_terminal()
+            || (matches!(self.state, NotificationState::Failed(_)) && !self.should_retry())
+    }
+
+    /// Whether some platform failed and every failed platform's error is permanent
+    pub fn failed_permanently(&self) -> bool {
+        let mut failed = self
+            .platform_states
+            .values()
+            .filter(|state| matches!(state.status, PlatformDeliveryStatus::Failed(_)))
+            .peekable();
+        failed.peek().is_some()
+            && failed.all(|state| state.error_details.as_ref().is_some_and(|error| error.is_permanent))
     }
 
     /// Calculate next retry delay using exponential backoff
@@ -308,7 +362,7 @@ pub enum NotificationState {
     ProcessingResponse,
     /// Notification updated with new content
     Updated,
-    /// Notification cancelled before delivery
+    /// Notification cancelled, before delivery or by taking it down
     Cancelled,
     /// Notification expired (TTL reached)
     Expired,
@@ -358,28 +412,34 @@ impl NotificationState {
             (Delivered, Updated) => true,
             (Delivered, Expired) => true,
             (Delivered, Completed) => true,
+            (Delivered, Cancelled) => true,
 
             // From InteractionPending
             (InteractionPending, InteractionReceived) => true,
             (InteractionPending, ProcessingResponse) => true,
             (InteractionPending, Expired) => true,
+            (InteractionPending, Cancelled) => true,
 
             // From InteractionReceived
             (InteractionReceived, ProcessingResponse) => true,
             (InteractionReceived, Completed) => true,
+            (InteractionReceived, Cancelled) => true,
 
             // From ProcessingResponse
             (ProcessingResponse, InteractionPending) => true,
             (ProcessingResponse, Completed) => true,
             (ProcessingResponse, Failed(_)) => true,
+            (ProcessingResponse, Cancelled) => true,
 
             // From Updated
             (Updated, Delivering) => true,
             (Updated, InteractionPending) => true,
             (Updated, Completed) => true,
+            (Updated, Cancelled) => true,
 
             // From Failed - can retry
             (Failed(_), Validating) => true,
+            (Failed(_), Queued) => true,
             (Failed(_), PlatformRouting) => true,
             (Failed(_), Delivering) => true,
             (Failed(_), Cancelled) => true,
@@ -430,6 +490,8 @@ pub struct PlatformDeliveryState {
     pub last_attempt: Option<Instant>,
     pub delivery_latency: Option<Duration>,
     pub error_details: Option<PlatformError>,
+    /// Receipt of the delivery that showed the notification on this platform
+    pub receipt: Option<DeliveryReceipt>,
 }
 
 /// Platform delivery status
@@ -802,6 +864,33 @@ pub enum DeliveryAttemptResult {
     Failure(NotificationError),
 }
 
+/// Publishes every transition of one notification as it happens
+///
+/// Clones of a lifecycle share the feed, so a snapshot can still be watched.
+#[derive(Debug, Clone)]
+pub struct TransitionFeed {
+    sender: tokio::sync::broadcast::Sender<StateTransition>,
+}
+
+impl Default for TransitionFeed {
+    fn default() -> Self {
+        let (sender, _) = tokio::sync::broadcast::channel(TRANSITION_FEED_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl TransitionFeed {
+    /// Receive the transitions made from now on
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<StateTransition> {
+        self.sender.subscribe()
+    }
+
+    fn publish(&self, transition: StateTransition) {
+        // Nobody watching is fine
+        let _ = self.sender.send(transition);
+    }
+}
+
 /// State transition tracking for debugging and analytics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -9,12 +9,16 @@ use time_wrapper::DefaultableInstant;
 use uuid::Uuid;
 
 pub mod analytics;
+pub mod ansi;
+pub mod build_output;
 pub mod content;
 pub mod lifecycle;
 pub mod platform;
+pub mod rich_text;
 pub mod serde_time;
 pub mod time_wrapper;
 pub mod tracing;
+pub mod truncation;
 
 pub use analytics::{
     AnalyticsError, AnalyticsSummary, BusinessImpact, BusinessMetrics, ContentEffectiveness,
@@ -31,7 +35,7 @@ pub use content::{
     InputId, InputValidation, InteractionSet, LocalizationData, MediaAttachment,
     NotificationAction, NotificationContent, NotificationInput, NotificationInteraction,
     QuickReply, RichText, SelectionOption, SystemSound, ValidationState, VideoData, VideoFormat,
-    VideoSource,
+    VideoSource, DEDUP_KEY, REPLACES_KEY, decode_image_data_url,
 };
 // Re-export url::Url for convenience so consumers don't need to add url crate directly
 pub use url::Url;
@@ -43,17 +47,25 @@ pub use lifecycle::{
     PlatformDeliveryStatus, PlatformError, RetryPolicy, StateTransition, TransitionReason,
 };
 pub use platform::{
-    ActionChange, ActionFallback, AuthorizationManager, AuthorizationState, CompatibilityLevel,
-    DegradationStrategy, DeliveryOptions, FeatureDegradation, FeatureMatrix, GlobalPreferences,
-    MarkupFallback, MediaChange, MediaFallback, NativeHandleMetadata, NotificationRequest,
-    NotificationUpdate, PermissionLevel, Platform, PlatformBackend, PlatformCapabilities,
-    PlatformConfig, PlatformIntegration, PlatformManager, PlatformPreferences,
-    PlatformUserSettings, RateLimit,
+    ActionChange, ActionFallback, AppIdentity, AuthorizationManager, AuthorizationState,
+    CompatibilityLevel, DegradationStrategy, DeliveryOptions, FeatureDegradation, FeatureMatrix,
+    GlobalPreferences, MarkupFallback, MediaChange, MediaFallback, NativeHandleMetadata,
+    NotificationRequest, NotificationUpdate, PermissionLevel, Platform, PlatformBackend,
+    PlatformCapabilities, PlatformConfig, PlatformIntegration, PlatformInteraction,
+    PlatformInteractionKind, PlatformManager, PlatformPreferences, PlatformUserSettings, RateLimit,
+    CUSTOM_SOUND_OPTION,
 };
+pub use ansi::{parse_ansi, strip_ansi};
+pub use build_output::{
+    BuildDiagnostic, BuildOutputSummarizer, BuildSummary, BuildTool, DiagnosticSeverity,
+    SourceLocation, TestFailure,
+};
+pub use rich_text::{PangoSubset, RichBlock, RichDocument, RichInline};
 pub use tracing::{
     CorrelationData, PerformanceMarker, PerformanceMarkerType, SamplingConfig, TraceBreadcrumb,
     TraceContext, TracingContext,
 };
+pub use truncation::{TRUNCATED_BODY_KEY, TRUNCATED_TITLE_KEY, TruncationPolicy};
 
 /// Unique notification identity with enterprise-grade tracing
 /// Incorporates Slack's distributed tracing patterns and Discord's entity architecture
@@ -71,8 +83,10 @@ pub struct NotificationIdentity {
     pub created_at: DefaultableInstant,
     /// OpenTelemetry trace span for observability
     pub trace_span: Option<TraceSpan>,
-    /// Application context that created this notification
-    pub creator_context: CreatorContext,
+    /// Application context that created this notification; the app it is shown as when `None`
+    pub creator_context: Option<CreatorContext>,
+    /// Application the notification is shown as; the manager's identity when `None`
+    pub app: Option<platform::AppIdentity>,
 }
 
 impl Default for NotificationIdentity {
@@ -83,7 +97,8 @@ impl Default for NotificationIdentity {
             session_id: SessionId::generate(),
             created_at: DefaultableInstant::now(),
             trace_span: None,
-            creator_context: CreatorContext::default(),
+            creator_context: None,
+            app: None,
         }
     }
 }
@@ -96,7 +111,8 @@ impl NotificationIdentity {
             session_id,
             created_at: DefaultableInstant::now(),
             trace_span: None,
-            creator_context,
+            creator_context: Some(creator_context),
+            app: None,
         }
     }
 
@@ -105,6 +121,13 @@ impl NotificationIdentity {
         self
     }
 
+    /// Show the notification as coming from `app`, recording it as the creator
+    pub fn with_app(mut self, app: platform::AppIdentity) -> Self {
+        self.creator_context = Some(CreatorContext::for_app(&app));
+        self.app = Some(app);
+        self
+    }
+
     pub fn with_trace_span(mut self, trace_span: TraceSpan) -> Self {
         self.trace_span = Some(trace_span);
         self
@@ -208,6 +231,16 @@ impl CreatorContext {
         }
     }
 
+    /// Context naming the app's ID and version as the service
+    pub fn for_app(app: &platform::AppIdentity) -> Self {
+        Self {
+            service_name: app.app_id.clone(),
+            service_version: app.version.clone(),
+            feature_context: None,
+            user_context: None,
+        }
+    }
+
     pub fn with_feature(mut self, feature: impl Into<String>) -> Self {
         self.feature_context = Some(feature.into());
         self
@@ -276,6 +309,11 @@ impl TraceSpan {
     pub fn duration(&self) -> Duration {
         self.start_time.elapsed()
     }
+
+    /// W3C `traceparent` header value for this span (sampled)
+    pub fn traceparent(&self) -> String {
+        format!("00-{:032x}-{:016x}-01", self.trace_id.as_u128(), self.span_id.as_u64())
+    }
 }
 
 /// OpenTelemetry Span ID
@@ -455,27 +493,91 @@ pub struct CategoryOptions {
     pub hidden_preview_show_subtitle: bool,
 }
 
+/// What kind of failure an error is, whatever the platform called it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErrorKind {
+    /// The notification service isn't running or has no owner on the bus
+    ServiceUnavailable,
+    /// Connecting to the service failed or the connection broke off
+    Connection,
+    /// The service or a download didn't answer in time
+    Timeout,
+    /// The service is busy or out of resources
+    LimitsExceeded,
+    /// The user or the system denied access
+    PermissionDenied,
+    /// The request was malformed or rejected as invalid
+    InvalidRequest,
+    /// The platform doesn't support what was asked
+    Unsupported,
+    /// A notification, file or other resource doesn't exist
+    NotFound,
+    /// Not classified; treated as permanent
+    Other,
+}
+
+impl ErrorKind {
+    /// Whether trying again later may succeed
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorKind::ServiceUnavailable
+                | ErrorKind::Connection
+                | ErrorKind::Timeout
+                | ErrorKind::LimitsExceeded
+        )
+    }
+}
+
+/// Underlying error a [`NotificationError`] was made from, returned by `source()`
+#[derive(Clone)]
+pub struct ErrorSource(std::sync::Arc<dyn std::error::Error + Send + Sync>);
+
+impl<E: std::error::Error + Send + Sync + 'static> From<E> for ErrorSource {
+    fn from(error: E) -> Self {
+        Self(std::sync::Arc::new(error))
+    }
+}
+
+impl std::fmt::Debug for ErrorSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+
 /// Error types for comprehensive error handling
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
 pub enum NotificationError {
     /// Platform-specific delivery error
     PlatformError {
-        platform: String,
+        platform: Platform,
+        kind: ErrorKind,
+        /// Stable code of the platform error, e.g. from `linux_errors::DBUS_ERROR_CODES`
         error_code: Option<i32>,
         message: String,
+        #[serde(skip)]
+        source: Option<ErrorSource>,
     },
     /// Content validation error
     ValidationError { field: String, message: String },
     /// Permission/authorization error
     AuthorizationError {
-        platform: String,
+        platform: Platform,
         required_permission: String,
     },
     /// Resource error (file not found, network error, etc.)
     ResourceError {
         resource_type: String,
         resource_id: String,
+        kind: ErrorKind,
+        message: String,
+        #[serde(skip)]
+        source: Option<ErrorSource>,
+    },
+    /// Delivery failed on the listed platforms and the retry engine gave up
+    DeliveryFailed {
+        platform_errors: HashMap<Platform, String>,
         message: String,
     },
     /// Timeout error for delivery or interaction
@@ -503,11 +605,12 @@ impl std::fmt::Display for NotificationError {
                 platform,
                 error_code,
                 message,
+                ..
             } => {
                 write!(
                     f,
                     "Platform error on {}: {} (code: {:?})",
-                    platform, message, error_code
+                    platform.name(), message, error_code
                 )
             },
             NotificationError::ValidationError { field, message } => {
@@ -520,13 +623,14 @@ impl std::fmt::Display for NotificationError {
                 write!(
                     f,
                     "Authorization error on {}: missing permission {}",
-                    platform, required_permission
+                    platform.name(), required_permission
                 )
             },
             NotificationError::ResourceError {
                 resource_type,
                 resource_id,
                 message,
+                ..
             } => {
                 write!(
                     f,
@@ -558,11 +662,55 @@ impl std::fmt::Display for NotificationError {
             NotificationError::SanitizationError { content_type, message } => {
                 write!(f, "Sanitization error for {}: {}", content_type, message)
             },
+            NotificationError::DeliveryFailed { platform_errors, message } => {
+                let mut platforms: Vec<&str> = platform_errors.keys().map(|p| p.name()).collect();
+                platforms.sort_unstable();
+                write!(f, "Delivery failed on {}: {}", platforms.join(", "), message)
+            },
         }
     }
 }
 
-impl std::error::Error for NotificationError {}
+impl std::error::Error for NotificationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NotificationError::PlatformError { source, .. }
+            | NotificationError::ResourceError { source, .. } => source
+                .as_ref()
+                .map(|source| source.0.as_ref() as &(dyn std::error::Error + 'static)),
+            _ => None,
+        }
+    }
+}
+
+impl NotificationError {
+    /// What kind of failure this is
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            NotificationError::PlatformError { kind, .. }
+            | NotificationError::ResourceError { kind, .. } => *kind,
+            NotificationError::ValidationError { .. }
+            | NotificationError::SanitizationError { .. } => ErrorKind::InvalidRequest,
+            NotificationError::AuthorizationError { .. } => ErrorKind::PermissionDenied,
+            NotificationError::TimeoutError { .. } => ErrorKind::Timeout,
+            NotificationError::ResourceExhausted { .. } => ErrorKind::LimitsExceeded,
+            NotificationError::DeliveryFailed { .. } => ErrorKind::Other,
+        }
+    }
+
+    /// Whether trying again later may succeed
+    pub fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+
+    /// Stable platform error code, where the platform reported one
+    pub fn error_code(&self) -> Option<i32> {
+        match self {
+            NotificationError::PlatformError { error_code, .. } => *error_code,
+            _ => None,
+        }
+    }
+}
 
 
 
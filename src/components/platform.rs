@@ -51,22 +51,46 @@ impl PlatformIntegration {
         }
     }
 
-    /// Negotiate capabilities with all target platforms
+    /// Negotiate capabilities with every target platform that has a backend
+    ///
+    /// Targets without a backend, or whose negotiation failed with nothing
+    /// cached, get no capabilities, so nothing is validated or degraded for them.
+    /// Fallbacks are derived again from the negotiated features; the caller's
+    /// critical features and substitutions are kept.
     pub async fn negotiate_capabilities(
         &mut self,
         platform_manager: &PlatformManager,
     ) -> NotificationResult<()> {
+        self.platform_capabilities.clear();
         for platform in &self.target_platforms.clone() {
-            let capabilities = platform_manager.get_capabilities(*platform).await?;
-            self.platform_capabilities.insert(*platform, capabilities);
+            if platform_manager.backend(*platform).is_none() {
+                continue;
+            }
+            match platform_manager.get_capabilities(*platform).await {
+                Ok(capabilities) => {
+                    self.platform_capabilities.insert(*platform, capabilities);
+                }
+                Err(e) => {
+                    ::tracing::warn!(
+                        "Skipping validation for {}, its capabilities are unknown: {}",
+                        platform.name(),
+                        e
+                    );
+                }
+            }
         }
 
         // Build feature matrix based on negotiated capabilities
         self.feature_matrix = FeatureMatrix::from_capabilities(&self.platform_capabilities);
 
         // Determine degradation strategy
-        self.degradation_strategy =
-            DegradationStrategy::calculate_optimal_strategy(&self.feature_matrix);
+        let optimal = DegradationStrategy::calculate_optimal_strategy(&self.feature_matrix);
+        self.degradation_strategy = DegradationStrategy {
+            action_fallback: optimal.action_fallback,
+            media_fallback: optimal.media_fallback,
+            markup_fallback: optimal.markup_fallback,
+            ..std::mem::take(&mut self.degradation_strategy)
+        };
 
         Ok(())
     }
@@ -99,6 +123,14 @@ impl PlatformIntegration {
             .is_some_and(|state| state.is_authorized())
     }
 
+    /// User-configured sound override for `platform`, if any
+    pub fn custom_sound(&self, platform: Platform) -> Option<&str> {
+        self.user_preferences
+            .platform_settings
+            .get(&platform)
+            .and_then(|settings| settings.custom_sound.as_deref())
+    }
+
     /// Refresh platform capabilities
     pub fn refresh_capabilities(&mut self) {
         // Update capability cache timestamps to trigger refresh
@@ -169,6 +201,23 @@ impl Platform {
         }
     }
 
+    /// Platform this build runs on; other Unix-likes count as Linux
+    pub fn current() -> Platform {
+        if cfg!(target_os = "macos") {
+            Platform::MacOS
+        } else if cfg!(target_os = "windows") {
+            Platform::Windows
+        } else if cfg!(target_os = "ios") {
+            Platform::IOs
+        } else if cfg!(target_os = "android") {
+            Platform::Android
+        } else if cfg!(target_family = "wasm") {
+            Platform::Web
+        } else {
+            Platform::Linux
+        }
+    }
+
     pub fn is_desktop(&self) -> bool {
         matches!(self, Platform::MacOS | Platform::Windows | Platform::Linux)
     }
@@ -863,11 +912,38 @@ impl PlatformManager {
         }
     }
 
+    /// Create a platform manager that owns the given backends
+    pub fn with_backends(backends: HashMap<Platform, Box<dyn PlatformBackend>>) -> Self {
+        Self {
+            backends,
+            ..Self::new()
+        }
+    }
+
     pub fn register_backend(&mut self, platform: Platform, backend: Box<dyn PlatformBackend>) {
         self.backends.insert(platform, backend);
     }
 
+    /// Platforms with a registered backend
+    pub fn platforms(&self) -> impl Iterator<Item = Platform> + '_ {
+        self.backends.keys().copied()
+    }
+
+    /// Get the registered backend for a platform, if any
+    pub fn backend(&self, platform: Platform) -> Option<&dyn PlatformBackend> {
+        self.backends.get(&platform).map(|backend| backend.as_ref())
+    }
+
+    /// Drop the cached capabilities for a platform so the next lookup renegotiates
+    pub async fn invalidate_capabilities(&self, platform: Platform) {
+        self.capability_cache.write().await.remove(&platform);
+    }
+
     /// Get platform capabilities with caching
+    ///
+    /// Fails for a platform without a backend. When negotiating fails, the
+    /// last negotiated capabilities are used, and the error returned if there
+    /// are none.
     pub async fn get_capabilities(
         &self,
         platform: Platform,
@@ -885,10 +961,35 @@ impl PlatformManager {
         }
 
         // Negotiate capabilities with backend
-        let capabilities = if let Some(backend) = self.backends.get(&platform) {
-            backend.negotiate_capabilities().await?
-        } else {
-            platform.default_capabilities()
+        let Some(backend) = self.backends.get(&platform) else {
+            return Err(super::NotificationError::PlatformError {
+                platform,
+                kind: super::ErrorKind::Unsupported,
+                error_code: None,
+                message: "No backend registered for this platform".to_string(),
+                source: None,
+            });
+        };
+        let capabilities = match backend.negotiate_capabilities().await {
+            Ok(capabilities) => capabilities,
+            Err(e) => {
+                let stale = self
+                    .capability_cache
+                    .read()
+                    .await
+                    .get(&platform)
+                    .map(|(capabilities, _)| capabilities.clone());
+                let Some(stale) = stale else {
+                    return Err(e);
+                };
+                ::tracing::warn!(
+                    "Negotiating capabilities with {} failed, using the last negotiated ones: {}",
+                    platform.name(),
+                    e
+                );
+                // Not refreshed, so the next lookup negotiates again
+                return Ok(stale);
+            }
         };
 
         // Update cache
@@ -933,19 +1034,62 @@ pub trait PlatformBackend: Send + Sync {
     ) -> std::pin::Pin<
         Box<dyn std::future::Future<Output = NotificationResult<DeliveryReceipt>> + Send + '_>,
     >;
+    /// Update a delivered notification shown as coming from `app`
     fn update_notification(
         &self,
         id: &str,
         update: &NotificationUpdate,
+        app: &AppIdentity,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = NotificationResult<()>> + Send + '_>>;
+    /// Withdraw a notification shown as coming from `app`
     fn cancel_notification(
         &self,
         id: &str,
+        app: &AppIdentity,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = NotificationResult<()>> + Send + '_>>;
 
     fn request_authorization(
         &self,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = NotificationResult<bool>> + Send + '_>>;
+
+    /// Subscribe to user interactions with delivered notifications
+    ///
+    /// Returns `None` when the backend cannot report interactions.
+    fn subscribe_interactions(&self) -> Option<tokio::sync::broadcast::Receiver<PlatformInteraction>> {
+        None
+    }
+}
+
+/// What the user did with a delivered notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlatformInteractionKind {
+    /// An action button (or the platform's default action) was invoked
+    Action(ActionId),
+    /// Inline text reply; `input_id` is `None` when the platform doesn't say which input
+    Reply {
+        input_id: Option<InputId>,
+        text: String,
+    },
+    /// Closed by the user without choosing an action
+    Dismissed,
+    /// Gone without the user's doing, e.g. its notification server restarted;
+    /// the native ID no longer refers to it
+    Withdrawn,
+    /// Closed by the backend once its time to live ran out, on servers that
+    /// ignore the timeout; the native ID no longer refers to it
+    Expired,
+    /// The platform's notification server changed, so negotiated capabilities are
+    /// stale; `native_id` is empty
+    ServerChanged,
+}
+
+/// User interaction reported by a platform backend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformInteraction {
+    pub platform: Platform,
+    /// Native ID from the delivery receipt
+    pub native_id: String,
+    pub kind: PlatformInteractionKind,
 }
 
 /// Authorization manager trait
@@ -1133,6 +1277,67 @@ pub struct NotificationRequest {
     pub content: NotificationContent,
     pub options: DeliveryOptions,
     pub correlation_id: String,
+    /// Application the notification is shown as coming from
+    pub app: AppIdentity,
+}
+
+/// Application notifications are shown as coming from
+///
+/// Set once per manager (see `NotificationManager::with_app_identity`) or per
+/// notification, so one process can send on behalf of several tools.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppIdentity {
+    /// Name shown by the notification server (Linux `app_name`)
+    pub display_name: String,
+    /// Bundle identifier (macOS), AppUserModelID (Windows) and default desktop entry ID
+    pub app_id: String,
+    /// Desktop entry ID for the Linux `desktop-entry` hint, when it differs from `app_id`
+    pub desktop_entry: Option<String>,
+    /// Icon name or path for notifications without an app icon of their own
+    pub icon: Option<String>,
+    pub version: String,
+}
+
+impl AppIdentity {
+    pub fn new(display_name: impl Into<String>, app_id: impl Into<String>) -> Self {
+        Self {
+            display_name: display_name.into(),
+            app_id: app_id.into(),
+            desktop_entry: None,
+            icon: None,
+            version: String::new(),
+        }
+    }
+
+    pub fn with_desktop_entry(mut self, desktop_entry: impl Into<String>) -> Self {
+        self.desktop_entry = Some(desktop_entry.into());
+        self
+    }
+
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Desktop entry ID named in the Linux `desktop-entry` hint
+    pub fn desktop_entry_id(&self) -> &str {
+        self.desktop_entry.as_deref().unwrap_or(&self.app_id)
+    }
+}
+
+impl Default for AppIdentity {
+    /// This crate's bundle, as installed by `DesktopEntry::from_bundle`
+    fn default() -> Self {
+        use crate::backends::linux_desktop::{BUNDLE_IDENTIFIER, BUNDLE_NAME};
+        Self::new(BUNDLE_NAME, BUNDLE_IDENTIFIER)
+            .with_icon(BUNDLE_IDENTIFIER)
+            .with_version(env!("CARGO_PKG_VERSION"))
+    }
 }
 
 /// Notification update for content changes
@@ -1150,8 +1355,88 @@ pub struct NotificationUpdate {
     pub options: Option<DeliveryOptions>,
 }
 
+impl NotificationUpdate {
+    /// Merge the update into previously delivered content
+    ///
+    /// A full `content` replacement applies first, then the field changes:
+    /// `title`, `subtitle` (empty clears it) and `body` (plain text) set those
+    /// fields, other keys go to `custom_data`. Media and action changes apply last.
+    pub fn apply_to(&self, content: &mut NotificationContent) {
+        if let Some(replacement) = &self.content {
+            *content = replacement.clone();
+        }
+
+        for (field, value) in &self.content_changes {
+            match field.as_str() {
+                "title" => content.title = value.clone(),
+                "subtitle" => {
+                    content.subtitle = Some(value.clone()).filter(|subtitle| !subtitle.is_empty());
+                }
+                "body" => content.body = super::RichText::plain(value.clone()),
+                _ => {
+                    content.custom_data.insert(field.clone(), value.clone());
+                }
+            }
+        }
+
+        for change in &self.media_changes {
+            match change {
+                MediaChange::Add(attachment) => content.media.push(attachment.clone()),
+                MediaChange::Remove(id) => content
+                    .media
+                    .retain(|attachment| media_id(attachment).as_deref() != Some(id)),
+                MediaChange::Update { id, new_data } => {
+                    for attachment in &mut content.media {
+                        if media_id(attachment).as_deref() == Some(id) {
+                            *attachment = new_data.clone();
+                        }
+                    }
+                }
+            }
+        }
+
+        let actions = &mut content.interactions.actions;
+        for change in &self.action_changes {
+            match change {
+                ActionChange::Add(action) => actions.push(action.clone()),
+                ActionChange::Remove(id) => actions.retain(|action| action.id.as_str() != id),
+                ActionChange::Update { id, new_action } => {
+                    for action in actions.iter_mut() {
+                        if action.id.as_str() == id {
+                            *action = new_action.clone();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Path, URL or icon name identifying an attachment in `MediaChange`s
+fn media_id(attachment: &MediaAttachment) -> Option<String> {
+    use super::content::{AudioSource, ImageData, VideoSource};
+    match attachment {
+        MediaAttachment::Image { data, .. } => match data {
+            ImageData::File(path) => Some(path.display().to_string()),
+            ImageData::Url(url) => Some(url.to_string()),
+            ImageData::SystemIcon(name) => Some(name.clone()),
+            ImageData::Embedded { .. } => None,
+        },
+        MediaAttachment::Audio { source, .. } => match source {
+            AudioSource::File(path) => Some(path.display().to_string()),
+            AudioSource::Url(url) => Some(url.to_string()),
+            _ => None,
+        },
+        MediaAttachment::Video { data, .. } => match &data.source {
+            VideoSource::File(path) => Some(path.display().to_string()),
+            VideoSource::Url(url) => Some(url.to_string()),
+            VideoSource::Embedded { .. } => None,
+        },
+        MediaAttachment::File { path, .. } => Some(path.display().to_string()),
+    }
+}
 
-/// Media change for updates
+/// Media change for updates, naming attachments by path, URL or icon name
 #[derive(Debug, Clone)]
 pub enum MediaChange {
     Add(MediaAttachment),
@@ -1162,7 +1447,7 @@ pub enum MediaChange {
     },
 }
 
-/// Action change for updates
+/// Action change for updates, naming actions by ID
 #[derive(Debug, Clone)]
 pub enum ActionChange {
     Add(NotificationAction),
@@ -1173,6 +1458,9 @@ pub enum ActionChange {
     },
 }
 
+/// `DeliveryOptions::platform_specific` key carrying `PlatformUserSettings::custom_sound`
+pub const CUSTOM_SOUND_OPTION: &str = "custom_sound";
+
 /// Delivery options for platform-specific configuration
 #[derive(Debug, Clone)]
 pub struct DeliveryOptions {
@@ -1214,4 +1502,6 @@ impl Default for DeliveryOptions {
 }
 
 // Re-export commonly used types from content module for convenience
-pub use super::content::{MediaAttachment, NotificationAction, NotificationContent};
+pub use super::content::{
+    ActionId, InputId, MediaAttachment, NotificationAction, NotificationContent,
+};
@@ -0,0 +1,954 @@
+// Shared document model for rich notification text
+// Markdown, sanitized HTML and ANSI terminal output are parsed once into a small block/inline tree and
+// every output format (plain, structured plain, Pango, HTML, toast text) is a
+// renderer over that tree, so all targets agree on lists, entities and nesting
+
+use super::content::{decode_html_entities, sanitize_html};
+
+/// A parsed rich text document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RichDocument {
+    pub blocks: Vec<RichBlock>,
+}
+
+/// Block-level document node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RichBlock {
+    Paragraph(Vec<RichInline>),
+    Heading { level: u8, content: Vec<RichInline> },
+    CodeBlock(String),
+    /// `start` is `Some` for ordered lists
+    List { start: Option<u64>, items: Vec<Vec<RichBlock>> },
+    Quote(Vec<RichBlock>),
+}
+
+/// Inline document node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RichInline {
+    Text(String),
+    Strong(Vec<RichInline>),
+    Emphasis(Vec<RichInline>),
+    Underline(Vec<RichInline>),
+    Strikethrough(Vec<RichInline>),
+    Code(String),
+    /// `href` is `None` when the destination was not a safe http(s) or fragment link
+    Link { href: Option<String>, content: Vec<RichInline> },
+    /// Foreground color as `#rrggbb`
+    Colored { foreground: String, content: Vec<RichInline> },
+    LineBreak,
+}
+
+impl RichDocument {
+    /// Plain text with blank lines separating paragraphs and newlines as line breaks
+    pub fn from_plain(text: &str) -> Self {
+        let blocks = text
+            .split("\n\n")
+            .filter(|paragraph| !paragraph.trim().is_empty())
+            .map(|paragraph| {
+                let mut content = Vec::new();
+                for (index, line) in paragraph.split('\n').enumerate() {
+                    if index > 0 {
+                        content.push(RichInline::LineBreak);
+                    }
+                    if !line.is_empty() {
+                        content.push(RichInline::Text(line.to_string()));
+                    }
+                }
+                RichBlock::Paragraph(content)
+            })
+            .collect();
+        Self { blocks }
+    }
+
+    /// Parse CommonMark (with strikethrough, tables and task lists)
+    ///
+    /// Raw HTML is dropped and links keep only http(s) or fragment destinations.
+    pub fn parse_markdown(markdown: &str) -> Self {
+        use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+        let mut options = Options::empty();
+        options.insert(Options::ENABLE_STRIKETHROUGH);
+        options.insert(Options::ENABLE_TABLES);
+        options.insert(Options::ENABLE_TASKLISTS);
+
+        let mut builder = DocumentBuilder::new();
+        let mut code_block: Option<String> = None;
+        let mut row_cells = 0;
+
+        for event in Parser::new_ext(markdown, options) {
+            match event {
+                Event::Start(tag) => match tag {
+                    Tag::Paragraph => builder.start_inline(InlineKind::Paragraph),
+                    Tag::Heading { level, .. } => {
+                        builder.start_inline(InlineKind::Heading(level as u8))
+                    }
+                    Tag::BlockQuote(_) => builder.open_container(ContainerKind::Quote),
+                    Tag::CodeBlock(_) => {
+                        builder.flush_inline();
+                        code_block = Some(String::new());
+                    }
+                    Tag::List(start) => builder.open_container(ContainerKind::List { start }),
+                    Tag::Item => builder.open_container(ContainerKind::Item),
+                    Tag::TableHead | Tag::TableRow => {
+                        builder.start_inline(InlineKind::Paragraph);
+                        row_cells = 0;
+                    }
+                    Tag::TableCell => {
+                        if row_cells > 0 {
+                            builder.push_text(" | ");
+                        }
+                        row_cells += 1;
+                    }
+                    Tag::Emphasis => builder.open_span(Span::Emphasis),
+                    Tag::Strong => builder.open_span(Span::Strong),
+                    Tag::Strikethrough => builder.open_span(Span::Strikethrough),
+                    Tag::Link { dest_url, .. } => builder.open_span(Span::Link(safe_href(&dest_url))),
+                    _ => {}
+                },
+                Event::End(tag_end) => match tag_end {
+                    TagEnd::Paragraph
+                    | TagEnd::Heading(_)
+                    | TagEnd::TableHead
+                    | TagEnd::TableRow => builder.flush_inline(),
+                    TagEnd::BlockQuote(_) => builder.close_container(ContainerKind::Quote),
+                    TagEnd::CodeBlock => {
+                        if let Some(code) = code_block.take() {
+                            builder.push_block(RichBlock::CodeBlock(
+                                code.trim_end_matches('\n').to_string(),
+                            ));
+                        }
+                    }
+                    TagEnd::List(_) => builder.close_container(ContainerKind::List { start: None }),
+                    TagEnd::Item => builder.close_container(ContainerKind::Item),
+                    TagEnd::Emphasis => builder.close_span(Span::Emphasis),
+                    TagEnd::Strong => builder.close_span(Span::Strong),
+                    TagEnd::Strikethrough => builder.close_span(Span::Strikethrough),
+                    TagEnd::Link => builder.close_span(Span::Link(None)),
+                    _ => {}
+                },
+                Event::Text(text) => match &mut code_block {
+                    Some(code) => code.push_str(&text),
+                    None => builder.push_text(&text),
+                },
+                Event::Code(code) => builder.push_code(&code),
+                Event::SoftBreak => builder.push_text(" "),
+                Event::HardBreak => builder.line_break(),
+                Event::TaskListMarker(checked) => {
+                    builder.push_text(if checked { "[x] " } else { "[ ] " })
+                }
+                // Raw HTML, rules, footnotes and math are not rendered
+                _ => {}
+            }
+        }
+
+        builder.finish()
+    }
+
+    /// Sanitize HTML and parse the result
+    ///
+    /// Unknown elements are transparent: their text is kept, the tag is not.
+    pub fn parse_html(html: &str) -> Self {
+        // The sanitizer's html5ever serializer yields well-formed, lowercase
+        // markup with quoted attributes, which keeps the tokenizer below simple
+        let clean = sanitize_html(html).unwrap_or_default();
+
+        let mut builder = DocumentBuilder::new();
+        let mut pre: Option<String> = None;
+        let mut pre_depth = 0;
+        let mut code: Option<String> = None;
+        let mut row_cells = 0;
+
+        let mut rest = clean.as_str();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('<') {
+                let end = after.find('>').unwrap_or(after.len());
+                let tag = &after[..end];
+                rest = after.get(end + 1..).unwrap_or_default();
+
+                let closing = tag.starts_with('/');
+                let body = tag.trim_start_matches('/').trim_end_matches('/');
+                let name = body
+                    .split(|c: char| c.is_whitespace())
+                    .next()
+                    .unwrap_or_default()
+                    .to_ascii_lowercase();
+
+                if pre.is_some() && name != "pre" {
+                    continue;
+                }
+
+                match (name.as_str(), closing) {
+                    ("p" | "tr", false) => {
+                        builder.start_inline(InlineKind::Paragraph);
+                        row_cells = 0;
+                    }
+                    ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                        let level = name[1..].parse().unwrap_or(1);
+                        builder.start_inline(InlineKind::Heading(level));
+                    }
+                    ("p" | "tr" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6", _)
+                    | ("table", false) => builder.flush_inline(),
+                    ("td" | "th", false) => {
+                        if row_cells > 0 {
+                            builder.push_text(" | ");
+                        }
+                        row_cells += 1;
+                    }
+                    ("br", _) => builder.line_break(),
+                    ("ul", false) => builder.open_container(ContainerKind::List { start: None }),
+                    ("ol", false) => {
+                        let start = html_attribute(body, "start")
+                            .and_then(|value| value.parse().ok())
+                            .unwrap_or(1);
+                        builder.open_container(ContainerKind::List { start: Some(start) });
+                    }
+                    ("ul" | "ol", true) => builder.close_container(ContainerKind::List { start: None }),
+                    ("li", false) => builder.open_container(ContainerKind::Item),
+                    ("li", true) => builder.close_container(ContainerKind::Item),
+                    ("blockquote", false) => builder.open_container(ContainerKind::Quote),
+                    ("blockquote", true) => builder.close_container(ContainerKind::Quote),
+                    ("pre", false) => {
+                        builder.flush_inline();
+                        pre_depth += 1;
+                        pre.get_or_insert_with(String::new);
+                    }
+                    ("pre", true) => {
+                        pre_depth -= 1;
+                        if pre_depth == 0
+                            && let Some(text) = pre.take()
+                        {
+                            let text = text.strip_prefix('\n').unwrap_or(&text);
+                            builder.push_block(RichBlock::CodeBlock(
+                                text.trim_end_matches('\n').to_string(),
+                            ));
+                        }
+                    }
+                    ("code" | "tt" | "kbd" | "samp", false) => {
+                        code.get_or_insert_with(String::new);
+                    }
+                    ("code" | "tt" | "kbd" | "samp", true) => {
+                        if let Some(text) = code.take() {
+                            builder.push_code(&text);
+                        }
+                    }
+                    ("strong" | "b", false) => builder.open_span(Span::Strong),
+                    ("strong" | "b", true) => builder.close_span(Span::Strong),
+                    ("em" | "i", false) => builder.open_span(Span::Emphasis),
+                    ("em" | "i", true) => builder.close_span(Span::Emphasis),
+                    ("u" | "ins", false) => builder.open_span(Span::Underline),
+                    ("u" | "ins", true) => builder.close_span(Span::Underline),
+                    ("s" | "del" | "strike", false) => builder.open_span(Span::Strikethrough),
+                    ("s" | "del" | "strike", true) => builder.close_span(Span::Strikethrough),
+                    ("a", false) => {
+                        let href = html_attribute(body, "href").and_then(|href| safe_href(&href));
+                        builder.open_span(Span::Link(href));
+                    }
+                    ("a", true) => builder.close_span(Span::Link(None)),
+                    _ => {}
+                }
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                let text = decode_html_entities(&rest[..end]);
+                rest = &rest[end..];
+
+                if let Some(pre_text) = &mut pre {
+                    pre_text.push_str(&text);
+                } else if let Some(code_text) = &mut code {
+                    code_text.push_str(&collapse_line_whitespace(&text));
+                } else {
+                    builder.push_html_text(&collapse_line_whitespace(&text));
+                }
+            }
+        }
+
+        builder.finish()
+    }
+
+    /// Text only, one line per paragraph, heading, list item or code line
+    pub fn to_plain_text(&self) -> String {
+        layout_blocks(&self.blocks, &PlainStyle)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Plain text that keeps structure: bullets, indented code and link URLs
+    pub fn to_structured_plain_text(&self) -> String {
+        finish_layout(&layout_blocks(&self.blocks, &StructuredStyle))
+    }
+
+    /// Pango markup limited to the tags notification servers accept
+    /// (`b`, `i`, `u`, `s`, `tt`, `a`)
+    pub fn to_pango_markup(&self) -> String {
+        self.to_pango_markup_subset(PangoSubset::FULL)
+    }
+
+    /// Pango markup using only the tags in `subset`; links the server can't
+    /// render keep their URL in the text
+    pub fn to_pango_markup_subset(&self, subset: PangoSubset) -> String {
+        finish_layout(&layout_blocks(&self.blocks, &PangoStyle(subset)))
+    }
+
+    /// Escaped text for a toast `<text>` element, which has no inline markup
+    pub fn to_toast_text(&self) -> String {
+        xml_escape(&self.to_structured_plain_text())
+    }
+
+    /// Minimal, safe HTML
+    pub fn to_html(&self) -> String {
+        html_blocks(&self.blocks)
+    }
+}
+
+/// Keep only destinations that are safe to open from a notification
+fn safe_href(href: &str) -> Option<String> {
+    let lower = href.trim().to_ascii_lowercase();
+    (lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with('#'))
+        .then(|| href.trim().to_string())
+}
+
+/// Value of a double-quoted attribute in a serialized start tag
+fn html_attribute(tag: &str, name: &str) -> Option<String> {
+    let needle = format!(" {}=\"", name);
+    let start = tag.find(&needle)? + needle.len();
+    let end = tag[start..].find('"')?;
+    Some(decode_html_entities(&tag[start..start + end]))
+}
+
+/// Source line breaks in HTML text are plain whitespace
+fn collapse_line_whitespace(text: &str) -> String {
+    if !text.contains(['\n', '\r']) {
+        return text.to_string();
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut run = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() {
+            run.push(c);
+            continue;
+        }
+        flush_whitespace_run(&mut result, &mut run);
+        result.push(c);
+    }
+    flush_whitespace_run(&mut result, &mut run);
+    result
+}
+
+fn flush_whitespace_run(result: &mut String, run: &mut String) {
+    if run.contains(['\n', '\r']) {
+        result.push(' ');
+    } else {
+        result.push_str(run);
+    }
+    run.clear();
+}
+
+// Tree construction shared by both parsers
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InlineKind {
+    Paragraph,
+    Heading(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Span {
+    Root,
+    Strong,
+    Emphasis,
+    Underline,
+    Strikethrough,
+    Link(Option<String>),
+}
+
+impl Span {
+    fn same_kind(&self, other: &Span) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    fn wrap(self, content: Vec<RichInline>) -> RichInline {
+        match self {
+            Span::Root => unreachable!("the root span is never wrapped"),
+            Span::Strong => RichInline::Strong(content),
+            Span::Emphasis => RichInline::Emphasis(content),
+            Span::Underline => RichInline::Underline(content),
+            Span::Strikethrough => RichInline::Strikethrough(content),
+            Span::Link(href) => RichInline::Link { href, content },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContainerKind {
+    Root,
+    Quote,
+    List { start: Option<u64> },
+    Item,
+}
+
+impl ContainerKind {
+    fn same_kind(&self, other: &ContainerKind) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+struct Container {
+    kind: ContainerKind,
+    blocks: Vec<RichBlock>,
+    items: Vec<Vec<RichBlock>>,
+}
+
+impl Container {
+    fn new(kind: ContainerKind) -> Self {
+        Self {
+            kind,
+            blocks: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+}
+
+struct InlineBlock {
+    kind: InlineKind,
+    spans: Vec<(Span, Vec<RichInline>)>,
+}
+
+struct DocumentBuilder {
+    containers: Vec<Container>,
+    inline: Option<InlineBlock>,
+}
+
+impl DocumentBuilder {
+    fn new() -> Self {
+        Self {
+            containers: vec![Container::new(ContainerKind::Root)],
+            inline: None,
+        }
+    }
+
+    fn inline_content(&mut self) -> &mut Vec<RichInline> {
+        let inline = self.inline.get_or_insert_with(|| InlineBlock {
+            kind: InlineKind::Paragraph,
+            spans: vec![(Span::Root, Vec::new())],
+        });
+        // The root span is never popped
+        &mut inline.spans.last_mut().expect("root span").1
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let content = self.inline_content();
+        if let Some(RichInline::Text(last)) = content.last_mut() {
+            last.push_str(text);
+        } else {
+            content.push(RichInline::Text(text.to_string()));
+        }
+    }
+
+    /// Whitespace between HTML blocks does not start a paragraph
+    fn push_html_text(&mut self, text: &str) {
+        if self.inline.is_none() && text.trim().is_empty() {
+            return;
+        }
+        self.push_text(text);
+    }
+
+    fn push_code(&mut self, code: &str) {
+        self.inline_content().push(RichInline::Code(code.to_string()));
+    }
+
+    fn line_break(&mut self) {
+        self.inline_content().push(RichInline::LineBreak);
+    }
+
+    fn open_span(&mut self, span: Span) {
+        self.inline_content();
+        if let Some(inline) = &mut self.inline {
+            inline.spans.push((span, Vec::new()));
+        }
+    }
+
+    /// Close the innermost open span of `span`'s kind, and any opened inside it
+    fn close_span(&mut self, span: Span) {
+        let Some(inline) = &mut self.inline else {
+            return;
+        };
+        if !inline.spans.iter().skip(1).any(|(open, _)| open.same_kind(&span)) {
+            return;
+        }
+        while let Some((open, content)) = inline.spans.pop() {
+            let done = open.same_kind(&span);
+            let wrapped = open.wrap(content);
+            if let Some((_, parent)) = inline.spans.last_mut() {
+                parent.push(wrapped);
+            }
+            if done {
+                break;
+            }
+        }
+    }
+
+    fn start_inline(&mut self, kind: InlineKind) {
+        self.flush_inline();
+        self.inline = Some(InlineBlock {
+            kind,
+            spans: vec![(Span::Root, Vec::new())],
+        });
+    }
+
+    /// Close the open paragraph or heading and add it unless it is blank
+    fn flush_inline(&mut self) {
+        let Some(mut inline) = self.inline.take() else {
+            return;
+        };
+        while inline.spans.len() > 1 {
+            let (open, content) = inline.spans.pop().expect("span");
+            let wrapped = open.wrap(content);
+            inline.spans.last_mut().expect("root span").1.push(wrapped);
+        }
+        let content = inline.spans.pop().map(|(_, content)| content).unwrap_or_default();
+        if is_blank(&content) {
+            return;
+        }
+
+        self.push_block(match inline.kind {
+            InlineKind::Paragraph => RichBlock::Paragraph(content),
+            InlineKind::Heading(level) => RichBlock::Heading { level, content },
+        });
+    }
+
+    fn push_block(&mut self, block: RichBlock) {
+        self.flush_inline();
+        if matches!(self.top().kind, ContainerKind::List { .. }) {
+            self.containers.push(Container::new(ContainerKind::Item));
+        }
+        self.top().blocks.push(block);
+    }
+
+    fn open_container(&mut self, kind: ContainerKind) {
+        self.flush_inline();
+        if kind == ContainerKind::Item
+            || !matches!(self.top().kind, ContainerKind::List { .. })
+        {
+            self.containers.push(Container::new(kind));
+        } else {
+            // A block opening directly inside a list belongs to an implicit item
+            self.containers.push(Container::new(ContainerKind::Item));
+            self.containers.push(Container::new(kind));
+        }
+    }
+
+    /// Close the innermost open container of `kind`'s kind, and any opened inside it
+    fn close_container(&mut self, kind: ContainerKind) {
+        self.flush_inline();
+        if !self.containers.iter().skip(1).any(|open| open.kind.same_kind(&kind)) {
+            return;
+        }
+        while self.containers.len() > 1 {
+            let done = self.top().kind.same_kind(&kind);
+            self.pop_container();
+            if done {
+                break;
+            }
+        }
+    }
+
+    fn pop_container(&mut self) {
+        let Some(container) = self.containers.pop() else {
+            return;
+        };
+        match container.kind {
+            ContainerKind::Root => self.containers.push(container),
+            ContainerKind::Item => {
+                if let ContainerKind::List { .. } = self.top().kind {
+                    self.top().items.push(container.blocks);
+                } else if !container.blocks.is_empty() {
+                    // Stray list item outside any list
+                    self.push_block(RichBlock::List {
+                        start: None,
+                        items: vec![container.blocks],
+                    });
+                }
+            }
+            ContainerKind::List { start } => {
+                if !container.items.is_empty() {
+                    self.push_block(RichBlock::List {
+                        start,
+                        items: container.items,
+                    });
+                }
+            }
+            ContainerKind::Quote => {
+                if !container.blocks.is_empty() {
+                    self.push_block(RichBlock::Quote(container.blocks));
+                }
+            }
+        }
+    }
+
+    fn top(&mut self) -> &mut Container {
+        self.containers.last_mut().expect("root container")
+    }
+
+    fn finish(mut self) -> RichDocument {
+        self.flush_inline();
+        while self.containers.len() > 1 {
+            self.pop_container();
+        }
+        RichDocument {
+            blocks: self.containers.pop().map(|root| root.blocks).unwrap_or_default(),
+        }
+    }
+}
+
+fn is_blank(content: &[RichInline]) -> bool {
+    content.iter().all(|inline| match inline {
+        RichInline::Text(text) => text.trim().is_empty(),
+        RichInline::LineBreak => true,
+        RichInline::Code(code) => code.is_empty(),
+        RichInline::Strong(inner)
+        | RichInline::Emphasis(inner)
+        | RichInline::Underline(inner)
+        | RichInline::Strikethrough(inner)
+        | RichInline::Link { content: inner, .. }
+        | RichInline::Colored { content: inner, .. } => is_blank(inner),
+    })
+}
+
+// Text renderers share one layout and differ in how nodes are styled
+
+trait TextStyle {
+    fn text(&self, text: &str) -> String;
+    fn code(&self, code: &str) -> String;
+    fn code_block(&self, code: &str) -> String;
+    fn list_marker(&self, start: Option<u64>, index: usize) -> String;
+    fn quote_prefix(&self) -> &'static str;
+
+    fn strong(&self, inner: String) -> String {
+        inner
+    }
+    fn emphasis(&self, inner: String) -> String {
+        inner
+    }
+    fn underline(&self, inner: String) -> String {
+        inner
+    }
+    fn strikethrough(&self, inner: String) -> String {
+        inner
+    }
+    fn heading(&self, inner: String) -> String {
+        inner
+    }
+    fn link(&self, _href: Option<&str>, inner: String) -> String {
+        inner
+    }
+    fn colored(&self, _foreground: &str, inner: String) -> String {
+        inner
+    }
+}
+
+struct PlainStyle;
+
+impl TextStyle for PlainStyle {
+    fn text(&self, text: &str) -> String {
+        text.to_string()
+    }
+    fn code(&self, code: &str) -> String {
+        code.to_string()
+    }
+    fn code_block(&self, code: &str) -> String {
+        code.to_string()
+    }
+    fn list_marker(&self, _start: Option<u64>, _index: usize) -> String {
+        String::new()
+    }
+    fn quote_prefix(&self) -> &'static str {
+        ""
+    }
+}
+
+struct StructuredStyle;
+
+impl TextStyle for StructuredStyle {
+    fn text(&self, text: &str) -> String {
+        text.to_string()
+    }
+    fn code(&self, code: &str) -> String {
+        format!("`{}`", code)
+    }
+    fn code_block(&self, code: &str) -> String {
+        code.lines()
+            .map(|line| format!("  {}", line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    fn list_marker(&self, start: Option<u64>, index: usize) -> String {
+        match start {
+            Some(start) => format!("{}. ", start + index as u64),
+            None => "• ".to_string(),
+        }
+    }
+    fn quote_prefix(&self) -> &'static str {
+        "> "
+    }
+    fn link(&self, href: Option<&str>, inner: String) -> String {
+        match href {
+            Some(href) if !href.starts_with('#') && href != inner => {
+                format!("{} ({})", inner, href)
+            }
+            _ => inner,
+        }
+    }
+}
+
+/// Pango tags a notification server renders beyond the spec's `b`, `i` and `u`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PangoSubset {
+    /// `<a href>` links, the `body-hyperlinks` capability
+    pub hyperlinks: bool,
+    /// `<tt>`, `<s>` and `<span foreground>`, which only full Pango renderers accept
+    pub extended: bool,
+}
+
+impl PangoSubset {
+    /// Every tag `to_pango_markup` emits
+    pub const FULL: Self = Self {
+        hyperlinks: true,
+        extended: true,
+    };
+    /// Only the tags the notification spec defines
+    pub const SPEC: Self = Self {
+        hyperlinks: true,
+        extended: false,
+    };
+}
+
+struct PangoStyle(PangoSubset);
+
+impl TextStyle for PangoStyle {
+    fn text(&self, text: &str) -> String {
+        pango_escape(text)
+    }
+    fn code(&self, code: &str) -> String {
+        if self.0.extended {
+            format!("<tt>{}</tt>", pango_escape(code))
+        } else {
+            pango_escape(&StructuredStyle.code(code))
+        }
+    }
+    fn code_block(&self, code: &str) -> String {
+        if self.0.extended {
+            format!("<tt>{}</tt>", pango_escape(code))
+        } else {
+            pango_escape(&StructuredStyle.code_block(code))
+        }
+    }
+    fn list_marker(&self, start: Option<u64>, index: usize) -> String {
+        StructuredStyle.list_marker(start, index)
+    }
+    fn quote_prefix(&self) -> &'static str {
+        "&gt; "
+    }
+    fn strong(&self, inner: String) -> String {
+        format!("<b>{}</b>", inner)
+    }
+    fn emphasis(&self, inner: String) -> String {
+        format!("<i>{}</i>", inner)
+    }
+    fn underline(&self, inner: String) -> String {
+        format!("<u>{}</u>", inner)
+    }
+    fn strikethrough(&self, inner: String) -> String {
+        if self.0.extended {
+            format!("<s>{}</s>", inner)
+        } else {
+            inner
+        }
+    }
+    fn heading(&self, inner: String) -> String {
+        format!("<b>{}</b>", inner)
+    }
+    fn link(&self, href: Option<&str>, inner: String) -> String {
+        match href {
+            Some(href) if !href.starts_with('#') && self.0.hyperlinks => {
+                format!("<a href=\"{}\">{}</a>", attribute_escape(href), inner)
+            }
+            Some(href) if !href.starts_with('#') && href != inner => {
+                format!("{} ({})", inner, pango_escape(href))
+            }
+            _ => inner,
+        }
+    }
+    fn colored(&self, foreground: &str, inner: String) -> String {
+        if self.0.extended {
+            format!("<span foreground=\"{}\">{}</span>", attribute_escape(foreground), inner)
+        } else {
+            inner
+        }
+    }
+}
+
+/// Blocks separated by a blank line
+fn layout_blocks(blocks: &[RichBlock], style: &impl TextStyle) -> String {
+    blocks
+        .iter()
+        .map(|block| layout_block(block, style))
+        .filter(|text| !text.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn layout_block(block: &RichBlock, style: &impl TextStyle) -> String {
+    match block {
+        RichBlock::Paragraph(content) => layout_inlines(content, style),
+        RichBlock::Heading { content, .. } => style.heading(layout_inlines(content, style)),
+        RichBlock::CodeBlock(code) => style.code_block(code),
+        RichBlock::List { start, items } => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let marker = style.list_marker(*start, index);
+                let indent = " ".repeat(marker.chars().count());
+                let body = item
+                    .iter()
+                    .map(|block| layout_block(block, style))
+                    .filter(|text| !text.trim().is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                body.lines()
+                    .enumerate()
+                    .map(|(line_index, line)| {
+                        let prefix = if line_index == 0 { &marker } else { &indent };
+                        format!("{}{}", prefix, line)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        RichBlock::Quote(blocks) => layout_blocks(blocks, style)
+            .lines()
+            .map(|line| format!("{}{}", style.quote_prefix(), line))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+fn layout_inlines(content: &[RichInline], style: &impl TextStyle) -> String {
+    content
+        .iter()
+        .map(|inline| match inline {
+            RichInline::Text(text) => style.text(text),
+            RichInline::Code(code) => style.code(code),
+            RichInline::LineBreak => "\n".to_string(),
+            RichInline::Strong(inner) => style.strong(layout_inlines(inner, style)),
+            RichInline::Emphasis(inner) => style.emphasis(layout_inlines(inner, style)),
+            RichInline::Underline(inner) => style.underline(layout_inlines(inner, style)),
+            RichInline::Strikethrough(inner) => style.strikethrough(layout_inlines(inner, style)),
+            RichInline::Link { href, content } => {
+                style.link(href.as_deref(), layout_inlines(content, style))
+            }
+            RichInline::Colored { foreground, content } => {
+                style.colored(foreground, layout_inlines(content, style))
+            }
+        })
+        .collect()
+}
+
+/// Trim trailing spaces per line and surrounding blank lines
+fn finish_layout(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+fn html_blocks(blocks: &[RichBlock]) -> String {
+    blocks.iter().map(html_block).collect::<Vec<_>>().join("\n")
+}
+
+fn html_block(block: &RichBlock) -> String {
+    match block {
+        RichBlock::Paragraph(content) => format!("<p>{}</p>", html_inlines(content)),
+        RichBlock::Heading { level, content } => {
+            format!("<h{level}>{}</h{level}>", html_inlines(content))
+        }
+        RichBlock::CodeBlock(code) => format!("<pre><code>{}</code></pre>", html_escape(code)),
+        RichBlock::List { start, items } => {
+            let items: String = items
+                .iter()
+                .map(|item| match item.as_slice() {
+                    // Tight items render without a wrapping paragraph
+                    [RichBlock::Paragraph(content)] => format!("<li>{}</li>", html_inlines(content)),
+                    blocks => format!("<li>{}</li>", html_blocks(blocks)),
+                })
+                .collect();
+            match start {
+                Some(1) => format!("<ol>{}</ol>", items),
+                Some(start) => format!("<ol start=\"{}\">{}</ol>", start, items),
+                None => format!("<ul>{}</ul>", items),
+            }
+        }
+        RichBlock::Quote(blocks) => format!("<blockquote>{}</blockquote>", html_blocks(blocks)),
+    }
+}
+
+fn html_inlines(content: &[RichInline]) -> String {
+    content
+        .iter()
+        .map(|inline| match inline {
+            RichInline::Text(text) => html_escape(text),
+            RichInline::Code(code) => format!("<code>{}</code>", html_escape(code)),
+            RichInline::LineBreak => "<br>".to_string(),
+            RichInline::Strong(inner) => format!("<strong>{}</strong>", html_inlines(inner)),
+            RichInline::Emphasis(inner) => format!("<em>{}</em>", html_inlines(inner)),
+            RichInline::Underline(inner) => format!("<u>{}</u>", html_inlines(inner)),
+            RichInline::Strikethrough(inner) => format!("<del>{}</del>", html_inlines(inner)),
+            RichInline::Link { href: Some(href), content } => format!(
+                "<a href=\"{}\" rel=\"noopener noreferrer\">{}</a>",
+                html_escape(href),
+                html_inlines(content)
+            ),
+            RichInline::Link { href: None, content } => html_inlines(content),
+            RichInline::Colored { foreground, content } => format!(
+                "<span style=\"color:{}\">{}</span>",
+                html_escape(foreground),
+                html_inlines(content)
+            ),
+        })
+        .collect()
+}
+
+pub(crate) fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+/// Escape text for Pango markup (XML-like escaping)
+pub(crate) fn pango_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn attribute_escape(text: &str) -> String {
+    pango_escape(text).replace('"', "&quot;")
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,348 @@
+// Grapheme-aware, markup-safe truncation for notification text
+// Used by the builder when a truncation policy is opted into, so over-long
+// titles and bodies are shortened instead of failing validation
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::ansi::{SGR_RESET, visible_offsets};
+use super::content::{RichText, decode_html_entities};
+
+/// `custom_data` key recording the original byte length of a truncated title
+pub const TRUNCATED_TITLE_KEY: &str = "truncated.title";
+/// `custom_data` key recording the original byte length of a truncated body
+pub const TRUNCATED_BODY_KEY: &str = "truncated.body";
+
+/// HTML elements that never take a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Policy for shortening text that exceeds a platform limit
+#[derive(Debug, Clone, PartialEq)]
+pub struct TruncationPolicy {
+    /// Marker appended to truncated text
+    pub ellipsis: String,
+    /// Prefer cutting at a sentence, line or word boundary over a bare grapheme
+    pub prefer_boundaries: bool,
+    /// Fraction of the kept text that may be given up to reach a boundary
+    pub boundary_window: f32,
+}
+
+impl Default for TruncationPolicy {
+    fn default() -> Self {
+        Self {
+            ellipsis: "…".to_string(),
+            prefer_boundaries: true,
+            boundary_window: 0.3,
+        }
+    }
+}
+
+impl TruncationPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_ellipsis(mut self, ellipsis: impl Into<String>) -> Self {
+        self.ellipsis = ellipsis.into();
+        self
+    }
+
+    pub fn with_prefer_boundaries(mut self, prefer_boundaries: bool) -> Self {
+        self.prefer_boundaries = prefer_boundaries;
+        self
+    }
+
+    /// Truncate plain text to at most `max_len` bytes
+    ///
+    /// Returns `None` when the text already fits.
+    pub fn truncate_plain(&self, text: &str, max_len: usize) -> Option<String> {
+        if text.len() <= max_len {
+            return None;
+        }
+
+        let ellipsis = self.ellipsis_for(max_len);
+        let cut = self.cut_point(text, max_len - ellipsis.len());
+        Some(format!("{}{}", text[..cut].trim_end(), ellipsis))
+    }
+
+    /// Truncate rich text so its plain-text rendering is at most `max_len` bytes
+    ///
+    /// HTML and Markdown are cut on visible text and any elements or emphasis
+    /// left open by the cut are closed again; ANSI output gets an SGR reset. Returns `None` when the text
+    /// already fits or the variant cannot be truncated safely.
+    pub fn truncate_rich_text(&self, text: &RichText, max_len: usize) -> Option<RichText> {
+        let plain_len = text.to_plain_text().len();
+        if plain_len <= max_len {
+            return None;
+        }
+
+        // Markup rendering can add or collapse whitespace, so shrink the budget
+        // by any overshoot until the rendered plain text fits
+        let mut budget = max_len;
+        loop {
+            let truncated = match text {
+                RichText::Plain(plain) => RichText::Plain(self.truncate_plain(plain, budget)?),
+                RichText::Html(html) => RichText::Html(self.truncate_html(html, budget)),
+                RichText::Markdown(md) => RichText::Markdown(self.truncate_markdown(md, budget)),
+                RichText::Ansi(ansi) => RichText::Ansi(self.truncate_ansi(ansi, budget)),
+                RichText::PlatformSpecific(_) => return None,
+            };
+
+            let rendered_len = truncated.to_plain_text().len();
+            if rendered_len <= max_len {
+                return Some(truncated);
+            }
+            if budget == 0 {
+                return None;
+            }
+            budget = budget.saturating_sub(rendered_len - max_len);
+        }
+    }
+
+    /// Ellipsis to use for a limit, dropped entirely when it alone would not fit
+    fn ellipsis_for(&self, max_len: usize) -> &str {
+        if self.ellipsis.len() < max_len {
+            &self.ellipsis
+        } else {
+            ""
+        }
+    }
+
+    /// Byte offset to cut `text` at so the kept prefix is at most `budget` bytes
+    fn cut_point(&self, text: &str, budget: usize) -> usize {
+        let mut end = 0;
+        for (index, grapheme) in text.grapheme_indices(true) {
+            if index + grapheme.len() > budget {
+                break;
+            }
+            end = index + grapheme.len();
+        }
+
+        if self.prefer_boundaries {
+            self.boundary_before(&text[..end]).unwrap_or(end)
+        } else {
+            end
+        }
+    }
+
+    /// Latest sentence, line or word boundary within the boundary window
+    fn boundary_before(&self, prefix: &str) -> Option<usize> {
+        let window = (prefix.len() as f32 * self.boundary_window.clamp(0.0, 1.0)) as usize;
+        let min = prefix.len().saturating_sub(window).max(1);
+
+        let sentence = prefix
+            .char_indices()
+            .zip(prefix.chars().skip(1))
+            .filter(|((_, c), next)| {
+                *c == '\n' || (matches!(c, '.' | '!' | '?') && next.is_whitespace())
+            })
+            .map(|((index, c), _)| index + c.len_utf8())
+            .filter(|&end| end >= min)
+            .last();
+        if sentence.is_some() {
+            return sentence;
+        }
+
+        prefix
+            .char_indices()
+            .filter(|(_, c)| c.is_whitespace())
+            .map(|(index, _)| index)
+            .rfind(|&end| end >= min)
+    }
+
+    /// Truncate HTML on its visible text and close any elements left open
+    fn truncate_html(&self, html: &str, max_len: usize) -> String {
+        let ellipsis = self.ellipsis_for(max_len);
+
+        // Visible text alongside the source offset each visible byte run ends at
+        let mut visible = String::new();
+        let mut source_ends: Vec<(usize, usize)> = Vec::new();
+        let mut index = 0;
+        while index < html.len() {
+            let rest = &html[index..];
+            if rest.starts_with('<') {
+                index += rest.find('>').map_or(rest.len(), |end| end + 1);
+            } else if rest.starts_with('&')
+                && let Some(end) = rest.find(';').filter(|&end| end <= 10)
+            {
+                visible.push_str(&decode_html_entities(&rest[..=end]));
+                index += end + 1;
+                source_ends.push((visible.len(), index));
+            } else {
+                let c = rest.chars().next().unwrap_or_default();
+                visible.push(c);
+                index += c.len_utf8();
+                source_ends.push((visible.len(), index));
+            }
+        }
+
+        let cut = self.cut_point(&visible, max_len - ellipsis.len());
+        let kept = visible[..cut].trim_end().len();
+        let source_cut = source_ends
+            .iter()
+            .take_while(|(visible_end, _)| *visible_end <= kept)
+            .last()
+            .map_or(0, |(_, source_end)| *source_end);
+
+        let mut truncated = html[..source_cut].to_string();
+        truncated.push_str(ellipsis);
+        for tag in open_html_elements(&html[..source_cut]).iter().rev() {
+            truncated.push_str(&format!("</{}>", tag));
+        }
+        truncated
+    }
+
+    /// Truncate terminal output on its visible text and reset any styling left active
+    fn truncate_ansi(&self, text: &str, max_len: usize) -> String {
+        let ellipsis = self.ellipsis_for(max_len);
+        let (visible, source_ends) = visible_offsets(text);
+
+        let cut = self.cut_point(&visible, max_len - ellipsis.len());
+        let kept = visible[..cut].trim_end().len();
+
+        // Text runs are copied verbatim, so a cut inside one maps byte for byte
+        let mut source_cut = 0;
+        let mut visible_start = 0;
+        for &(visible_end, source_end) in &source_ends {
+            if visible_end <= kept {
+                source_cut = source_end;
+            } else {
+                if visible_start < kept {
+                    source_cut = source_end - (visible_end - kept);
+                }
+                break;
+            }
+            visible_start = visible_end;
+        }
+
+        format!("{}{}{}", &text[..source_cut], ellipsis, SGR_RESET)
+    }
+
+    /// Truncate Markdown source and close emphasis, code spans and fences left open
+    fn truncate_markdown(&self, markdown: &str, max_len: usize) -> String {
+        let ellipsis = self.ellipsis_for(max_len);
+        let cut = self.cut_point(markdown, max_len - ellipsis.len());
+        let mut kept = markdown[..cut].trim_end();
+
+        // A link cut inside its destination would render as literal syntax
+        if let Some(open) = kept.rfind('[')
+            && let Some(dest) = kept[open..].find("](")
+            && !kept[open + dest..].contains(')')
+        {
+            kept = kept[..open].trim_end();
+        }
+
+        let mut truncated = kept.to_string();
+        truncated.push_str(ellipsis);
+        for marker in open_markdown_markers(kept).iter().rev() {
+            if *marker == "```" {
+                truncated.push('\n');
+            }
+            truncated.push_str(marker);
+        }
+        truncated
+    }
+}
+
+/// Names of HTML elements still open at the end of `html`, outermost first
+fn open_html_elements(html: &str) -> Vec<String> {
+    let mut stack: Vec<String> = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + len];
+        rest = &rest[start + len + 1..];
+
+        if tag.starts_with('!') || tag.starts_with('?') || tag.ends_with('/') {
+            continue;
+        }
+        if let Some(closing) = tag.strip_prefix('/') {
+            let name = closing.trim().to_ascii_lowercase();
+            if let Some(position) = stack.iter().rposition(|open| *open == name) {
+                stack.truncate(position);
+            }
+            continue;
+        }
+
+        let name = tag
+            .split(|c: char| c.is_whitespace())
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if !name.is_empty() && !VOID_ELEMENTS.contains(&name.as_str()) {
+            stack.push(name);
+        }
+    }
+
+    stack
+}
+
+/// Markdown markers still open at the end of `markdown`, outermost first
+fn open_markdown_markers(markdown: &str) -> Vec<&'static str> {
+    let mut stack: Vec<&'static str> = Vec::new();
+
+    for line in markdown.split('\n') {
+        if line.trim_start().starts_with("```") {
+            if stack.last() == Some(&"```") {
+                stack.pop();
+            } else {
+                stack.push("```");
+            }
+            continue;
+        }
+        if stack.last() == Some(&"```") {
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let in_code = stack.last() == Some(&"`");
+            let marker: Option<&'static str> = match c {
+                '`' => Some("`"),
+                _ if in_code => None,
+                '\\' => {
+                    i += 2;
+                    continue;
+                }
+                '*' if chars.get(i + 1) == Some(&'*') => Some("**"),
+                '*' => Some("*"),
+                '~' if chars.get(i + 1) == Some(&'~') => Some("~~"),
+                '_' => {
+                    // Intraword underscores (snake_case) are not emphasis
+                    let double = chars.get(i + 1) == Some(&'_');
+                    let after = chars.get(i + if double { 2 } else { 1 });
+                    let before = i.checked_sub(1).and_then(|j| chars.get(j));
+                    let intraword = before.is_some_and(|b| b.is_alphanumeric())
+                        && after.is_some_and(|a| a.is_alphanumeric());
+                    match (intraword, double) {
+                        (true, _) => None,
+                        (false, true) => Some("__"),
+                        (false, false) => Some("_"),
+                    }
+                }
+                _ => None,
+            };
+
+            match marker {
+                Some(marker) => {
+                    if stack.last() == Some(&marker) {
+                        stack.pop();
+                    } else {
+                        stack.push(marker);
+                    }
+                    i += marker.len();
+                }
+                None => i += 1,
+            }
+        }
+    }
+
+    stack
+}
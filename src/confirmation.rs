@@ -0,0 +1,236 @@
+//! Two-step confirmation for actions with an [`ActionConfirmation`]
+//!
+//! Pressing such an action doesn't run it. The manager shows a confirmation
+//! notification with the confirmation text and Confirm/Cancel buttons instead,
+//! in place of the original where the platform can replace notifications.
+//! The action's handler runs only after Confirm; Cancel, dismissing the
+//! confirmation or letting it expire count as cancelled.
+
+use std::sync::Arc;
+
+use crate::components::lifecycle::{NotificationState as LifecycleState, TransitionReason};
+use crate::{
+    ActionContext, ActionId, ActionStyle, ActivationType, InteractionEvent, InteractionOutcome,
+    InteractionRouter, NotificationAction, NotificationBuilder, NotificationId,
+    PlatformInteractionKind, RichText, UserInteraction,
+};
+
+/// Action ID of the Confirm button on confirmation notifications
+pub const CONFIRM_ACTION: &str = "confirmation.confirm";
+/// Action ID of the Cancel button on confirmation notifications
+pub const CANCEL_ACTION: &str = "confirmation.cancel";
+/// Interaction metadata key recording how a confirmation ended
+pub const CONFIRMATION_KEY: &str = "confirmation";
+
+/// Action waiting for its confirmation notification to be answered
+pub(crate) struct PendingConfirmation {
+    context: ActionContext,
+    interaction: UserInteraction,
+}
+
+/// How a confirmation ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resolution {
+    Confirmed,
+    Cancelled,
+    Expired,
+}
+
+impl Resolution {
+    fn as_str(self) -> &'static str {
+        match self {
+            Resolution::Confirmed => "confirmed",
+            Resolution::Cancelled => "cancelled",
+            Resolution::Expired => "expired",
+        }
+    }
+}
+
+impl InteractionRouter {
+    /// Show the confirmation for an action instead of running it
+    pub(crate) fn request_confirmation(
+        self: &Arc<Self>,
+        context: ActionContext,
+        interaction: UserInteraction,
+    ) {
+        let Some(action) = context.action.as_ref() else {
+            return;
+        };
+        let Some(confirmation) = action.confirmation.as_ref() else {
+            return;
+        };
+        let Some((priority, mut platform_integration, shown_natively, original)) =
+            self.state.get(&context.notification_id).map(|entry| {
+                let shown_natively = entry
+                    .lifecycle
+                    .platform_states
+                    .get(&context.platform)
+                    .is_some_and(|platform_state| platform_state.native_id.is_some());
+                (
+                    entry.content.priority,
+                    entry.platform_integration.clone(),
+                    shown_natively,
+                    entry.identity.clone(),
+                )
+            })
+        else {
+            return;
+        };
+
+        let in_place = shown_natively
+            && platform_integration
+                .platform_capabilities
+                .get(&context.platform)
+                .is_some_and(|capabilities| capabilities.supports_update_content);
+        let button = |id: &str, label: &str, style| NotificationAction {
+            id: ActionId::new(id),
+            label: label.to_string(),
+            icon: None,
+            style,
+            activation_type: ActivationType::Background,
+            url: None,
+            payload: None,
+            confirmation: None,
+        };
+        let confirm_style = match action.style {
+            ActionStyle::Destructive => ActionStyle::Destructive,
+            _ => ActionStyle::Default,
+        };
+
+        // Shown only where the action was pressed, with the original's capabilities and authorization
+        platform_integration.target_platforms = vec![context.platform];
+        let mut notification = match NotificationBuilder::new()
+            .with_title(confirmation.title.clone())
+            .with_body(RichText::plain(confirmation.message.clone()))
+            .with_priority(priority)
+            .with_platforms(vec![context.platform])
+            .with_action(button(CONFIRM_ACTION, &confirmation.confirm_label, confirm_style))
+            .with_action(button(CANCEL_ACTION, &confirmation.cancel_label, ActionStyle::Default))
+            .build()
+        {
+            Ok(notification) => notification,
+            Err(e) => {
+                ::tracing::warn!("Cannot show confirmation for action {}: {}", context.action_id, e);
+                self.record_resolution(context, interaction, Resolution::Cancelled);
+                return;
+            }
+        };
+        notification.platform_integration = platform_integration;
+        // Shown as the same app, in the same trace
+        notification.identity.correlation_id = original.correlation_id;
+        notification.identity.trace_span = original.trace_span;
+        notification.identity.creator_context = original.creator_context;
+        notification.identity.app = original.app;
+        if in_place {
+            notification.content = notification.content.with_replaces(context.notification_id);
+        }
+
+        let confirmation_id = notification.identity.id;
+        // Registered before queueing so an instant answer finds it
+        self.confirmations.insert(
+            confirmation_id,
+            PendingConfirmation {
+                context: context.clone(),
+                interaction,
+            },
+        );
+        if let Err(e) = crate::queue_notification(&self.state, notification) {
+            ::tracing::warn!("Cannot queue confirmation for action {}: {}", context.action_id, e);
+            if let Some((_, pending)) = self.confirmations.remove(&confirmation_id) {
+                self.record_resolution(pending.context, pending.interaction, Resolution::Cancelled);
+            }
+            return;
+        }
+
+        // The replaced original is no longer on screen
+        if in_place && let Some(mut entry) = self.state.get_mut(&context.notification_id) {
+            let correlation_id = entry.identity.correlation_id.clone();
+            let _ = entry.lifecycle.transition_to(
+                LifecycleState::Completed,
+                TransitionReason::Update,
+                Some(correlation_id),
+            );
+        }
+
+        let router = Arc::clone(self);
+        let timeout = self.action_handlers.confirmation_timeout();
+        tokio::spawn(async move {
+            tokio::time::sleep(timeout).await;
+            if let Some((_, pending)) = router.confirmations.remove(&confirmation_id) {
+                crate::withdraw_notification(&router.state, &router.platform_manager, confirmation_id)
+                    .await;
+                router.finish_confirmation(confirmation_id, LifecycleState::Expired, TransitionReason::Timeout);
+                router.record_resolution(pending.context, pending.interaction, Resolution::Expired);
+            }
+        });
+    }
+
+    /// Settle a pending confirmation answered by `event`
+    pub(crate) fn resolve_confirmation(self: &Arc<Self>, event: &InteractionEvent) {
+        let resolution = match &event.kind {
+            PlatformInteractionKind::Action(action_id) if action_id.as_str() == CONFIRM_ACTION => {
+                Resolution::Confirmed
+            }
+            PlatformInteractionKind::Action(action_id) if action_id.as_str() == CANCEL_ACTION => {
+                Resolution::Cancelled
+            }
+            PlatformInteractionKind::Dismissed => Resolution::Cancelled,
+            // Clicking the body or replying doesn't answer the confirmation
+            _ => return,
+        };
+        let Some((confirmation_id, pending)) = self.confirmations.remove(&event.notification_id) else {
+            return;
+        };
+
+        let router = Arc::clone(self);
+        tokio::spawn(async move {
+            // Answering through the notification closes it on most platforms, not all
+            crate::withdraw_notification(&router.state, &router.platform_manager, confirmation_id).await;
+            router.finish_confirmation(confirmation_id, LifecycleState::Completed, TransitionReason::UserInteraction);
+        });
+        self.record_resolution(pending.context, pending.interaction, resolution);
+    }
+
+    /// Run a confirmed action, or record the cancelled one as abandoned
+    fn record_resolution(
+        self: &Arc<Self>,
+        context: ActionContext,
+        mut interaction: UserInteraction,
+        resolution: Resolution,
+    ) {
+        interaction
+            .metadata
+            .insert(CONFIRMATION_KEY.to_string(), resolution.as_str().to_string());
+
+        if resolution == Resolution::Confirmed && self.action_handlers.handles(&context) {
+            self.dispatch(context, interaction);
+            return;
+        }
+        if resolution != Resolution::Confirmed {
+            interaction.outcome = InteractionOutcome::Abandoned;
+        }
+        if let Some(mut entry) = self.state.get_mut(&context.notification_id) {
+            entry.analytics.record_user_interaction(interaction);
+        }
+    }
+
+    fn finish_confirmation(
+        &self,
+        confirmation_id: NotificationId,
+        final_state: LifecycleState,
+        reason: TransitionReason,
+    ) {
+        if let Some(mut entry) = self.state.get_mut(&confirmation_id) {
+            let correlation_id = entry.identity.correlation_id.clone();
+            // Confirmations already interacted with can only complete
+            let final_state = if entry.lifecycle.state.can_transition_to(&final_state) {
+                final_state
+            } else {
+                LifecycleState::Completed
+            };
+            let _ = entry
+                .lifecycle
+                .transition_to(final_state, reason, Some(correlation_id));
+        }
+    }
+}
@@ -8,19 +8,35 @@
 #![recursion_limit = "256"]
 #![allow(hidden_glob_reexports)]
 
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
 use dashmap::DashMap;
 use tokio::task::JoinHandle;
 
+pub mod actions;
+pub mod ask;
 pub mod backends;
+pub mod blocking;
+pub mod command;
 pub mod components;
+pub mod confirmation;
+pub mod webhook;
 
 // Re-export all components for convenience
+pub use actions::*;
+pub use ask::*;
 pub use backends::*;
+pub use command::*;
 pub use components::*;
+pub use confirmation::*;
+pub use webhook::*;
+
+/// How often shutdown checks whether the queue has drained
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Longest shutdown waits for a platform to take one notification down
+const SHUTDOWN_CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// Internal state container for notification data
 #[allow(dead_code)] // Internal state management structure
@@ -40,12 +56,15 @@ pub struct Notification {
     pub platform_integration: PlatformIntegration,
     pub lifecycle: NotificationLifecycle,
     pub analytics: NotificationAnalytics,
+    /// Applied again when sending, against the limits the backends negotiated
+    pub truncation: Option<TruncationPolicy>,
 }
 
-/// Handle for querying notification status
+/// Handle for querying notification status and waiting on its progress
 pub struct NotificationHandle {
     pub id: NotificationId,
     state: Arc<DashMap<NotificationId, NotificationState>>,
+    interactions: tokio::sync::broadcast::Sender<InteractionEvent>,
 }
 
 impl NotificationHandle {
@@ -68,6 +87,167 @@ impl NotificationHandle {
     pub async fn analytics(&self) -> Option<NotificationAnalytics> {
         self.state.get(&self.id).map(|s| s.analytics.clone())
     }
+
+    /// Wait until the notification is shown on every target platform
+    ///
+    /// Fails once delivery failed, or when the notification ends without
+    /// having been shown.
+    pub async fn delivered(&self) -> NotificationResult<Vec<crate::components::DeliveryReceipt>> {
+        let mut transitions = self.watch();
+        loop {
+            if let Some(outcome) = self.delivery_outcome() {
+                return outcome;
+            }
+            if transitions.next().await.is_none() {
+                return self.delivery_outcome().unwrap_or_else(|| Err(untracked(self.id)));
+            }
+        }
+    }
+
+    /// Wait up to `timeout` for the user's next interaction with the notification
+    ///
+    /// `None` when the time runs out or the notification ends first.
+    pub async fn interaction(&self, timeout: Duration) -> Option<InteractionEvent> {
+        let mut interactions = self.interactions.subscribe();
+        let mut transitions = self.watch();
+        let wait = async {
+            loop {
+                tokio::select! {
+                    received = interactions.recv() => match received {
+                        Ok(event)
+                            if event.notification_id == self.id
+                                && !matches!(
+                                    event.kind,
+                                    PlatformInteractionKind::Withdrawn
+                                        | PlatformInteractionKind::Expired
+                                        | PlatformInteractionKind::ServerChanged
+                                ) =>
+                        {
+                            return Some(event);
+                        }
+                        Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+                    },
+                    transition = transitions.next() => {
+                        if transition.is_none_or(|transition| transition.to_state.is_terminal()) {
+                            return None;
+                        }
+                    }
+                }
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.ok().flatten()
+    }
+
+    /// Wait until the notification finished, and return its final state
+    ///
+    /// Finished means a terminal state, or `Failed` with no retry left.
+    pub async fn finished(&self) -> crate::components::lifecycle::NotificationState {
+        let mut transitions = self.watch();
+        let mut last = None;
+        while let Some(transition) = transitions.next().await {
+            last = Some(transition.to_state);
+        }
+        // Already finished, or the last transition was missed while lagging
+        self.state
+            .get(&self.id)
+            .map(|s| s.lifecycle.state.clone())
+            .or(last)
+            .unwrap_or_default()
+    }
+
+    /// Every state transition from now on, as it happens
+    ///
+    /// Ends after the transition that finished the notification: into a
+    /// terminal state, or into `Failed` with no retry left. A notification
+    /// that already finished yields nothing.
+    pub fn watch(&self) -> TransitionWatch {
+        // Subscribed under the entry's lock, so no transition slips in between
+        let receiver = self
+            .state
+            .get(&self.id)
+            .filter(|s| !s.lifecycle.is_finished())
+            .map(|s| s.lifecycle.transitions.subscribe());
+        TransitionWatch {
+            id: self.id,
+            state: Arc::clone(&self.state),
+            receiver,
+        }
+    }
+
+    /// Result of delivery, or `None` while it's still underway
+    fn delivery_outcome(&self) -> Option<NotificationResult<Vec<crate::components::DeliveryReceipt>>> {
+        use crate::components::lifecycle::NotificationState as LifecycleState;
+
+        let Some(entry) = self.state.get(&self.id) else {
+            return Some(Err(untracked(self.id)));
+        };
+        let lifecycle = &entry.lifecycle;
+        if lifecycle.timing.delivered_at.is_some() {
+            let receipts = entry
+                .platform_integration
+                .target_platforms
+                .iter()
+                .filter_map(|platform| lifecycle.platform_states.get(platform)?.receipt.clone())
+                .collect();
+            return Some(Ok(receipts));
+        }
+        match &lifecycle.state {
+            LifecycleState::Failed(details) if !lifecycle.should_retry() => Some(Err(NotificationError::DeliveryFailed {
+                platform_errors: details.platform_errors.clone(),
+                message: format!("Notification could not be delivered: {}", details.message),
+            })),
+            state if state.is_terminal() => Some(Err(NotificationError::ResourceError {
+                resource_type: "notification".to_string(),
+                resource_id: self.id.to_string(),
+                kind: components::ErrorKind::Other,
+                message: format!("Notification ended {:?} before it was delivered", state),
+                source: None,
+            })),
+            _ => None,
+        }
+    }
+}
+
+/// State transitions of one notification, from [`NotificationHandle::watch`]
+pub struct TransitionWatch {
+    id: NotificationId,
+    state: Arc<DashMap<NotificationId, NotificationState>>,
+    /// `None` once the notification finished
+    receiver: Option<tokio::sync::broadcast::Receiver<crate::components::lifecycle::StateTransition>>,
+}
+
+impl TransitionWatch {
+    /// Next transition, or `None` once the notification finished
+    pub async fn next(&mut self) -> Option<crate::components::lifecycle::StateTransition> {
+        let receiver = self.receiver.as_mut()?;
+        loop {
+            match receiver.recv().await {
+                Ok(transition) => {
+                    // Whether a failure is retried was decided before the transition
+                    let finished = transition.to_state.is_terminal()
+                        || (matches!(
+                            transition.to_state,
+                            crate::components::lifecycle::NotificationState::Failed(_)
+                        ) && self
+                            .state
+                            .get(&self.id)
+                            .is_none_or(|s| !s.lifecycle.retries_left()));
+                    if finished {
+                        self.receiver = None;
+                    }
+                    return Some(transition);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    ::tracing::warn!("Missed {} notification state transitions", skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    self.receiver = None;
+                    return None;
+                }
+            }
+        }
+    }
 }
 
 /// Public status structure for notification queries
@@ -79,9 +259,19 @@ pub struct NotificationStatus {
     pub created_at: crate::components::time_wrapper::DefaultableInstant,
 }
 
-/// Result of NotificationManager shutdown operation
+/// What shutdown does with notifications that haven't finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShutdownPolicy {
+    /// Deliver queued notifications before stopping; shown ones stay up
+    #[default]
+    Drain,
+    /// Cancel every notification that hasn't finished and take shown ones down
+    Abort,
+}
+
+/// How the background workers stopped
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ShutdownResult {
+pub enum WorkerShutdown {
     /// All workers shut down cleanly
     Clean,
     /// One or more workers panicked during shutdown
@@ -90,32 +280,99 @@ pub enum ShutdownResult {
     TimedOut,
 }
 
+/// Result of NotificationManager shutdown operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownResult {
+    pub workers: WorkerShutdown,
+    /// Queued notifications delivered while draining
+    pub delivered: usize,
+    /// Queued notifications whose delivery failed while draining
+    pub failed: usize,
+    /// Notifications moved to `Cancelled`, including retries that were still scheduled
+    pub cancelled: usize,
+    /// Shown notifications taken down, counted per platform
+    pub closed: usize,
+    /// Shown notifications that could not be taken down, counted per platform
+    pub close_errors: usize,
+}
+
+/// User interaction with a notification sent through the manager
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InteractionEvent {
+    pub notification_id: NotificationId,
+    pub platform: Platform,
+    pub kind: PlatformInteractionKind,
+}
+
 /// Notification Manager - main entry point for the library
 pub struct NotificationManager {
     state: Arc<DashMap<NotificationId, NotificationState>>,
-    platform_backends: Arc<HashMap<Platform, Box<dyn PlatformBackend>>>,
+    platform_manager: Arc<PlatformManager>,
     task_handles: Vec<JoinHandle<()>>,
     shutdown_tx: tokio::sync::broadcast::Sender<()>,
+    interactions: Arc<InteractionRouter>,
+    app_identity: parking_lot::RwLock<AppIdentity>,
 }
 
 impl NotificationManager {
     /// Create a new notification manager and spawn background workers
+    ///
+    /// # Panics
+    ///
+    /// Outside a tokio runtime; see [`NotificationManager::try_new`] and [`blocking`].
+    #[deprecated(note = "panics outside a tokio runtime; use `NotificationManager::try_new`")]
+    #[allow(clippy::new_without_default)] // Default can't fail, and creating a manager can
     pub fn new() -> Self {
+        Self::try_new().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create a new notification manager, or fail outside a tokio runtime
+    pub fn try_new() -> NotificationResult<Self> {
+        ensure_runtime()?;
+        Self::try_with_platform_manager(PlatformManager::with_backends(
+            PlatformBackendFactory::get_supported_backends(),
+        ))
+    }
+
+    /// Create a manager that delivers through the given backends
+    ///
+    /// # Panics
+    ///
+    /// Outside a tokio runtime; see [`NotificationManager::try_with_platform_manager`].
+    #[deprecated(
+        note = "panics outside a tokio runtime; use `NotificationManager::try_with_platform_manager`"
+    )]
+    pub fn with_platform_manager(platform_manager: PlatformManager) -> Self {
+        Self::try_with_platform_manager(platform_manager).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create a manager that delivers through the given backends, or fail outside a tokio runtime
+    pub fn try_with_platform_manager(platform_manager: PlatformManager) -> NotificationResult<Self> {
+        ensure_runtime()?;
         let (shutdown_tx, _) = tokio::sync::broadcast::channel(1);
+        let (interaction_tx, _) = tokio::sync::broadcast::channel(64);
         let state = Arc::new(DashMap::new());
-        let platform_backends = Arc::new(PlatformBackendFactory::get_supported_backends());
+        let platform_manager = Arc::new(platform_manager);
+        let interactions = Arc::new(InteractionRouter {
+            state: Arc::clone(&state),
+            platform_manager: Arc::clone(&platform_manager),
+            interaction_tx,
+            action_handlers: ActionHandlers::new(),
+            confirmations: DashMap::new(),
+        });
 
         // Spawn background workers
-        let task_handles = vec![
+        let mut task_handles = vec![
             // Lifecycle monitor worker
             tokio::spawn(lifecycle_monitor(
                 Arc::clone(&state),
+                Arc::clone(&platform_manager),
                 shutdown_tx.subscribe(),
             )),
             // Delivery worker
             tokio::spawn(delivery_worker(
                 Arc::clone(&state),
-                Arc::clone(&platform_backends),
+                Arc::clone(&platform_manager),
                 shutdown_tx.subscribe(),
             )),
             // Analytics aggregator
@@ -125,46 +382,211 @@ impl NotificationManager {
             )),
         ];
 
-        Self {
+        // Interaction listener for every backend that reports interactions
+        for platform in platform_manager.platforms() {
+            if let Some(platform_interactions) = platform_manager
+                .backend(platform)
+                .and_then(|backend| backend.subscribe_interactions())
+            {
+                task_handles.push(tokio::spawn(interaction_listener(
+                    Arc::clone(&interactions),
+                    platform_interactions,
+                    shutdown_tx.subscribe(),
+                )));
+            }
+        }
+
+        Ok(Self {
             state,
-            platform_backends,
+            platform_manager,
             task_handles,
             shutdown_tx,
-        }
+            interactions,
+            app_identity: parking_lot::RwLock::new(AppIdentity::default()),
+        })
+    }
+
+    /// Show notifications as coming from `app` unless they carry their own identity
+    pub fn with_app_identity(self, app: AppIdentity) -> Self {
+        self.set_app_identity(app);
+        self
+    }
+
+    /// Identity of notifications sent without one of their own
+    pub fn app_identity(&self) -> AppIdentity {
+        self.app_identity.read().clone()
+    }
+
+    pub fn set_app_identity(&self, app: AppIdentity) {
+        *self.app_identity.write() = app;
+    }
+
+    /// Platform manager holding the backends and their negotiated capabilities
+    pub fn platform_manager(&self) -> &PlatformManager {
+        &self.platform_manager
     }
 
     /// Send a notification and get a handle for tracking
+    ///
+    /// Content is revalidated against the capabilities negotiated with each target
+    /// platform's backend before the notification is queued.
     pub async fn send(
         &self,
-        notification: Notification,
+        mut notification: Notification,
     ) -> Result<NotificationHandle, NotificationError> {
-        let id = notification.identity.id;
-        let correlation_id = notification.identity.correlation_id.clone();
-
-        // Transition lifecycle to Queued state so delivery_worker will process it
-        let mut lifecycle = notification.lifecycle;
-        lifecycle.transition_to(
-            crate::components::lifecycle::NotificationState::Queued,
-            crate::components::lifecycle::TransitionReason::QueuedByAttentionManager,
-            Some(correlation_id),
-        )?;
-
-        // Store notification state with Queued lifecycle
-        self.state.insert(
-            id,
-            NotificationState {
-                identity: notification.identity,
-                content: notification.content,
-                lifecycle,  // Now in Queued state, ready for delivery_worker
-                platform_integration: notification.platform_integration,
-                analytics: notification.analytics,
-            },
-        );
+        if notification.identity.app.is_none() {
+            let app = self.app_identity();
+            // Built without a creator of its own: the app is the creator
+            if notification.identity.creator_context.is_none() {
+                notification.identity = notification.identity.with_app(app);
+            } else {
+                notification.identity.app = Some(app);
+            }
+        }
+        self.validate_negotiated(&mut notification).await?;
+        let id = queue_notification(&self.state, notification)?;
+
+        Ok(self.handle_for(id))
+    }
+
+    /// Handle for a notification sent through this manager
+    pub fn handle(&self, id: NotificationId) -> Option<NotificationHandle> {
+        self.state.contains_key(&id).then(|| self.handle_for(id))
+    }
 
-        Ok(NotificationHandle {
+    fn handle_for(&self, id: NotificationId) -> NotificationHandle {
+        NotificationHandle {
             id,
             state: Arc::clone(&self.state),
-        })
+            interactions: self.interactions.interaction_tx.clone(),
+        }
+    }
+
+    /// Change a notification's content, on screen too if it's shown already
+    ///
+    /// A notification still queued is delivered with the new content.
+    pub async fn update(&self, id: NotificationId, update: NotificationUpdate) -> NotificationResult<()> {
+        // Collect native IDs first so no map entry is locked across an await
+        let (app, shown): (AppIdentity, Vec<(Platform, String)>) = {
+            let mut entry = self.state.get_mut(&id).ok_or_else(|| untracked(id))?;
+            if entry.lifecycle.state.is_terminal() {
+                return Err(NotificationError::ValidationError {
+                    field: "notification".to_string(),
+                    message: format!("Notification {} already ended {:?}", id, entry.lifecycle.state),
+                });
+            }
+            update.apply_to(&mut entry.content);
+            let shown = entry
+                .lifecycle
+                .platform_states
+                .iter()
+                .filter(|(_, platform_state)| {
+                    platform_state.status == crate::components::lifecycle::PlatformDeliveryStatus::Delivered
+                })
+                .filter_map(|(platform, platform_state)| {
+                    platform_state.native_id.clone().map(|native_id| (*platform, native_id))
+                })
+                .collect();
+            (entry.identity.app.clone().unwrap_or_default(), shown)
+        };
+
+        for (platform, native_id) in shown {
+            if let Some(backend) = self.platform_manager.backend(platform) {
+                backend.update_notification(&native_id, &update, &app).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Cancel a notification: it's dropped from the queue, or taken down if shown
+    pub async fn cancel(&self, id: NotificationId) -> NotificationResult<()> {
+        {
+            let mut entry = self.state.get_mut(&id).ok_or_else(|| untracked(id))?;
+            if !entry.lifecycle.state.is_terminal() {
+                let correlation_id = entry.identity.correlation_id.clone();
+                entry.lifecycle.transition_to(
+                    crate::components::lifecycle::NotificationState::Cancelled,
+                    crate::components::lifecycle::TransitionReason::Cancellation,
+                    Some(correlation_id),
+                )?;
+            }
+        }
+        withdraw_notification(&self.state, &self.platform_manager, id).await;
+        Ok(())
+    }
+
+    /// Negotiate capabilities for every target platform and validate content against them
+    ///
+    /// Only targets with a registered backend are negotiated and validated.
+    async fn validate_negotiated(&self, notification: &mut Notification) -> NotificationResult<()> {
+        let integration = &mut notification.platform_integration;
+        integration.negotiate_capabilities(&self.platform_manager).await?;
+
+        // Live limits may be stricter than the ones the notification was built against
+        let negotiated: Vec<Platform> = integration.platform_capabilities.keys().copied().collect();
+        if let Some(policy) = &notification.truncation {
+            truncate_to_limits(policy, &mut notification.content, integration, &negotiated);
+        }
+
+        for platform in &negotiated {
+            let limits = integration.get_platform_limits(*platform);
+            notification.content.validate(&limits)?;
+        }
+        if negotiated.contains(&Platform::Linux) {
+            backends::image_utils::validate_system_icons(&notification.content).await?;
+        }
+
+        // Features no negotiated platform supports degrade; critical ones reject the notification
+        if negotiated.is_empty() {
+            return Ok(());
+        }
+        let requested_features = notification.content.requested_features();
+        let degradations = integration.apply_degradation(&requested_features);
+        for degradation in &degradations {
+            if let FeatureDegradation::CriticalUnsupported { feature, should_fail: true } = degradation {
+                return Err(NotificationError::ValidationError {
+                    field: feature.clone(),
+                    message: format!("Critical feature '{}' is not supported by any target platform", feature),
+                });
+            }
+        }
+        for degradation in &degradations {
+            notification.content.degrade(degradation);
+        }
+
+        Ok(())
+    }
+
+    /// Handlers run when the user invokes a notification action
+    pub fn action_handlers(&self) -> &ActionHandlers {
+        &self.interactions.action_handlers
+    }
+
+    /// Webhook settings and dead letters for action callbacks
+    pub fn webhooks(&self) -> &Webhooks {
+        self.interactions.action_handlers.webhooks()
+    }
+
+    /// Subscribe to interactions with notifications sent through this manager
+    pub fn subscribe_interactions(&self) -> tokio::sync::broadcast::Receiver<InteractionEvent> {
+        self.interactions.interaction_tx.subscribe()
+    }
+
+    /// Record an interaction that arrived outside the platform backends
+    ///
+    /// The interaction is recorded in the notification's analytics and lifecycle
+    /// and published to `subscribe_interactions` like a backend-reported one.
+    pub fn report_interaction(
+        &self,
+        notification_id: NotificationId,
+        platform: Platform,
+        kind: PlatformInteractionKind,
+    ) {
+        self.interactions.record(InteractionEvent {
+            notification_id,
+            platform,
+            kind,
+        });
     }
 
     /// Track a notification by ID
@@ -177,13 +599,26 @@ impl NotificationManager {
         })
     }
 
-    /// Gracefully shutdown the manager and all background workers with default 30s timeout
+    /// Gracefully shutdown the manager, draining queued notifications for up to 30s
     pub async fn shutdown(self) -> ShutdownResult {
         self.shutdown_with_timeout(Duration::from_secs(30)).await
     }
 
-    /// Gracefully shutdown the manager with custom timeout duration
+    /// Gracefully shutdown the manager, draining queued notifications for up to `timeout`
     pub async fn shutdown_with_timeout(self, timeout: Duration) -> ShutdownResult {
+        self.shutdown_with_policy(ShutdownPolicy::Drain, timeout).await
+    }
+
+    /// Shutdown the manager, handling unfinished notifications as `policy` says
+    ///
+    /// `timeout` bounds draining and, separately, waiting for the background workers.
+    pub async fn shutdown_with_policy(
+        self,
+        policy: ShutdownPolicy,
+        timeout: Duration,
+    ) -> ShutdownResult {
+        use crate::components::lifecycle::NotificationState as LifecycleState;
+
         let start = std::time::Instant::now();
         
         // PHASE 1: Collect metrics BEFORE shutdown
@@ -195,10 +630,10 @@ impl NotificationManager {
         
         for entry in self.state.iter() {
             match &entry.value().lifecycle.state {
-                crate::components::lifecycle::NotificationState::Queued => queued_count += 1,
-                crate::components::lifecycle::NotificationState::Delivering => delivering_count += 1,
-                crate::components::lifecycle::NotificationState::Delivered => delivered_count += 1,
-                crate::components::lifecycle::NotificationState::Failed(_) => failed_count += 1,
+                LifecycleState::Queued => queued_count += 1,
+                LifecycleState::Delivering => delivering_count += 1,
+                LifecycleState::Delivered => delivered_count += 1,
+                LifecycleState::Failed(_) => failed_count += 1,
                 _ => other_count += 1,
             }
         }
@@ -207,62 +642,45 @@ impl NotificationManager {
         let in_flight_count = queued_count + delivering_count;
         
         ::tracing::info!(
-            "NotificationManager shutdown starting - Total: {}, In-flight: {} (Queued: {}, Delivering: {}), Delivered: {}, Failed: {}, Other: {}",
-            total_notifications, in_flight_count, queued_count, delivering_count, delivered_count, failed_count, other_count
+            "NotificationManager shutdown starting ({:?}) - Total: {}, In-flight: {} (Queued: {}, Delivering: {}), Delivered: {}, Failed: {}, Other: {}",
+            policy, total_notifications, in_flight_count, queued_count, delivering_count, delivered_count, failed_count, other_count
         );
         
-        // PHASE 2: Cancel all non-terminal notifications on their target platforms
-        let mut cancelled_count = 0;
-        let mut cancel_errors = 0;
-        
-        for entry in self.state.iter() {
-            let notification = entry.value();
-            // Only cancel notifications in Delivering state (already sent to platform)
-            // Queued notifications haven't been delivered yet, so nothing to cancel
-            if matches!(
-                notification.lifecycle.state,
-                crate::components::lifecycle::NotificationState::Delivering
-            ) {
-                let notification_id = notification.identity.id.to_string();
-
-                // Cancel on each target platform for this notification
-                for platform in &notification.platform_integration.target_platforms {
-                    if let Some(backend) = self.platform_backends.get(platform) {
-                        // Wrap cancellation in timeout (2 seconds per notification)
-                        let cancel_result = tokio::time::timeout(
-                            std::time::Duration::from_secs(2),
-                            backend.cancel_notification(&notification_id)
-                        ).await;
-
-                        match cancel_result {
-                            Ok(Ok(())) => {
-                                cancelled_count += 1;
-                            }
-                            Ok(Err(e)) => {
-                                ::tracing::warn!(
-                                    "Failed to cancel notification {} on {:?}: {}",
-                                    notification_id, platform, e
-                                );
-                                cancel_errors += 1;
-                            }
-                            Err(_) => {
-                                ::tracing::warn!(
-                                    "Timeout cancelling notification {} on {:?} (exceeded 2s)",
-                                    notification_id, platform
-                                );
-                                cancel_errors += 1;
-                            }
-                        }
-                    }
+        // PHASE 2: Let the delivery worker get through the queue
+        let mut drained_count = 0;
+        let mut drain_failed_count = 0;
+        if policy == ShutdownPolicy::Drain {
+            let queued: Vec<NotificationId> = self
+                .state
+                .iter()
+                .filter(|entry| awaits_delivery(&entry.lifecycle.state))
+                .map(|entry| *entry.key())
+                .collect();
+            let deadline = tokio::time::Instant::now() + timeout;
+            while self.state.iter().any(|entry| awaits_delivery(&entry.lifecycle.state))
+                && tokio::time::Instant::now() < deadline
+            {
+                tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+            }
+
+            for id in queued {
+                let Some(entry) = self.state.get(&id) else {
+                    continue;
+                };
+                if entry.lifecycle.state.is_failed() {
+                    drain_failed_count += 1;
+                } else if entry
+                    .lifecycle
+                    .platform_states
+                    .values()
+                    .any(|platform_state| platform_state.native_id.is_some())
+                {
+                    drained_count += 1;
                 }
             }
-        }
-        
-        if cancelled_count > 0 || cancel_errors > 0 {
-            ::tracing::info!(
-                "Cancelled {} notification-platform pairs ({} errors)",
-                cancelled_count, cancel_errors
-            );
+            if self.state.iter().any(|entry| awaits_delivery(&entry.lifecycle.state)) {
+                ::tracing::warn!("Drain timed out after {:?}, cancelling the rest", timeout);
+            }
         }
         
         // PHASE 3: Send shutdown signal to workers
@@ -279,33 +697,77 @@ impl NotificationManager {
             results
         }).await;
         
-        let result = match shutdown_result {
+        let workers = match shutdown_result {
             Ok(results) => {
                 let panicked: Vec<_> = results.iter()
                     .filter(|r| r.is_err())
                     .collect();
                 
                 if panicked.is_empty() {
-                    ShutdownResult::Clean
+                    WorkerShutdown::Clean
                 } else {
                     ::tracing::error!("{} worker(s) panicked during shutdown", panicked.len());
-                    ShutdownResult::WorkersPanicked(panicked.len())
+                    WorkerShutdown::WorkersPanicked(panicked.len())
                 }
             }
             Err(_) => {
                 ::tracing::error!("Shutdown timed out after {:?}", timeout);
-                ShutdownResult::TimedOut
+                WorkerShutdown::TimedOut
+            }
+        };
+
+        // PHASE 5: Cancel what won't finish now that nothing delivers or retries
+        let cancelled = cancel_unfinished(&self.state, policy);
+
+        // PHASE 6: Take down what the cancelled notifications left on screen
+        let mut closed_count = 0;
+        let mut close_errors = 0;
+        for (notification_id, app, platform, native_id) in shown_by(&self.state, &cancelled) {
+            let Some(backend) = self.platform_manager.backend(platform) else {
+                continue;
+            };
+            let result = match tokio::time::timeout(
+                SHUTDOWN_CLOSE_TIMEOUT,
+                backend.cancel_notification(&native_id, &app),
+            )
+            .await
+            {
+                Ok(result) => result,
+                Err(_) => Err(NotificationError::TimeoutError {
+                    operation: format!("closing notification {}", notification_id),
+                    timeout_duration: SHUTDOWN_CLOSE_TIMEOUT,
+                }),
+            };
+            match &result {
+                Ok(()) => closed_count += 1,
+                Err(e) => {
+                    ::tracing::warn!(
+                        "Failed to close notification {} on {:?}: {}",
+                        notification_id, platform, e
+                    );
+                    close_errors += 1;
+                }
             }
+            record_close(&self.state, notification_id, platform, result);
+        }
+
+        let result = ShutdownResult {
+            workers,
+            delivered: drained_count,
+            failed: drain_failed_count,
+            cancelled: cancelled.len(),
+            closed: closed_count,
+            close_errors,
         };
         
-        // PHASE 5: Log final metrics
+        // PHASE 7: Log final metrics
         let elapsed = start.elapsed();
         ::tracing::info!(
-            "NotificationManager shutdown completed in {:?} - Result: {:?}, Notifications cancelled: {}, Cancel errors: {}",
-            elapsed, result, cancelled_count, cancel_errors
+            "NotificationManager shutdown completed in {:?} - Result: {:?}",
+            elapsed, result
         );
         
-        // PHASE 6: Cleanup cached temp images
+        // PHASE 8: Cleanup cached temp images
         crate::backends::cleanup_all_cached_images();
         ::tracing::debug!("Cleaned up cached notification images");
 
@@ -313,9 +775,320 @@ impl NotificationManager {
     }
 }
 
-impl Default for NotificationManager {
-    fn default() -> Self {
-        Self::new()
+/// Fail unless called from within a tokio runtime, which the background workers need
+fn ensure_runtime() -> NotificationResult<()> {
+    tokio::runtime::Handle::try_current()
+        .map(|_| ())
+        .map_err(|_| NotificationError::ResourceError {
+            resource_type: "runtime".to_string(),
+            resource_id: "tokio".to_string(),
+            kind: components::ErrorKind::Unsupported,
+            message: "NotificationManager must be created within a tokio runtime; \
+                      synchronous code can use blocking::NotificationManager"
+                .to_string(),
+            source: None,
+        })
+}
+
+/// Error for a notification ID this manager doesn't track
+pub(crate) fn untracked(id: NotificationId) -> NotificationError {
+    NotificationError::ResourceError {
+        resource_type: "notification".to_string(),
+        resource_id: id.to_string(),
+        kind: components::ErrorKind::NotFound,
+        message: "Notification is not tracked".to_string(),
+        source: None,
+    }
+}
+
+/// Store a notification in the Queued state so the delivery worker picks it up
+fn queue_notification(
+    state: &DashMap<NotificationId, NotificationState>,
+    notification: Notification,
+) -> NotificationResult<NotificationId> {
+    let id = notification.identity.id;
+    let correlation_id = notification.identity.correlation_id.clone();
+
+    // Transition lifecycle to Queued state so delivery_worker will process it
+    let mut lifecycle = notification.lifecycle;
+    lifecycle.transition_to(
+        crate::components::lifecycle::NotificationState::Queued,
+        crate::components::lifecycle::TransitionReason::QueuedByAttentionManager,
+        Some(correlation_id),
+    )?;
+
+    // Store notification state with Queued lifecycle
+    state.insert(
+        id,
+        NotificationState {
+            identity: notification.identity,
+            content: notification.content,
+            lifecycle,  // Now in Queued state, ready for delivery_worker
+            platform_integration: notification.platform_integration,
+            analytics: notification.analytics,
+        },
+    );
+
+    Ok(id)
+}
+
+/// Routes interactions to analytics, action handlers, confirmations and subscribers
+struct InteractionRouter {
+    state: Arc<DashMap<NotificationId, NotificationState>>,
+    platform_manager: Arc<PlatformManager>,
+    interaction_tx: tokio::sync::broadcast::Sender<InteractionEvent>,
+    action_handlers: ActionHandlers,
+    /// Confirmation notifications awaiting an answer, by their ID
+    confirmations: DashMap<NotificationId, confirmation::PendingConfirmation>,
+}
+
+impl InteractionRouter {
+    /// Record an interaction in the notification's state, run its action and publish it
+    ///
+    /// Interactions with a handler are recorded in analytics once the handler
+    /// finished, with its outcome and run time. Actions with a confirmation
+    /// run only after it is confirmed.
+    fn record(self: &Arc<Self>, event: InteractionEvent) {
+        if matches!(
+            event.kind,
+            PlatformInteractionKind::Withdrawn
+                | PlatformInteractionKind::Expired
+                | PlatformInteractionKind::ServerChanged
+        ) {
+            self.record_withdrawal(&event);
+            let _ = self.interaction_tx.send(event);
+            return;
+        }
+        if self.confirmations.contains_key(&event.notification_id) {
+            self.resolve_confirmation(&event);
+        }
+
+        let mut needs_confirmation = None;
+        if let Some(mut entry) = self.state.get_mut(&event.notification_id) {
+            let mut metadata = std::collections::HashMap::new();
+            // Replies run the action sharing their input's ID, the way text-input actions pair up
+            let invoked = match &event.kind {
+                PlatformInteractionKind::Action(action_id) => {
+                    metadata.insert("action_id".to_string(), action_id.to_string());
+                    Some((action_id.clone(), std::collections::HashMap::new()))
+                }
+                PlatformInteractionKind::Reply { input_id, text } => {
+                    let input_id = input_id
+                        .as_ref()
+                        .map(|input_id| input_id.as_str().to_string())
+                        .unwrap_or_else(|| REPLY_ACTION.to_string());
+                    Some((
+                        ActionId::new(input_id.clone()),
+                        std::collections::HashMap::from([(input_id, text.clone())]),
+                    ))
+                }
+                _ => None,
+            };
+            let interaction_type = match &event.kind {
+                PlatformInteractionKind::Action(_) => InteractionType::ActionPressed,
+                PlatformInteractionKind::Reply { .. } => InteractionType::InputSubmitted,
+                _ => InteractionType::Dismissed,
+            };
+            let action_context = invoked.map(|(action_id, inputs)| ActionContext {
+                notification_id: event.notification_id,
+                correlation_id: entry.identity.correlation_id.clone(),
+                trace_span: entry.identity.trace_span.clone(),
+                platform: event.platform,
+                action: entry
+                    .content
+                    .interactions
+                    .actions
+                    .iter()
+                    .find(|action| action.id == action_id)
+                    .cloned(),
+                action_id,
+                category: entry.content.category.as_ref().map(|category| category.identifier.clone()),
+                files: actions::attached_files(&entry.content.media),
+                inputs,
+            });
+            let response_time = entry
+                .lifecycle
+                .timing
+                .delivered_at
+                .map(|delivered_at| delivered_at.elapsed())
+                .unwrap_or_default();
+            let interaction = UserInteraction {
+                interaction_type,
+                platform: event.platform,
+                response_time,
+                metadata,
+                ..UserInteraction::default()
+            };
+            let correlation_id = entry.identity.correlation_id.clone();
+            let _ = entry.lifecycle.transition_to(
+                crate::components::lifecycle::NotificationState::InteractionReceived,
+                crate::components::lifecycle::TransitionReason::UserInteraction,
+                Some(correlation_id),
+            );
+
+            match action_context {
+                Some(context)
+                    if context
+                        .action
+                        .as_ref()
+                        .is_some_and(|action| action.confirmation.is_some()) =>
+                {
+                    needs_confirmation = Some((context, interaction));
+                }
+                Some(context) if self.action_handlers.handles(&context) => {
+                    self.dispatch(context, interaction);
+                }
+                _ => entry.analytics.record_user_interaction(interaction),
+            }
+        }
+        // Queued once the entry is released; inserting while holding it could deadlock its shard
+        if let Some((context, interaction)) = needs_confirmation {
+            self.request_confirmation(context, interaction);
+        }
+
+        // No subscribers is fine, the interaction is still recorded
+        let _ = self.interaction_tx.send(event);
+    }
+
+    /// Forget a native ID the platform withdrew, expiring the notification once it is shown nowhere
+    fn record_withdrawal(&self, event: &InteractionEvent) {
+        let reason = if event.kind == PlatformInteractionKind::Expired {
+            crate::components::lifecycle::TransitionReason::Expiration
+        } else {
+            crate::components::lifecycle::TransitionReason::SystemEvent
+        };
+        let Some(mut entry) = self.state.get_mut(&event.notification_id) else {
+            return;
+        };
+        let correlation_id = entry.identity.correlation_id.clone();
+        let lifecycle = &mut entry.lifecycle;
+        if let Some(platform_state) = lifecycle.platform_states.get_mut(&event.platform) {
+            platform_state.native_id = None;
+            platform_state.status = crate::components::lifecycle::PlatformDeliveryStatus::Cancelled;
+        }
+        if lifecycle
+            .platform_states
+            .values()
+            .all(|platform_state| platform_state.native_id.is_none())
+        {
+            let _ = lifecycle.transition_to(
+                crate::components::lifecycle::NotificationState::Expired,
+                reason,
+                Some(correlation_id),
+            );
+        }
+    }
+
+    /// Run the action's handler in the background, then record the interaction with its outcome
+    fn dispatch(self: &Arc<Self>, context: ActionContext, interaction: UserInteraction) {
+        let router = Arc::clone(self);
+        tokio::spawn(async move {
+            let notification_id = context.notification_id;
+            let started = std::time::Instant::now();
+            let outcome = router.action_handlers.dispatch(context).await.unwrap_or_default();
+            if let Some(mut entry) = router.state.get_mut(&notification_id) {
+                entry.analytics.record_user_interaction(UserInteraction {
+                    duration: Some(started.elapsed()),
+                    outcome,
+                    ..interaction
+                });
+            }
+        });
+    }
+}
+
+/// Notification shown on `platform` with this native ID, preferring the newest live one
+fn notification_for_native_id(
+    state: &DashMap<NotificationId, NotificationState>,
+    platform: Platform,
+    native_id: &str,
+) -> Option<NotificationId> {
+    state
+        .iter()
+        .filter(|entry| {
+            entry
+                .lifecycle
+                .platform_states
+                .get(&platform)
+                .is_some_and(|platform_state| platform_state.native_id.as_deref() == Some(native_id))
+        })
+        .max_by_key(|entry| (!entry.lifecycle.state.is_terminal(), entry.identity.created_at))
+        .map(|entry| *entry.key())
+}
+
+/// Background worker mapping backend interactions to notifications
+async fn interaction_listener(
+    router: Arc<InteractionRouter>,
+    mut interactions: tokio::sync::broadcast::Receiver<PlatformInteraction>,
+    mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            received = interactions.recv() => {
+                let interaction = match received {
+                    Ok(interaction) => interaction,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        ::tracing::warn!("Dropped {} notification interactions", skipped);
+                        continue;
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                };
+
+                if interaction.kind == PlatformInteractionKind::ServerChanged {
+                    router.platform_manager.invalidate_capabilities(interaction.platform).await;
+                    continue;
+                }
+                if let Some(notification_id) = notification_for_native_id(
+                    &router.state,
+                    interaction.platform,
+                    &interaction.native_id,
+                ) {
+                    router.record(InteractionEvent {
+                        notification_id,
+                        platform: interaction.platform,
+                        kind: interaction.kind,
+                    });
+                }
+            }
+            _ = shutdown_rx.recv() => break,
+        }
+    }
+}
+
+/// Remove a delivered notification from every platform it was shown on
+async fn withdraw_notification(
+    state: &DashMap<NotificationId, NotificationState>,
+    platform_manager: &PlatformManager,
+    notification_id: NotificationId,
+) {
+    // Collect native IDs first so no map entry is locked across an await
+    let (app, native_ids): (AppIdentity, Vec<(Platform, String)>) = match state.get(&notification_id) {
+        Some(entry) => (
+            entry.identity.app.clone().unwrap_or_default(),
+            entry
+                .lifecycle
+                .platform_states
+                .iter()
+                .filter_map(|(platform, platform_state)| {
+                    platform_state.native_id.clone().map(|native_id| (*platform, native_id))
+                })
+                .collect(),
+        ),
+        None => return,
+    };
+
+    for (platform, native_id) in native_ids {
+        let Some(backend) = platform_manager.backend(platform) else {
+            continue;
+        };
+        let result = backend.cancel_notification(&native_id, &app).await;
+        if let Err(e) = &result {
+            ::tracing::warn!(
+                "Failed to withdraw notification {} on {:?}: {}",
+                notification_id, platform, e
+            );
+        }
+        record_close(state, notification_id, platform, result);
     }
 }
 
@@ -325,6 +1098,7 @@ impl Default for NotificationManager {
 /// over notifications without blocking other workers or status queries.
 async fn lifecycle_monitor(
     state: Arc<DashMap<NotificationId, NotificationState>>,
+    platform_manager: Arc<PlatformManager>,
     mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
 ) {
     let mut interval = tokio::time::interval(Duration::from_millis(100));
@@ -339,8 +1113,13 @@ async fn lifecycle_monitor(
                     // Update lifecycle timing
                     notification_state.lifecycle.update_timing();
 
-                    // Check for expired notifications
-                    if notification_state.lifecycle.is_expired() {
+                    // Check for expired notifications, and ones nobody acted on in time
+                    let awaits_interaction = !notification_state.content.interactions.actions.is_empty()
+                        || !notification_state.content.interactions.inputs.is_empty()
+                        || !notification_state.content.interactions.quick_replies.is_empty();
+                    if notification_state.lifecycle.is_expired()
+                        || (awaits_interaction && notification_state.lifecycle.interaction_timed_out())
+                    {
                         let _ = notification_state.lifecycle.transition_to(
                             crate::components::lifecycle::NotificationState::Expired,
                             crate::components::lifecycle::TransitionReason::Expiration,
@@ -348,18 +1127,198 @@ async fn lifecycle_monitor(
                         );
                     }
 
-                    // Handle retry logic
-                    if notification_state.lifecycle.should_retry() {
+                    // Queue failed notifications again once their backoff delay has passed
+                    if notification_state.lifecycle.retry_due() {
                         let delay = notification_state.lifecycle.next_retry_delay();
                         notification_state.lifecycle.schedule_retry(delay);
+                        let _ = notification_state.lifecycle.transition_to(
+                            crate::components::lifecycle::NotificationState::Queued,
+                            crate::components::lifecycle::TransitionReason::Retry,
+                            None,
+                        );
                     }
                 }
+
+                close_expired(&state, &platform_manager);
             }
             _ = shutdown_rx.recv() => break,
         }
     }
 }
 
+/// Close expired notifications that are still shown, in the background
+///
+/// Native IDs a newer notification took over, by replacing the expired one in
+/// place, are left alone so the replacement stays up.
+fn close_expired(
+    state: &Arc<DashMap<NotificationId, NotificationState>>,
+    platform_manager: &Arc<PlatformManager>,
+) {
+    // Collected first; nothing below may run while the iteration holds entries
+    let mut shown: Vec<(NotificationId, AppIdentity, Platform, String)> = Vec::new();
+    for entry in state.iter() {
+        let lifecycle = &entry.lifecycle;
+        if lifecycle.state != crate::components::lifecycle::NotificationState::Expired
+            || !lifecycle.expiration.cleanup_on_expiry
+        {
+            continue;
+        }
+        for (platform, platform_state) in &lifecycle.platform_states {
+            // A close that failed before is not retried
+            if platform_state.status == crate::components::lifecycle::PlatformDeliveryStatus::Delivered
+                && platform_state.error_details.is_none()
+                && let Some(native_id) = &platform_state.native_id
+            {
+                shown.push((
+                    *entry.key(),
+                    entry.identity.app.clone().unwrap_or_default(),
+                    *platform,
+                    native_id.clone(),
+                ));
+            }
+        }
+    }
+    shown.retain(|(notification_id, _, platform, native_id)| {
+        notification_for_native_id(state, *platform, native_id) == Some(*notification_id)
+    });
+
+    for (notification_id, app, platform, native_id) in shown {
+        if let Some(mut entry) = state.get_mut(&notification_id)
+            && let Some(platform_state) = entry.lifecycle.platform_states.get_mut(&platform)
+        {
+            platform_state.status = crate::components::lifecycle::PlatformDeliveryStatus::InProgress;
+        }
+
+        let state = Arc::clone(state);
+        let platform_manager = Arc::clone(platform_manager);
+        tokio::spawn(async move {
+            let result = match platform_manager.backend(platform) {
+                Some(backend) => backend.cancel_notification(&native_id, &app).await,
+                None => Ok(()),
+            };
+            record_close(&state, notification_id, platform, result);
+        });
+    }
+}
+
+/// Whether the delivery worker still has to deliver a notification in this state
+fn awaits_delivery(state: &crate::components::lifecycle::NotificationState) -> bool {
+    use crate::components::lifecycle::NotificationState as LifecycleState;
+    matches!(
+        state,
+        LifecycleState::Created
+            | LifecycleState::Validating
+            | LifecycleState::PlatformRouting
+            | LifecycleState::Queued
+            | LifecycleState::Delivering
+    )
+}
+
+/// Move notifications that can't finish once the workers stop to `Cancelled`
+///
+/// Notifications still waiting for delivery or for a scheduled retry are
+/// cancelled either way; `Abort` cancels every other unfinished one too.
+fn cancel_unfinished(
+    state: &DashMap<NotificationId, NotificationState>,
+    policy: ShutdownPolicy,
+) -> Vec<NotificationId> {
+    let unfinished: Vec<NotificationId> = state
+        .iter()
+        .filter(|entry| {
+            let lifecycle = &entry.lifecycle;
+            match policy {
+                ShutdownPolicy::Drain => {
+                    awaits_delivery(&lifecycle.state) || lifecycle.should_retry()
+                }
+                ShutdownPolicy::Abort => !lifecycle.state.is_terminal(),
+            }
+        })
+        .map(|entry| *entry.key())
+        .collect();
+
+    unfinished
+        .into_iter()
+        .filter(|id| {
+            let Some(mut entry) = state.get_mut(id) else {
+                return false;
+            };
+            let correlation_id = entry.identity.correlation_id.clone();
+            entry
+                .lifecycle
+                .transition_to(
+                    crate::components::lifecycle::NotificationState::Cancelled,
+                    crate::components::lifecycle::TransitionReason::Cancellation,
+                    Some(correlation_id),
+                )
+                .is_ok()
+        })
+        .collect()
+}
+
+/// Native IDs the given notifications are still shown under
+///
+/// Native IDs a newer, unfinished notification took over are left out so the
+/// newer one stays up.
+fn shown_by(
+    state: &DashMap<NotificationId, NotificationState>,
+    notification_ids: &[NotificationId],
+) -> Vec<(NotificationId, AppIdentity, Platform, String)> {
+    let mut shown = Vec::new();
+    for id in notification_ids {
+        let Some(entry) = state.get(id) else {
+            continue;
+        };
+        for (platform, platform_state) in &entry.lifecycle.platform_states {
+            if platform_state.status == crate::components::lifecycle::PlatformDeliveryStatus::Delivered
+                && let Some(native_id) = &platform_state.native_id
+            {
+                shown.push((
+                    *id,
+                    entry.identity.app.clone().unwrap_or_default(),
+                    *platform,
+                    native_id.clone(),
+                ));
+            }
+        }
+    }
+    shown.retain(|(notification_id, _, platform, native_id)| {
+        notification_for_native_id(state, *platform, native_id) == Some(*notification_id)
+    });
+    shown
+}
+
+/// Record in `platform_states` whether closing a notification on `platform` worked
+fn record_close(
+    state: &DashMap<NotificationId, NotificationState>,
+    notification_id: NotificationId,
+    platform: Platform,
+    result: NotificationResult<()>,
+) {
+    let Some(mut entry) = state.get_mut(&notification_id) else {
+        return;
+    };
+    let Some(platform_state) = entry.lifecycle.platform_states.get_mut(&platform) else {
+        return;
+    };
+    platform_state.last_attempt = Some(std::time::Instant::now());
+    match result {
+        Ok(()) => {
+            platform_state.status = crate::components::lifecycle::PlatformDeliveryStatus::Cancelled;
+            platform_state.error_details = None;
+        }
+        Err(e) => {
+            // Still shown as far as anyone can tell
+            platform_state.status = crate::components::lifecycle::PlatformDeliveryStatus::Delivered;
+            platform_state.error_details = Some(crate::components::lifecycle::PlatformError {
+                error_code: e.error_code(),
+                error_message: format!("Failed to close notification: {}", e),
+                retry_after: None,
+                is_permanent: !e.is_retryable(),
+            });
+        }
+    }
+}
+
 /// Delivery job collected during read lock phase
 struct DeliveryJob {
     notification_id: NotificationId,
@@ -380,7 +1339,7 @@ enum DeliveryResult {
     Failure {
         notification_id: NotificationId,
         platform: Platform,
-        error: String,
+        error: NotificationError,
         correlation_id: CorrelationId,
         retry_count: u32,
     },
@@ -388,6 +1347,8 @@ enum DeliveryResult {
         notification_id: NotificationId,
         platform: Platform,
         correlation_id: CorrelationId,
+        /// Why the request failed, `None` when the user declined
+        error: Option<NotificationError>,
     },
 }
 
@@ -405,7 +1366,7 @@ enum DeliveryResult {
 /// during network I/O which can take 3-5+ seconds per delivery.
 async fn delivery_worker(
     state: Arc<DashMap<NotificationId, NotificationState>>,
-    platform_backends: Arc<HashMap<Platform, Box<dyn PlatformBackend>>>,
+    platform_manager: Arc<PlatformManager>,
     mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
 ) {
     let mut interval = tokio::time::interval(Duration::from_millis(50));
@@ -413,8 +1374,11 @@ async fn delivery_worker(
     loop {
         tokio::select! {
             _ = interval.tick() => {
+                let shown = shown_native_ids(&state);
+                let native_ids = native_ids(&state);
+
                 // PHASE 1: Collect delivery jobs using DashMap iteration (lock-free)
-                let delivery_jobs: Vec<DeliveryJob> = state
+                let mut delivery_jobs: Vec<DeliveryJob> = state
                     .iter()
                     .filter(|entry| entry.value().lifecycle.state == crate::components::lifecycle::NotificationState::Queued)
                     .flat_map(|entry| {
@@ -436,11 +1400,30 @@ async fn delivery_worker(
                             .map(|platform| {
                                 let is_authorized = notification_state.platform_integration.is_authorized(*platform);
                                 let request = if is_authorized {
+                                    let mut options = crate::components::platform::DeliveryOptions::default();
+                                    if let Some(sound) = notification_state.platform_integration.custom_sound(*platform) {
+                                        options.platform_specific.insert(
+                                            crate::components::CUSTOM_SOUND_OPTION.to_string(),
+                                            sound.to_string(),
+                                        );
+                                    }
+                                    if let Some(key) = notification_state.content.dedup_key() {
+                                        options.replace_id = shown
+                                            .get(&(key.to_string(), *platform))
+                                            .filter(|shown| shown.id != id)
+                                            .map(|shown| shown.native_id.clone());
+                                    }
+                                    if let Some(replaced) = notification_state.content.replaces()
+                                        && let Some(native_id) = native_ids.get(&(replaced.to_string(), *platform))
+                                    {
+                                        options.replace_id = Some(native_id.clone());
+                                    }
                                     Some(crate::components::platform::NotificationRequest {
                                         notification_id: notification_state.identity.id.to_string(),
                                         content: notification_state.content.clone(),
-                                        options: crate::components::platform::DeliveryOptions::default(),
+                                        options,
                                         correlation_id: notification_state.identity.correlation_id.to_string(),
+                                        app: notification_state.identity.app.clone().unwrap_or_default(),
                                     })
                                 } else {
                                     None
@@ -470,13 +1453,19 @@ async fn delivery_worker(
                         );
                     }
                 }
+                // Cancelled since the jobs were collected
+                delivery_jobs.retain(|job| {
+                    state
+                        .get(&job.notification_id)
+                        .is_some_and(|entry| !entry.lifecycle.state.is_terminal())
+                });
 
                 // PHASE 3: Perform deliveries WITHOUT holding any lock (can take seconds)
                 let mut delivery_results: Vec<DeliveryResult> = Vec::new();
 
                 for job in delivery_jobs {
                     if !job.is_authorized
-                        && let Some(backend) = platform_backends.get(&job.platform)
+                        && let Some(backend) = platform_manager.backend(job.platform)
                     {
                         match backend.request_authorization().await {
                             Ok(true) => {
@@ -492,18 +1481,19 @@ async fn delivery_worker(
                                 }
                                 // Continue with delivery (don't skip!)
                             }
-                            Ok(false) | Err(_) => {
+                            result => {
                                 delivery_results.push(DeliveryResult::Unauthorized {
                                     notification_id: job.notification_id,
                                     platform: job.platform,
                                     correlation_id: job.correlation_id,
+                                    error: result.err(),
                                 });
                                 continue;
                             }
                         }
                     }
 
-                    if let Some(backend) = platform_backends.get(&job.platform)
+                    if let Some(backend) = platform_manager.backend(job.platform)
                         && let Some(request) = job.request
                     {
                         // ACTUALLY DELIVER TO PLATFORM (no lock held!)
@@ -526,7 +1516,7 @@ async fn delivery_worker(
                                 delivery_results.push(DeliveryResult::Failure {
                                     notification_id: job.notification_id,
                                     platform: job.platform,
-                                    error: error.to_string(),
+                                    error,
                                     correlation_id: job.correlation_id,
                                     retry_count,
                                 });
@@ -536,8 +1526,15 @@ async fn delivery_worker(
                 }
 
                 // PHASE 4: Update state with results (per-entry locking via DashMap)
+                let mut cancelled_meanwhile = Vec::new();
                 for result in delivery_results {
                     if let Some(mut notification_state) = state.get_mut(&result.notification_id()) {
+                        if matches!(result, DeliveryResult::Success { .. })
+                            && notification_state.lifecycle.state
+                                == crate::components::lifecycle::NotificationState::Cancelled
+                        {
+                            cancelled_meanwhile.push(result.notification_id());
+                        }
                         let correlation_id = match &result {
                             DeliveryResult::Success { correlation_id, .. } => correlation_id.clone(),
                             DeliveryResult::Failure { correlation_id, .. } => correlation_id.clone(),
@@ -550,7 +1547,7 @@ async fn delivery_worker(
                                 let platform_state = crate::components::lifecycle::PlatformDeliveryState {
                                     platform: receipt.platform,
                                     status: crate::components::lifecycle::PlatformDeliveryStatus::Delivered,
-                                    native_id: Some(receipt.native_id),
+                                    native_id: Some(receipt.native_id.clone()),
                                     attempt_count: 1,
                                     last_attempt: Some(std::time::Instant::now()),
                                     delivery_latency: Some(
@@ -560,6 +1557,7 @@ async fn delivery_worker(
                                             .unwrap_or_default(),
                                     ),
                                     error_details: None,
+                                    receipt: Some(receipt),
                                 };
                                 notification_state.lifecycle.update_platform_state(platform, platform_state);
                             }
@@ -567,21 +1565,22 @@ async fn delivery_worker(
                                 // Update platform state with real error
                                 let platform_state = crate::components::lifecycle::PlatformDeliveryState {
                                     platform,
-                                    status: crate::components::lifecycle::PlatformDeliveryStatus::Failed(error.clone()),
+                                    status: crate::components::lifecycle::PlatformDeliveryStatus::Failed(error.to_string()),
                                     native_id: None,
                                     attempt_count: retry_count + 1,
                                     last_attempt: Some(std::time::Instant::now()),
                                     delivery_latency: None,
                                     error_details: Some(crate::components::lifecycle::PlatformError {
-                                        error_code: None,
-                                        error_message: error,
+                                        error_code: error.error_code(),
+                                        error_message: error.to_string(),
                                         retry_after: None,
-                                        is_permanent: false,
+                                        is_permanent: !error.is_retryable(),
                                     }),
+                                    receipt: None,
                                 };
                                 notification_state.lifecycle.update_platform_state(platform, platform_state);
                             }
-                            DeliveryResult::Unauthorized { platform, .. } => {
+                            DeliveryResult::Unauthorized { platform, error, .. } => {
                                 // Update platform state with authorization error
                                 let platform_state = crate::components::lifecycle::PlatformDeliveryState {
                                     platform,
@@ -591,11 +1590,15 @@ async fn delivery_worker(
                                     last_attempt: Some(std::time::Instant::now()),
                                     delivery_latency: None,
                                     error_details: Some(crate::components::lifecycle::PlatformError {
-                                        error_code: None,
-                                        error_message: format!("Authorization required for platform: {}", platform.name()),
+                                        error_code: error.as_ref().and_then(NotificationError::error_code),
+                                        error_message: match &error {
+                                            Some(e) => format!("Authorization required for platform: {}: {}", platform.name(), e),
+                                            None => format!("Authorization required for platform: {}", platform.name()),
+                                        },
                                         retry_after: None,
-                                        is_permanent: true,
+                                        is_permanent: error.as_ref().is_none_or(|e| !e.is_retryable()),
                                     }),
+                                    receipt: None,
                                 };
                                 notification_state.lifecycle.update_platform_state(platform, platform_state);
                             }
@@ -639,10 +1642,25 @@ async fn delivery_worker(
                                 }
                             }
 
-                            if !platform_errors.is_empty() {
+                            if !platform_errors.is_empty()
+                                && !matches!(
+                                    notification_state.lifecycle.state,
+                                    crate::components::lifecycle::NotificationState::Failed(_)
+                                )
+                            {
+                                let message = format!("Delivery failed for {} platform(s)", platform_errors.len());
+                                // Counted before the transition, so watchers see whether a retry follows
+                                notification_state.lifecycle.record_delivery_attempt(
+                                    crate::components::lifecycle::DeliveryAttemptResult::Failure(
+                                        NotificationError::DeliveryFailed {
+                                            platform_errors: platform_errors.clone(),
+                                            message: message.clone(),
+                                        },
+                                    ),
+                                );
                                 let error_details = crate::components::lifecycle::ErrorDetails {
                                     error_type: crate::components::lifecycle::ErrorType::PlatformError,
-                                    message: format!("Delivery failed for {} platform(s)", platform_errors.len()),
+                                    message,
                                     retry_count: max_retry_count,
                                     last_attempt: Some(std::time::SystemTime::now()),
                                     platform_errors,
@@ -658,12 +1676,77 @@ async fn delivery_worker(
                         // else: some platforms are still pending/in-progress, keep current state
                     }
                 }
+
+                // Shown after being cancelled mid-delivery: take them down again
+                for notification_id in cancelled_meanwhile {
+                    withdraw_notification(&state, &platform_manager, notification_id).await;
+                }
             }
             _ = shutdown_rx.recv() => break,
         }
     }
 }
 
+/// Newest delivered notification for a dedup key on one platform
+struct ShownNotification {
+    id: NotificationId,
+    created_at: crate::components::time_wrapper::DefaultableInstant,
+    native_id: String,
+}
+
+/// Native ID of every notification shown so far, by notification ID and platform
+fn native_ids(
+    state: &DashMap<NotificationId, NotificationState>,
+) -> std::collections::HashMap<(String, Platform), String> {
+    let mut native_ids = std::collections::HashMap::new();
+    for entry in state.iter() {
+        for (platform, platform_state) in &entry.lifecycle.platform_states {
+            if let Some(native_id) = &platform_state.native_id {
+                native_ids.insert((entry.key().to_string(), *platform), native_id.clone());
+            }
+        }
+    }
+    native_ids
+}
+
+/// Newest delivered notification per dedup key and platform
+fn shown_native_ids(
+    state: &DashMap<NotificationId, NotificationState>,
+) -> std::collections::HashMap<(String, Platform), ShownNotification> {
+    let mut shown: std::collections::HashMap<(String, Platform), ShownNotification> =
+        std::collections::HashMap::new();
+
+    for entry in state.iter() {
+        let notification_state = entry.value();
+        let Some(key) = notification_state.content.dedup_key() else {
+            continue;
+        };
+        if notification_state.lifecycle.state.is_terminal() {
+            continue;
+        }
+
+        for (platform, platform_state) in &notification_state.lifecycle.platform_states {
+            let Some(native_id) = &platform_state.native_id else {
+                continue;
+            };
+            let candidate = ShownNotification {
+                id: *entry.key(),
+                created_at: notification_state.identity.created_at,
+                native_id: native_id.clone(),
+            };
+            let slot = (key.to_string(), *platform);
+            if shown
+                .get(&slot)
+                .is_none_or(|existing| existing.created_at < candidate.created_at)
+            {
+                shown.insert(slot, candidate);
+            }
+        }
+    }
+
+    shown
+}
+
 impl DeliveryResult {
     fn notification_id(&self) -> NotificationId {
         match self {
@@ -742,6 +1825,9 @@ pub struct NotificationBuilder {
     platform_integration: Option<PlatformIntegration>,
     lifecycle: Option<NotificationLifecycle>,
     analytics: Option<NotificationAnalytics>,
+    truncation: Option<TruncationPolicy>,
+    app: Option<AppIdentity>,
+    creator_context: Option<CreatorContext>,
 }
 
 impl NotificationBuilder {
@@ -752,6 +1838,9 @@ impl NotificationBuilder {
             platform_integration: None,
             lifecycle: None,
             analytics: None,
+            truncation: None,
+            app: None,
+            creator_context: None,
         }
     }
 
@@ -782,7 +1871,10 @@ impl NotificationBuilder {
     }
 
     pub fn with_platforms(mut self, platforms: Vec<Platform>) -> Self {
-        self.platform_integration = Some(PlatformIntegration::new(platforms));
+        match &mut self.platform_integration {
+            Some(integration) => integration.target_platforms = platforms,
+            None => self.platform_integration = Some(PlatformIntegration::new(platforms)),
+        }
         self
     }
 
@@ -808,13 +1900,81 @@ impl NotificationBuilder {
         self
     }
 
+    pub fn with_action(mut self, action: NotificationAction) -> Self {
+        if let Some(ref mut content) = self.content {
+            content.interactions.actions.push(action);
+        } else {
+            let mut content = NotificationContent::new("", RichText::plain(""));
+            content.interactions.actions.push(action);
+            self.content = Some(content);
+        }
+        self
+    }
+
+    /// Replace the last shown notification with the same key instead of stacking a new one
+    ///
+    /// The key is stored in the content's `custom_data` under [`DEDUP_KEY`]; the
+    /// manager passes the earlier notification's native ID as `replace_id`.
+    pub fn with_dedup_key(mut self, key: impl Into<String>) -> Self {
+        let content = self
+            .content
+            .take()
+            .unwrap_or_else(|| NotificationContent::new("", RichText::plain("")));
+        self.content = Some(content.with_dedup_key(key));
+        self
+    }
+
+    /// Show the notification as coming from `app` instead of the manager's identity
+    pub fn with_app_identity(mut self, app: AppIdentity) -> Self {
+        self.app = Some(app);
+        self
+    }
+
+    /// Record `creator_context` as the creator instead of the app the notification is shown as
+    pub fn with_creator_context(mut self, creator_context: CreatorContext) -> Self {
+        self.creator_context = Some(creator_context);
+        self
+    }
+
+    /// Truncate an over-long title or body instead of failing the build
+    ///
+    /// Truncated fields are recorded in the content's `custom_data` under
+    /// [`TRUNCATED_TITLE_KEY`] and [`TRUNCATED_BODY_KEY`] with their original length.
+    pub fn with_truncation(mut self, policy: TruncationPolicy) -> Self {
+        self.truncation = Some(policy);
+        self
+    }
+
+    /// How features the target platforms lack are degraded, and which must not be
+    ///
+    /// Fallbacks are derived again from the negotiated capabilities on send;
+    /// critical features and substitutions are kept.
+    pub fn with_degradation_strategy(mut self, strategy: DegradationStrategy) -> Self {
+        self.platform_integration
+            .get_or_insert_with(|| PlatformIntegration::new(default_target_platforms()))
+            .degradation_strategy = strategy;
+        self
+    }
+
+    /// When the notification expires; the manager then closes it wherever it is shown
+    /// unless `cleanup_on_expiry` is off
+    pub fn with_expiration(mut self, policy: ExpirationPolicy) -> Self {
+        self.lifecycle.get_or_insert_with(NotificationLifecycle::new).expiration = policy;
+        self
+    }
+
     /// Build the notification with validation
     /// 
     /// Returns an error if:
     /// - Content is missing or invalid
     /// - Title is empty
     /// - No target platforms are specified
-    /// - Content exceeds platform-specific limits
+    /// - Content exceeds platform-specific limits and no truncation policy is set
+    ///
+    /// Limits come from the capabilities already negotiated on the platform
+    /// integration. Lengths are not checked for platforms without negotiated
+    /// capabilities; `send` checks them against what the backends report, or use
+    /// [`Self::build_negotiated`] to check them here.
     pub fn build(self) -> Result<Notification, NotificationBuildError> {
         // Validate content is present
         let mut content = self.content.ok_or(NotificationBuildError::MissingContent)?;
@@ -825,19 +1985,24 @@ impl NotificationBuilder {
         }
         
         // Get target platforms (use provided or default to all desktop platforms)
-        let platforms = self.platform_integration
-            .as_ref()
-            .map(|p| p.target_platforms.clone())
-            .unwrap_or_else(|| vec![Platform::MacOS, Platform::Windows, Platform::Linux]);
+        let platform_integration = self
+            .platform_integration
+            .unwrap_or_else(|| PlatformIntegration::new(default_target_platforms()));
         
         // Validate platforms list is not empty
-        if platforms.is_empty() {
+        if platform_integration.target_platforms.is_empty() {
             return Err(NotificationBuildError::NoPlatforms);
         }
         
+        // Shorten content to the strictest limit across target platforms if opted in
+        if let Some(policy) = &self.truncation {
+            let targets = &platform_integration.target_platforms;
+            truncate_to_limits(policy, &mut content, &platform_integration, targets);
+        }
+        
         // Validate content against platform limits for each target platform
-        for platform in &platforms {
-            let limits = platform.default_capabilities().get_limits();
+        for platform in &platform_integration.target_platforms {
+            let limits = validation_limits(&platform_integration, *platform);
             
             // Validate title length for this platform
             if let Some(&max_title) = limits.get("max_title_length")
@@ -854,23 +2019,98 @@ impl NotificationBuilder {
             content.validate(&limits)?;
         }
         
-        let session_id = SessionId::generate();
-        let creator_context = CreatorContext::new("native-notifications");
+        let mut identity = self.identity.unwrap_or_default();
+        if let Some(app) = self.app {
+            identity = identity.with_app(app);
+        }
+        if let Some(creator_context) = self.creator_context {
+            identity.creator_context = Some(creator_context);
+        }
 
         Ok(Notification {
-            identity: self
-                .identity
-                .unwrap_or_else(|| NotificationIdentity::new(session_id.clone(), creator_context)),
+            identity,
             content,
-            platform_integration: self.platform_integration.unwrap_or_else(|| {
-                PlatformIntegration::new(platforms)
-            }),
+            platform_integration,
             lifecycle: self.lifecycle.unwrap_or_default(),
             analytics: self.analytics.unwrap_or_else(|| {
                 NotificationAnalytics::new(NotificationId::generate(), CorrelationId::generate())
             }),
+            truncation: self.truncation,
         })
     }
+
+    /// Build the notification after negotiating capabilities with the manager's backends
+    ///
+    /// Validation then uses the limits each backend reported (cached by the
+    /// manager's `PlatformManager`) instead of the static per-platform defaults.
+    pub async fn build_negotiated(
+        mut self,
+        manager: &NotificationManager,
+    ) -> Result<Notification, NotificationBuildError> {
+        let mut platform_integration = self
+            .platform_integration
+            .take()
+            .unwrap_or_else(|| PlatformIntegration::new(default_target_platforms()));
+        platform_integration
+            .negotiate_capabilities(manager.platform_manager())
+            .await?;
+        self.platform_integration = Some(platform_integration);
+        self.build()
+    }
+}
+
+/// Limits to validate against: negotiated capabilities, else none
+fn validation_limits(
+    platform_integration: &PlatformIntegration,
+    platform: Platform,
+) -> std::collections::HashMap<String, usize> {
+    platform_integration
+        .platform_capabilities
+        .get(&platform)
+        .map(|caps| caps.get_limits())
+        .unwrap_or_default()
+}
+
+/// Truncate title and body to the smallest limits among `platforms`
+///
+/// The original lengths recorded by an earlier truncation are kept.
+fn truncate_to_limits(
+    policy: &TruncationPolicy,
+    content: &mut NotificationContent,
+    platform_integration: &PlatformIntegration,
+    platforms: &[Platform],
+) {
+    let strictest = |key: &str| {
+        platforms
+            .iter()
+            .filter_map(|platform| validation_limits(platform_integration, *platform).get(key).copied())
+            .min()
+    };
+
+    if let Some(max_title) = strictest("max_title_length")
+        && let Some(title) = policy.truncate_plain(&content.title, max_title)
+    {
+        content
+            .custom_data
+            .entry(TRUNCATED_TITLE_KEY.to_string())
+            .or_insert_with(|| content.title.len().to_string());
+        content.title = title;
+    }
+
+    if let Some(max_body) = strictest("max_body_length")
+        && let Some(body) = policy.truncate_rich_text(&content.body, max_body)
+    {
+        content
+            .custom_data
+            .entry(TRUNCATED_BODY_KEY.to_string())
+            .or_insert_with(|| content.body.to_plain_text().len().to_string());
+        content.body = body;
+    }
+}
+
+/// Platforms targeted when the builder is not given an explicit list
+fn default_target_platforms() -> Vec<Platform> {
+    vec![Platform::MacOS, Platform::Windows, Platform::Linux]
 }
 
 impl Default for NotificationBuilder {
@@ -0,0 +1,315 @@
+//! Signed, retried action webhooks
+//!
+//! Actions whose payload has a `callback_url` POST a [`WebhookEnvelope`] there.
+//! The body is signed with HMAC-SHA256 under the configured secret
+//! ([`SIGNATURE_HEADER`]), carries the notification's trace as a W3C
+//! `traceparent` header, and is retried with the configured
+//! [`BackoffStrategy`]. Callbacks that still fail land in the
+//! [`DeadLetterStore`] for inspection or redelivery.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::components::{ErrorKind, ErrorSource};
+use crate::{
+    BackoffStrategy, CorrelationId, NotificationError, NotificationResult, TraceSpan, Url,
+};
+
+/// Header carrying `sha256=<hex HMAC of the body>`
+pub const SIGNATURE_HEADER: &str = "X-Notification-Signature";
+/// Header carrying the notification's correlation ID
+pub const CORRELATION_HEADER: &str = "X-Correlation-Id";
+/// W3C trace context header
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// Dead letters kept before the oldest is dropped
+const DEFAULT_DEAD_LETTER_CAPACITY: usize = 1000;
+
+static WEBHOOK_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+/// JSON body POSTed to an action's callback URL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookEnvelope {
+    pub notification_id: String,
+    pub correlation_id: String,
+    pub action_id: String,
+    /// Values of the notification's inputs submitted with the action
+    pub inputs: HashMap<String, String>,
+    /// Payload data of the action
+    pub data: HashMap<String, String>,
+    /// RFC 3339 time the action was invoked
+    pub timestamp: String,
+}
+
+impl WebhookEnvelope {
+    pub fn new(
+        notification_id: impl ToString,
+        correlation_id: &CorrelationId,
+        action_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            notification_id: notification_id.to_string(),
+            correlation_id: correlation_id.to_string(),
+            action_id: action_id.into(),
+            inputs: HashMap::new(),
+            data: HashMap::new(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Webhook signing and retry settings
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// HMAC-SHA256 key; unsigned when `None`
+    pub secret: Option<Vec<u8>>,
+    /// Attempts including the first one
+    pub max_attempts: u32,
+    pub backoff: BackoffStrategy,
+    pub request_timeout: Duration,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            max_attempts: 5,
+            backoff: BackoffStrategy::ExponentialWithJitter {
+                base_delay: Duration::from_millis(500),
+                max_delay: Duration::from_secs(30),
+                multiplier: 2.0,
+                jitter: 0.1,
+            },
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Callback that could not be delivered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub url: Url,
+    pub envelope: WebhookEnvelope,
+    /// `traceparent` sent with the attempts, reused on redelivery
+    pub traceparent: Option<String>,
+    pub attempts: u32,
+    pub last_error: String,
+    /// RFC 3339 time of the last attempt
+    pub failed_at: String,
+}
+
+/// Bounded in-memory store of undeliverable callbacks
+pub struct DeadLetterStore {
+    letters: parking_lot::Mutex<VecDeque<DeadLetter>>,
+    capacity: usize,
+}
+
+impl Default for DeadLetterStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEAD_LETTER_CAPACITY)
+    }
+}
+
+impl DeadLetterStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            letters: parking_lot::Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn push(&self, letter: DeadLetter) {
+        let mut letters = self.letters.lock();
+        if letters.len() >= self.capacity
+            && let Some(dropped) = letters.pop_front()
+        {
+            ::tracing::warn!(
+                "Dead-letter store full, dropping callback for action {}",
+                dropped.envelope.action_id
+            );
+        }
+        letters.push_back(letter);
+    }
+
+    /// Copy of the stored letters, oldest first
+    pub fn letters(&self) -> Vec<DeadLetter> {
+        self.letters.lock().iter().cloned().collect()
+    }
+
+    /// Remove and return all letters, oldest first
+    pub fn drain(&self) -> Vec<DeadLetter> {
+        self.letters.lock().drain(..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.lock().is_empty()
+    }
+}
+
+/// Sends action callbacks for a manager
+#[derive(Default)]
+pub struct Webhooks {
+    config: parking_lot::RwLock<WebhookConfig>,
+    dead_letters: DeadLetterStore,
+}
+
+impl Webhooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn config(&self) -> WebhookConfig {
+        self.config.read().clone()
+    }
+
+    pub fn set_config(&self, config: WebhookConfig) {
+        *self.config.write() = config;
+    }
+
+    /// Sign callbacks with HMAC-SHA256 under `secret`
+    pub fn set_secret(&self, secret: impl Into<Vec<u8>>) {
+        self.config.write().secret = Some(secret.into());
+    }
+
+    pub fn dead_letters(&self) -> &DeadLetterStore {
+        &self.dead_letters
+    }
+
+    /// POST `envelope` to `url`, retrying; undeliverable envelopes are dead-lettered
+    pub async fn deliver(
+        &self,
+        url: &Url,
+        envelope: WebhookEnvelope,
+        trace_span: Option<&TraceSpan>,
+    ) -> NotificationResult<()> {
+        let traceparent = trace_span.map(TraceSpan::traceparent);
+        self.deliver_with_trace(url, envelope, traceparent).await
+    }
+
+    /// Retry a dead letter; it is stored again if it still fails
+    pub async fn redeliver(&self, letter: DeadLetter) -> NotificationResult<()> {
+        self.deliver_with_trace(&letter.url, letter.envelope, letter.traceparent)
+            .await
+    }
+
+    async fn deliver_with_trace(
+        &self,
+        url: &Url,
+        envelope: WebhookEnvelope,
+        traceparent: Option<String>,
+    ) -> NotificationResult<()> {
+        let config = self.config();
+        let body = serde_json::to_vec(&envelope).map_err(|e| NotificationError::ValidationError {
+            field: "callback".to_string(),
+            message: format!("Failed to serialize callback envelope: {}", e),
+        })?;
+
+        let mut attempts = 0;
+        let error = loop {
+            attempts += 1;
+            let error = match post(url, &body, &envelope, traceparent.as_deref(), &config).await {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+            if !error.retryable || attempts >= config.max_attempts.max(1) {
+                break error;
+            }
+            ::tracing::debug!(
+                "Callback for action {} failed (attempt {}): {}",
+                envelope.action_id,
+                attempts,
+                error.message
+            );
+            tokio::time::sleep(config.backoff.calculate_delay(attempts - 1)).await;
+        };
+
+        ::tracing::warn!(
+            "Dead-lettering callback for action {} after {} attempts: {}",
+            envelope.action_id,
+            attempts,
+            error.message
+        );
+        self.dead_letters.push(DeadLetter {
+            url: url.clone(),
+            envelope,
+            traceparent,
+            attempts,
+            last_error: error.message.clone(),
+            failed_at: chrono::Utc::now().to_rfc3339(),
+        });
+        Err(NotificationError::ResourceError {
+            resource_type: "callback".to_string(),
+            resource_id: url.to_string(),
+            kind: error.kind,
+            message: error.message,
+            source: error.source,
+        })
+    }
+}
+
+struct AttemptError {
+    message: String,
+    kind: ErrorKind,
+    retryable: bool,
+    source: Option<ErrorSource>,
+}
+
+async fn post(
+    url: &Url,
+    body: &[u8],
+    envelope: &WebhookEnvelope,
+    traceparent: Option<&str>,
+    config: &WebhookConfig,
+) -> Result<(), AttemptError> {
+    let mut request = WEBHOOK_CLIENT
+        .post(url.clone())
+        .timeout(config.request_timeout)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(CORRELATION_HEADER, &envelope.correlation_id)
+        .body(body.to_vec());
+    if let Some(secret) = &config.secret {
+        request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign_webhook(secret, body)));
+    }
+    if let Some(traceparent) = traceparent {
+        request = request.header(TRACEPARENT_HEADER, traceparent);
+    }
+
+    let response = request.send().await.map_err(|e| AttemptError {
+        message: e.to_string(),
+        kind: crate::backends::reqwest_error_kind(&e),
+        retryable: true,
+        source: Some(e.into()),
+    })?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let kind = crate::backends::http_status_kind(status);
+    Err(AttemptError {
+        message: format!("Callback returned {}", status),
+        kind,
+        // Other client errors won't succeed on retry
+        retryable: kind.is_retryable(),
+        source: None,
+    })
+}
+
+/// Hex HMAC-SHA256 of `body` under `secret`, as sent in [`SIGNATURE_HEADER`]
+pub fn sign_webhook(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
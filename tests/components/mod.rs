@@ -1,8 +1,12 @@
 //! Component tests for kodegen-native-notify
 
 mod test_analytics;
+mod test_ansi;
+mod test_build_output;
 mod test_content;
 mod test_lifecycle;
 mod test_mod;
 mod test_platform;
+mod test_rich_text;
 mod test_tracing;
+mod test_truncation;
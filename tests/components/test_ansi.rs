@@ -0,0 +1,77 @@
+//! Tests for components/ansi.rs
+
+use kodegen_native_notify::{RichText, TruncationPolicy, strip_ansi};
+
+#[test]
+fn test_sgr_styles_map_to_pango() {
+    let text = RichText::ansi("\x1b[1mbold\x1b[0m \x1b[3mitalic\x1b[23m \x1b[4munder\x1b[24m plain");
+    assert_eq!(
+        text.to_pango_markup(),
+        "<b>bold</b> <i>italic</i> <u>under</u> plain"
+    );
+    assert_eq!(text.to_plain_text(), "bold italic under plain");
+}
+
+#[test]
+fn test_foreground_colors_map_to_pango_spans() {
+    // 16-color, bright, 256-color cube, 256-color gray ramp and truecolor
+    let text = RichText::ansi(
+        "\x1b[31mred\x1b[39m \x1b[92mgreen\x1b[0m \x1b[38;5;208morange\x1b[0m \
+         \x1b[38;5;244mgray\x1b[0m \x1b[38;2;18;52;86mtrue\x1b[0m",
+    );
+    assert_eq!(
+        text.to_pango_markup(),
+        "<span foreground=\"#cd0000\">red</span> \
+         <span foreground=\"#00ff00\">green</span> \
+         <span foreground=\"#ff8700\">orange</span> \
+         <span foreground=\"#808080\">gray</span> \
+         <span foreground=\"#123456\">true</span>"
+    );
+}
+
+#[test]
+fn test_color_and_weight_nest_and_background_is_ignored() {
+    let text = RichText::ansi("\x1b[1;33;44m<warn>\x1b[0m done");
+    assert_eq!(
+        text.to_pango_markup(),
+        "<span foreground=\"#cdcd00\"><b>&lt;warn&gt;</b></span> done"
+    );
+}
+
+#[test]
+fn test_cursor_movement_and_osc_sequences_are_dropped() {
+    let raw = "\x1b]0;window title\x07\x1b[2K\x1b[1A\
+        \x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\ text\x1b[?25l\n\
+        Compiling 10%\rCompiling 100%\n";
+
+    assert_eq!(strip_ansi(raw), "link text\nCompiling 100%\n");
+
+    let text = RichText::ansi(raw);
+    assert_eq!(text.to_plain_text(), "link text\nCompiling 100%\n");
+    assert_eq!(text.to_pango_markup(), "link text\nCompiling 100%");
+}
+
+#[test]
+fn test_ansi_truncation_keeps_styles_and_resets() {
+    let policy = TruncationPolicy::new()
+        .with_ellipsis("...")
+        .with_prefer_boundaries(false);
+    let text = RichText::ansi("\x1b[31merror\x1b[0m: something went badly wrong");
+
+    let truncated = policy
+        .truncate_rich_text(&text, 12)
+        .expect("text should be truncated");
+    assert!(truncated.to_plain_text().len() <= 12);
+    match truncated {
+        RichText::Ansi(ref body) => assert_eq!(body, "\x1b[31merror\x1b[0m: so...\x1b[0m"),
+        _ => panic!("Expected Ansi variant"),
+    }
+
+    let short = policy
+        .truncate_rich_text(&text, 6)
+        .expect("text should be truncated");
+    match short {
+        RichText::Ansi(ref body) => assert_eq!(body, "\x1b[31merr...\x1b[0m"),
+        _ => panic!("Expected Ansi variant"),
+    }
+}
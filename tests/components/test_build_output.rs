@@ -0,0 +1,248 @@
+//! Tests for components/build_output.rs
+
+use kodegen_native_notify::{
+    BuildOutputSummarizer, BuildTool, RichText, SourceLocation, TestFailure,
+};
+
+const CARGO_BUILD: &str = "\
+   Compiling kodegen-utils v0.5.0
+\x1b[1m\x1b[33mwarning\x1b[0m: unused variable: `x`
+ --> src/utils.rs:3:9
+  |
+3 |     let x = 1;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+warning: `kodegen-utils` (lib) generated 1 warning
+   Compiling kodegen v0.5.0
+error[E0308]: mismatched types
+  --> src/lib.rs:4:5
+   |
+4  |     \"five\"
+   |     ^^^^^^ expected `u32`, found `&str`
+
+error[E0425]: cannot find value `y` in this scope
+  --> src/main.rs:10:13
+   |
+10 |     let z = y;
+   |             ^ not found in this scope
+
+error: cannot find macro `foo` in this scope
+ --> src/main.rs:2:5
+
+Some errors have detailed explanations: E0308, E0425.
+error: could not compile `kodegen` (bin \"kodegen\") due to 3 previous errors";
+
+const CARGO_TEST: &str = "\
+running 3 tests
+test tests::parses ... ok
+test tests::adds ... FAILED
+test tests::old_style ... FAILED
+
+failures:
+
+---- tests::adds stdout ----
+
+thread 'tests::adds' panicked at src/lib.rs:10:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+---- tests::old_style stdout ----
+thread 'tests::old_style' panicked at 'boom', src/old.rs:7:5
+
+failures:
+    tests::adds
+    tests::old_style
+
+test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+error: test failed, to rerun pass `--lib`";
+
+const NPM_TSC: &str = "\
+> web@1.0.0 build
+> tsc
+
+src/app.ts(12,7): error TS2322: Type 'string' is not assignable to type 'number'.
+src/api.ts:3:1 - error TS2304: Cannot find name 'fetchJson'.
+
+Found 2 errors in 2 files.
+
+npm ERR! code ELIFECYCLE
+npm ERR! errno 2
+npm ERR! web@1.0.0 build: `tsc`
+npm ERR! Exit status 2
+npm ERR! A complete log of this run can be found in: /tmp/npm.log";
+
+const NPM_MISSING_SCRIPT: &str = "\
+npm error Missing script: \"buidl\"
+npm error
+npm error Did you mean this?
+npm error   npm run build # run the \"build\" package script
+npm error A complete log of this run can be found in: /tmp/npm.log";
+
+const PYTEST: &str = "\
+============================= test session starts ==============================
+collected 4 items
+
+tests/test_math.py .F.F                                                  [100%]
+
+=================================== FAILURES ===================================
+___________________________________ test_add ___________________________________
+
+    def test_add():
+>       assert add(1, 1) == 3
+E       assert 2 == 3
+E        +  where 2 = add(1, 1)
+
+tests/test_math.py:5: AssertionError
+______________________________ TestDiv.test_zero _______________________________
+
+self = <tests.test_math.TestDiv object at 0x7f>
+
+    def test_zero(self):
+>       div(1, 0)
+E       ZeroDivisionError: division by zero
+
+tests/test_math.py:14: ZeroDivisionError
+=========================== short test summary info ============================
+FAILED tests/test_math.py::test_add - assert 2 == 3
+FAILED tests/test_math.py::TestDiv::test_zero - ZeroDivisionError: division by zero
+========================= 2 failed, 2 passed in 0.05s ==========================";
+
+#[test]
+fn test_cargo_build_errors_and_counts() {
+    let summary = BuildOutputSummarizer::new().summarize(CARGO_BUILD);
+
+    assert_eq!(summary.tools, vec![BuildTool::Cargo]);
+    assert_eq!(summary.error_count, 3);
+    assert_eq!(summary.warning_count, 1);
+    assert_eq!(summary.counts(), "3 errors, 1 warning");
+
+    let first = &summary.errors[0];
+    assert_eq!(first.code.as_deref(), Some("E0308"));
+    assert_eq!(first.message, "mismatched types");
+    assert_eq!(
+        first.location,
+        Some(SourceLocation {
+            path: "src/lib.rs".to_string(),
+            line: 4,
+            column: Some(5),
+        })
+    );
+    assert_eq!(summary.errors[2].code, None);
+    assert_eq!(
+        summary.errors[2].location.as_ref().map(ToString::to_string).as_deref(),
+        Some("src/main.rs:2:5")
+    );
+
+    assert_eq!(
+        summary.to_plain_text(),
+        "3 errors, 1 warning\n\
+         error[E0308]: mismatched types (src/lib.rs:4:5)\n\
+         error[E0425]: cannot find value `y` in this scope (src/main.rs:10:13)\n\
+         error: cannot find macro `foo` in this scope (src/main.rs:2:5)"
+    );
+}
+
+#[test]
+fn test_error_limit_keeps_counting() {
+    let summary = BuildOutputSummarizer::new()
+        .with_max_errors(1)
+        .summarize(CARGO_BUILD);
+
+    assert_eq!(summary.errors.len(), 1);
+    assert_eq!(summary.error_count, 3);
+    assert!(summary.to_plain_text().ends_with("… and 2 more errors"));
+}
+
+#[test]
+fn test_cargo_test_failures() {
+    let summary = BuildOutputSummarizer::new().summarize(CARGO_TEST);
+
+    assert_eq!(summary.tools, vec![BuildTool::CargoTest]);
+    assert_eq!(summary.error_count, 0);
+    assert_eq!(summary.tests_failed, 2);
+    assert_eq!(summary.tests_passed, 1);
+    assert_eq!(summary.counts(), "2 tests failed");
+    assert_eq!(
+        summary.failed_tests,
+        vec![
+            TestFailure {
+                tool: BuildTool::CargoTest,
+                name: "tests::adds".to_string(),
+                message: Some("assertion `left == right` failed".to_string()),
+                location: Some(SourceLocation {
+                    path: "src/lib.rs".to_string(),
+                    line: 10,
+                    column: Some(9),
+                }),
+            },
+            TestFailure {
+                tool: BuildTool::CargoTest,
+                name: "tests::old_style".to_string(),
+                message: Some("boom".to_string()),
+                location: Some(SourceLocation {
+                    path: "src/old.rs".to_string(),
+                    line: 7,
+                    column: Some(5),
+                }),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_tsc_errors_through_npm() {
+    let summary = BuildOutputSummarizer::new().summarize(NPM_TSC);
+
+    // The tsc errors carry the information; npm's own lines are noise here
+    assert_eq!(summary.tools, vec![BuildTool::Tsc]);
+    assert_eq!(summary.error_count, 2);
+    assert_eq!(
+        summary.to_plain_text(),
+        "2 errors\n\
+         error[TS2322]: Type 'string' is not assignable to type 'number'. (src/app.ts:12:7)\n\
+         error[TS2304]: Cannot find name 'fetchJson'. (src/api.ts:3:1)"
+    );
+
+    let summary = BuildOutputSummarizer::new().summarize(NPM_MISSING_SCRIPT);
+    assert_eq!(summary.tools, vec![BuildTool::Npm]);
+    assert_eq!(summary.to_plain_text(), "1 error\nerror: Missing script: \"buidl\"");
+}
+
+#[test]
+fn test_pytest_failure_blocks() {
+    let summary = BuildOutputSummarizer::new().summarize(PYTEST);
+
+    assert_eq!(summary.tools, vec![BuildTool::Pytest]);
+    assert_eq!(summary.tests_failed, 2);
+    assert_eq!(summary.tests_passed, 2);
+    assert_eq!(
+        summary.to_plain_text(),
+        "2 tests failed\n\
+         FAILED tests/test_math.py::test_add: assert 2 == 3 (tests/test_math.py:5)\n\
+         FAILED tests/test_math.py::TestDiv::test_zero: ZeroDivisionError: division by zero \
+         (tests/test_math.py:14)"
+    );
+}
+
+#[test]
+fn test_rich_text_body_and_unrecognized_output() {
+    let summary = BuildOutputSummarizer::new()
+        .with_max_errors(1)
+        .summarize(CARGO_BUILD);
+    let RichText::Html(html) = summary.to_rich_text() else {
+        panic!("Expected Html variant");
+    };
+    assert_eq!(
+        html,
+        "<p><b>3 errors, 1 warning</b></p><ul>\
+         <li><b>error[E0308]</b>: mismatched types <code>src/lib.rs:4:5</code></li>\
+         <li>… and 2 more errors</li></ul>"
+    );
+
+    let summary = BuildOutputSummarizer::new().summarize("   Compiling foo v0.1.0\n    Finished");
+    assert!(summary.is_empty());
+    assert!(!summary.has_failures());
+    assert_eq!(summary.to_plain_text(), "");
+}
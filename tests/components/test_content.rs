@@ -68,10 +68,9 @@ fn test_html_to_plain_entity_decoding() {
     let html = RichText::html("a &amp; b");
     assert_eq!(html.to_plain_text(), "a & b");
 
-    // Note: &lt;tag&gt; decodes to <tag>, which is then treated as an HTML tag
-    // and stripped. To preserve literal angle brackets, use different encoding.
+    // Escaped angle brackets are text, not markup
     let html2 = RichText::html("&lt;tag&gt;");
-    assert_eq!(html2.to_plain_text(), "");
+    assert_eq!(html2.to_plain_text(), "<tag>");
 
     let html3 = RichText::html("&quot;quoted&quot;");
     assert_eq!(html3.to_plain_text(), "\"quoted\"");
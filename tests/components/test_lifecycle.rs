@@ -125,3 +125,55 @@ fn test_state_history_tracking() {
 
     assert!(lifecycle.state_history.len() >= 2);
 }
+
+#[test]
+fn test_permanent_platform_failures_are_not_retried() {
+    use kodegen_native_notify::Platform;
+    use kodegen_native_notify::components::lifecycle::{
+        PlatformDeliveryState, PlatformDeliveryStatus, PlatformError,
+    };
+
+    let failed_on = |lifecycle: &mut NotificationLifecycle, platform, is_permanent| {
+        lifecycle.update_platform_state(
+            platform,
+            PlatformDeliveryState {
+                platform,
+                status: PlatformDeliveryStatus::Failed("failed".to_string()),
+                native_id: None,
+                attempt_count: 1,
+                last_attempt: None,
+                delivery_latency: None,
+                error_details: Some(PlatformError {
+                    error_code: None,
+                    error_message: "failed".to_string(),
+                    retry_after: None,
+                    is_permanent,
+                }),
+                receipt: None,
+            },
+        );
+    };
+
+    let mut lifecycle = NotificationLifecycle::default();
+    let _ = lifecycle.transition_to(NotificationState::Validating, TransitionReason::Initial, None);
+    let _ = lifecycle.transition_to(
+        NotificationState::Failed(kodegen_native_notify::LifecycleErrorDetails {
+            error_type: kodegen_native_notify::LifecycleErrorType::PlatformError,
+            message: "Delivery failed".to_string(),
+            retry_count: 0,
+            last_attempt: None,
+            platform_errors: std::collections::HashMap::new(),
+        }),
+        TransitionReason::DeliveryFailed,
+        None,
+    );
+
+    failed_on(&mut lifecycle, Platform::Linux, true);
+    assert!(lifecycle.failed_permanently());
+    assert!(!lifecycle.should_retry());
+
+    // One platform that may recover is enough to try again
+    failed_on(&mut lifecycle, Platform::Windows, false);
+    assert!(!lifecycle.failed_permanently());
+    assert!(lifecycle.should_retry());
+}
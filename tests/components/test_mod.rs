@@ -9,6 +9,9 @@ use kodegen_native_notify::{
     ActionOptions,
     ActionIcon,
     TraceSpan,
+    ErrorKind,
+    NotificationError,
+    Platform,
 };
 
 #[test]
@@ -77,3 +80,54 @@ fn test_trace_span_creation() {
     assert_eq!(span.attributes.get("service"), Some(&"test".to_string()));
     assert_eq!(span.attributes.get("version"), Some(&"1.0.0".to_string()));
 }
+
+#[test]
+fn test_notification_error_classification_and_source() {
+    let io_error = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "bus went away");
+    let error = NotificationError::PlatformError {
+        platform: Platform::Linux,
+        kind: ErrorKind::Connection,
+        error_code: Some(1020),
+        message: "Failed to send notification".to_string(),
+        source: Some(io_error.into()),
+    };
+    assert_eq!(error.kind(), ErrorKind::Connection);
+    assert!(error.is_retryable());
+    assert_eq!(error.error_code(), Some(1020));
+    assert!(error.to_string().contains("Platform error on Linux"));
+    let source = std::error::Error::source(&error).expect("source is kept");
+    assert_eq!(source.to_string(), "bus went away");
+
+    let denied = NotificationError::AuthorizationError {
+        platform: Platform::MacOS,
+        required_permission: "notifications".to_string(),
+    };
+    assert_eq!(denied.kind(), ErrorKind::PermissionDenied);
+    assert!(!denied.is_retryable());
+    assert!(std::error::Error::source(&denied).is_none());
+
+    let missing = NotificationError::ResourceError {
+        resource_type: "image".to_string(),
+        resource_id: "/nowhere.png".to_string(),
+        kind: ErrorKind::NotFound,
+        message: "Image file does not exist".to_string(),
+        source: None,
+    };
+    assert!(!missing.is_retryable());
+    assert_eq!(missing.error_code(), None);
+
+    let failed = NotificationError::DeliveryFailed {
+        platform_errors: [
+            (Platform::Windows, "toast rejected".to_string()),
+            (Platform::Linux, "no daemon".to_string()),
+        ]
+        .into_iter()
+        .collect(),
+        message: "Notification could not be delivered".to_string(),
+    };
+    assert_eq!(
+        failed.to_string(),
+        "Delivery failed on Linux, Windows: Notification could not be delivered"
+    );
+    assert!(!failed.is_retryable());
+}
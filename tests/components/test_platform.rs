@@ -2,6 +2,7 @@
 
 use std::collections::HashMap;
 use kodegen_native_notify::{
+    AppIdentity,
     Platform,
     FeatureMatrix,
     AuthorizationState,
@@ -9,7 +10,73 @@ use kodegen_native_notify::{
     DegradationStrategy,
     ActionFallback,
     FeatureDegradation,
+    PlatformBackend,
+    PlatformCapabilities,
+    PlatformIntegration,
+    PlatformManager,
+    NotificationRequest,
+    NotificationUpdate,
+    NotificationResult,
+    DeliveryReceipt,
+    ActionChange,
+    ActionId,
+    ActionStyle,
+    ActivationType,
+    ImageData,
+    ImagePlacement,
+    MediaAttachment,
+    MediaChange,
+    NotificationAction,
+    NotificationContent,
+    RichText,
 };
+use std::future::Future;
+use std::pin::Pin;
+
+/// Backend stub that reports fixed capabilities
+struct StubBackend {
+    capabilities: PlatformCapabilities,
+}
+
+impl PlatformBackend for StubBackend {
+    fn negotiate_capabilities(
+        &self,
+    ) -> Pin<Box<dyn Future<Output = NotificationResult<PlatformCapabilities>> + Send + '_>> {
+        let capabilities = self.capabilities.clone();
+        Box::pin(async move { Ok(capabilities) })
+    }
+
+    fn deliver_notification(
+        &self,
+        request: &NotificationRequest,
+    ) -> Pin<Box<dyn Future<Output = NotificationResult<DeliveryReceipt>> + Send + '_>> {
+        let native_id = request.notification_id.clone();
+        Box::pin(async move { Ok(DeliveryReceipt::new(Platform::Linux, native_id)) })
+    }
+
+    fn update_notification(
+        &self,
+        _id: &str,
+        _update: &NotificationUpdate,
+        _app: &AppIdentity,
+    ) -> Pin<Box<dyn Future<Output = NotificationResult<()>> + Send + '_>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn cancel_notification(
+        &self,
+        _id: &str,
+        _app: &AppIdentity,
+    ) -> Pin<Box<dyn Future<Output = NotificationResult<()>> + Send + '_>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn request_authorization(
+        &self,
+    ) -> Pin<Box<dyn Future<Output = NotificationResult<bool>> + Send + '_>> {
+        Box::pin(async { Ok(true) })
+    }
+}
 
 #[test]
 fn test_platform_capabilities() {
@@ -79,3 +146,121 @@ fn test_degradation_strategy() {
         _ => panic!("Expected FeatureRemoved degradation"),
     }
 }
+
+#[tokio::test]
+async fn test_negotiated_capabilities_replace_defaults() {
+    let mut manager = PlatformManager::new();
+    manager.register_backend(
+        Platform::Linux,
+        Box::new(StubBackend {
+            capabilities: PlatformCapabilities {
+                max_title_length: None,
+                max_body_length: Some(64),
+                ..Platform::Linux.default_capabilities()
+            },
+        }),
+    );
+
+    let mut integration = PlatformIntegration::new(vec![Platform::Linux, Platform::Web]);
+    integration
+        .negotiate_capabilities(&manager)
+        .await
+        .expect("negotiation should succeed");
+
+    // Linux limits come from the backend, not the static table
+    let linux_limits = integration.get_platform_limits(Platform::Linux);
+    assert_eq!(linux_limits.get("max_title_length"), None);
+    assert_eq!(linux_limits.get("max_body_length"), Some(&64));
+
+    // Platforms without a backend aren't negotiated or validated against
+    assert!(integration.get_platform_limits(Platform::Web).is_empty());
+    assert!(!integration.platform_capabilities.contains_key(&Platform::Web));
+    assert!(manager.get_capabilities(Platform::Web).await.is_err());
+}
+
+fn update_test_action(id: &str, label: &str) -> NotificationAction {
+    NotificationAction {
+        id: ActionId::new(id),
+        label: label.to_string(),
+        icon: None,
+        style: ActionStyle::Default,
+        activation_type: ActivationType::Background,
+        url: None,
+        payload: None,
+        confirmation: None,
+    }
+}
+
+fn update_test_image(path: &str) -> MediaAttachment {
+    MediaAttachment::Image {
+        data: ImageData::File(path.into()),
+        placement: ImagePlacement::AppIcon,
+        alt_text: None,
+        dimensions: None,
+    }
+}
+
+#[test]
+fn test_update_merges_into_shown_content() {
+    let mut content = NotificationContent::new("Building", RichText::markdown("**step 1** of 3"));
+    content.subtitle = Some("kodegen".to_string());
+    content.media = vec![update_test_image("/tmp/icon.png"), update_test_image("/tmp/old.png")];
+    content.interactions.actions = vec![
+        update_test_action("cancel", "Cancel"),
+        update_test_action("logs", "Logs"),
+    ];
+
+    let update = NotificationUpdate {
+        content_changes: HashMap::from([
+            ("title".to_string(), "Built".to_string()),
+            ("subtitle".to_string(), String::new()),
+            ("build".to_string(), "42".to_string()),
+        ]),
+        media_changes: vec![
+            MediaChange::Remove("/tmp/old.png".to_string()),
+            MediaChange::Add(update_test_image("/tmp/new.png")),
+        ],
+        action_changes: vec![
+            ActionChange::Remove("cancel".to_string()),
+            ActionChange::Update {
+                id: "logs".to_string(),
+                new_action: update_test_action("logs", "Open logs"),
+            },
+        ],
+        ..NotificationUpdate::default()
+    };
+    update.apply_to(&mut content);
+
+    // Unchanged fields are kept as shown, markup included
+    assert_eq!(content.title, "Built");
+    assert_eq!(content.subtitle, None);
+    assert!(matches!(&content.body, RichText::Markdown(md) if md == "**step 1** of 3"));
+    assert_eq!(content.custom_data.get("build").map(String::as_str), Some("42"));
+    let media: Vec<_> = content
+        .media
+        .iter()
+        .map(|attachment| match attachment {
+            MediaAttachment::Image { data: ImageData::File(path), .. } => path.display().to_string(),
+            other => panic!("unexpected attachment {:?}", other),
+        })
+        .collect();
+    assert_eq!(media, vec!["/tmp/icon.png", "/tmp/new.png"]);
+    let actions: Vec<_> = content
+        .interactions
+        .actions
+        .iter()
+        .map(|action| (action.id.as_str(), action.label.as_str()))
+        .collect();
+    assert_eq!(actions, vec![("logs", "Open logs")]);
+
+    // A full replacement applies first, field changes on top
+    let update = NotificationUpdate {
+        content: Some(NotificationContent::new("Deploying", "to staging")),
+        content_changes: HashMap::from([("body".to_string(), "to production".to_string())]),
+        ..NotificationUpdate::default()
+    };
+    update.apply_to(&mut content);
+    assert_eq!(content.title, "Deploying");
+    assert_eq!(content.body.to_plain_text(), "to production");
+    assert!(content.media.is_empty());
+}
@@ -0,0 +1,162 @@
+//! Tests for components/rich_text.rs
+
+use kodegen_native_notify::{PangoSubset, RichBlock, RichDocument, RichInline, RichText};
+
+/// The same message written as Markdown and as HTML
+const MARKDOWN: &str = "## Build failed\n\n\
+    **3 errors** in *core* & `cli`, see [the log](https://ci.example.com/run/42).\n\n\
+    - compile `parser`\n- link \\<stage\\>\n  1. retry\n  2. give up\n\n\
+    ```\nerror[E0308]: mismatched types\n  --> src/lib.rs:4:5\n```";
+
+const HTML: &str = "<h2>Build failed</h2>\
+    <p><strong>3 errors</strong> in <em>core</em> &amp; <code>cli</code>, see \
+    <a href=\"https://ci.example.com/run/42\" onclick=\"steal()\">the log</a>.</p>\n\
+    <ul>\n  <li>compile <code>parser</code></li>\n  <li>link &lt;stage&gt;\
+    <ol><li>retry</li><li>give up</li></ol></li>\n</ul>\
+    <pre><code>error[E0308]: mismatched types\n  --&gt; src/lib.rs:4:5\n</code></pre>";
+
+#[test]
+fn test_markdown_and_html_parse_to_the_same_document() {
+    let from_markdown = RichText::markdown(MARKDOWN).to_document();
+    let from_html = RichText::html(HTML).to_document();
+    assert_eq!(from_markdown, from_html);
+
+    assert_eq!(
+        from_markdown.blocks[0],
+        RichBlock::Heading {
+            level: 2,
+            content: vec![RichInline::Text("Build failed".to_string())],
+        }
+    );
+    assert!(matches!(
+        &from_markdown.blocks[2],
+        RichBlock::List { start: None, items } if items.len() == 2
+    ));
+}
+
+#[test]
+fn test_renderer_snapshots() {
+    for text in [RichText::markdown(MARKDOWN), RichText::html(HTML)] {
+        assert_eq!(
+            text.to_plain_text(),
+            "Build failed\n\
+             3 errors in core & cli, see the log.\n\
+             compile parser\n\
+             link <stage>\n\
+             retry\n\
+             give up\n\
+             error[E0308]: mismatched types\n\
+             --> src/lib.rs:4:5"
+        );
+
+        assert_eq!(
+            text.to_structured_plain_text(),
+            "Build failed\n\n\
+             3 errors in core & `cli`, see the log (https://ci.example.com/run/42).\n\n\
+             • compile `parser`\n\
+             • link <stage>\n  \
+               1. retry\n  \
+               2. give up\n\n  \
+             error[E0308]: mismatched types\n    \
+               --> src/lib.rs:4:5"
+        );
+
+        assert_eq!(
+            text.to_pango_markup(),
+            "<b>Build failed</b>\n\n\
+             <b>3 errors</b> in <i>core</i> &amp; <tt>cli</tt>, see \
+             <a href=\"https://ci.example.com/run/42\">the log</a>.\n\n\
+             • compile <tt>parser</tt>\n\
+             • link &lt;stage&gt;\n  \
+               1. retry\n  \
+               2. give up\n\n\
+             <tt>error[E0308]: mismatched types\n  --&gt; src/lib.rs:4:5</tt>"
+        );
+
+        assert_eq!(
+            text.to_html(),
+            "<h2>Build failed</h2>\n\
+             <p><strong>3 errors</strong> in <em>core</em> &amp; <code>cli</code>, see \
+             <a href=\"https://ci.example.com/run/42\" rel=\"noopener noreferrer\">the log</a>.</p>\n\
+             <ul><li>compile <code>parser</code></li>\
+             <li><p>link &lt;stage&gt;</p>\n<ol><li>retry</li><li>give up</li></ol></li></ul>\n\
+             <pre><code>error[E0308]: mismatched types\n  --&gt; src/lib.rs:4:5</code></pre>"
+        );
+
+        assert!(text.to_toast_text().starts_with("Build failed\n\n3 errors in core &amp; `cli`"));
+        assert!(text.to_toast_text().contains("• link &lt;stage&gt;"));
+    }
+}
+
+#[test]
+fn test_unsafe_links_keep_text_only() {
+    for text in [
+        RichText::markdown("[click](javascript:alert(1))"),
+        RichText::html("<a href=\"javascript:alert(1)\">click</a>"),
+    ] {
+        assert_eq!(
+            text.to_document().blocks,
+            vec![RichBlock::Paragraph(vec![RichInline::Link {
+                href: None,
+                content: vec![RichInline::Text("click".to_string())],
+            }])]
+        );
+        assert_eq!(text.to_pango_markup(), "click");
+        assert_eq!(text.to_structured_plain_text(), "click");
+    }
+}
+
+#[test]
+fn test_line_breaks_and_nested_emphasis_agree() {
+    let markdown = RichText::markdown("***both*** and a  \nbreak");
+    let html = RichText::html("<p><em><strong>both</strong></em> and a<br>break</p>");
+
+    assert_eq!(markdown.to_pango_markup(), "<i><b>both</b></i> and a\nbreak");
+    assert_eq!(html.to_pango_markup(), "<i><b>both</b></i> and a\nbreak");
+    assert_eq!(markdown.to_plain_text(), html.to_plain_text());
+}
+
+#[test]
+fn test_plain_text_document() {
+    let document = RichDocument::from_plain("first line\nsecond <line>\n\nnext paragraph");
+    assert_eq!(document.blocks.len(), 2);
+    assert_eq!(
+        document.to_pango_markup(),
+        "first line\nsecond &lt;line&gt;\n\nnext paragraph"
+    );
+    assert_eq!(RichText::plain("a & b").to_html(), "<p>a &amp; b</p>");
+}
+
+#[test]
+fn test_pango_subsets_for_limited_servers() {
+    let text = RichText::markdown(MARKDOWN);
+    assert_eq!(text.to_pango_markup_subset(PangoSubset::FULL), text.to_pango_markup());
+
+    // Spec tags only: code loses <tt> but keeps its backticks and indentation
+    assert_eq!(
+        text.to_pango_markup_subset(PangoSubset::SPEC),
+        "<b>Build failed</b>\n\n\
+         <b>3 errors</b> in <i>core</i> &amp; `cli`, see \
+         <a href=\"https://ci.example.com/run/42\">the log</a>.\n\n\
+         • compile `parser`\n\
+         • link &lt;stage&gt;\n  \
+           1. retry\n  \
+           2. give up\n\n  \
+         error[E0308]: mismatched types\n    \
+           --&gt; src/lib.rs:4:5"
+    );
+
+    // Without hyperlinks the URL stays readable; without extensions colors and strikes go
+    let no_links = PangoSubset {
+        hyperlinks: false,
+        extended: false,
+    };
+    assert_eq!(
+        RichText::markdown("see [the log](https://ci.example.com/?a=1&b=2) ~~now~~").to_pango_markup_subset(no_links),
+        "see the log (https://ci.example.com/?a=1&amp;b=2) now"
+    );
+    assert_eq!(
+        RichText::ansi("\x1b[31mfailed\x1b[0m").to_pango_markup_subset(no_links),
+        "failed"
+    );
+}
@@ -0,0 +1,73 @@
+//! Tests for components/truncation.rs
+
+use kodegen_native_notify::{RichText, TruncationPolicy};
+
+#[test]
+fn test_plain_text_within_limit_is_untouched() {
+    let policy = TruncationPolicy::new();
+    assert_eq!(policy.truncate_plain("short", 10), None);
+}
+
+#[test]
+fn test_plain_truncation_respects_grapheme_clusters() {
+    // Family emoji is a single grapheme made of several code points
+    let text = "ab👨‍👩‍👧cd";
+    let policy = TruncationPolicy::new()
+        .with_ellipsis("...")
+        .with_prefer_boundaries(false);
+
+    let truncated = policy.truncate_plain(text, 10).expect("text should be truncated");
+    assert_eq!(truncated, "ab...");
+    assert!(truncated.len() <= 10);
+}
+
+#[test]
+fn test_plain_truncation_prefers_sentence_boundary() {
+    let text = "Tests passed. Coverage report is ready";
+    let truncated = TruncationPolicy::new()
+        .truncate_plain(text, 20)
+        .expect("text should be truncated");
+
+    assert_eq!(truncated, "Tests passed.…");
+}
+
+#[test]
+fn test_plain_truncation_falls_back_to_word_boundary() {
+    let text = "cargo build finished with warnings";
+    let truncated = TruncationPolicy::new()
+        .truncate_plain(text, 24)
+        .expect("text should be truncated");
+
+    assert_eq!(truncated, "cargo build finished…");
+    assert!(truncated.len() <= 24);
+}
+
+#[test]
+fn test_html_truncation_closes_open_tags() {
+    let html = RichText::html("<b>Tests <i>failed in module</i> alpha</b> and beta");
+    let truncated = TruncationPolicy::new()
+        .with_prefer_boundaries(false)
+        .truncate_rich_text(&html, 12)
+        .expect("html should be truncated");
+
+    match truncated {
+        RichText::Html(ref body) => assert_eq!(body, "<b>Tests <i>fai…</i></b>"),
+        _ => panic!("Expected Html variant"),
+    }
+    assert!(truncated.to_plain_text().len() <= 12);
+}
+
+#[test]
+fn test_markdown_truncation_closes_emphasis() {
+    let markdown = RichText::markdown("Result: **all tests passed in 3.2s** today");
+    let truncated = TruncationPolicy::new()
+        .with_prefer_boundaries(false)
+        .truncate_rich_text(&markdown, 20)
+        .expect("markdown should be truncated");
+
+    match truncated {
+        RichText::Markdown(ref body) => assert!(body.ends_with("…**"), "got {body}"),
+        _ => panic!("Expected Markdown variant"),
+    }
+    assert!(truncated.to_plain_text().len() <= 20);
+}
@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use kodegen_native_notify::*;
 use kodegen_native_notify::components::lifecycle::NotificationState;
 
@@ -151,29 +155,59 @@ fn test_builder_validation_valid_content() {
     println!("✅ Builder validation (valid content) test passed!");
 }
 
-#[test]
-fn test_builder_validation_title_too_long() {
-    // Should fail: title too long for Windows (max 128)
+#[tokio::test]
+async fn test_builder_validation_title_too_long() {
+    // Lengths are checked against the limit the backend reports, not a static table
+    let (manager, backend) = stub_manager();
+    *backend.max_title_length.lock().unwrap() = Some(128);
     let long_title = "x".repeat(500);
-    let result = NotificationBuilder::new()
-        .with_title(long_title)
-        .with_body(RichText::plain("Body"))
-        .with_platforms(vec![Platform::Windows])
-        .build();
+    let builder = || {
+        NotificationBuilder::new()
+            .with_title(long_title.clone())
+            .with_body(RichText::plain("Body"))
+            .with_platforms(vec![Platform::Linux])
+    };
 
-    assert!(result.is_err());
-    match result.unwrap_err() {
+    builder().build().expect("nothing negotiated, so no length limit applies");
+
+    match builder().build_negotiated(&manager).await.unwrap_err() {
         NotificationBuildError::TitleTooLong { platform, length, max } => {
-            assert_eq!(platform, "Windows");
+            assert_eq!(platform, "Linux");
             assert_eq!(length, 500);
             assert_eq!(max, 128);
         }
-        _ => panic!("Expected TitleTooLong error"),
+        other => panic!("Expected TitleTooLong error, got {:?}", other),
     }
 
     println!("✅ Builder validation (title too long) test passed!");
 }
 
+#[tokio::test]
+async fn test_builder_truncation_policy() {
+    // With a truncation policy the over-long title is shortened instead of rejected
+    let (manager, backend) = stub_manager();
+    *backend.max_title_length.lock().unwrap() = Some(128);
+    let long_title = "x".repeat(500);
+    let notification = NotificationBuilder::new()
+        .with_title(long_title)
+        .with_body(RichText::plain("Body"))
+        .with_platforms(vec![Platform::Linux])
+        .with_truncation(TruncationPolicy::new())
+        .build_negotiated(&manager)
+        .await
+        .expect("Truncated notification should build successfully");
+
+    assert!(notification.content.title.len() <= 128);
+    assert!(notification.content.title.ends_with('…'));
+    assert_eq!(
+        notification.content.custom_data.get(TRUNCATED_TITLE_KEY),
+        Some(&"500".to_string())
+    );
+    assert!(!notification.content.custom_data.contains_key(TRUNCATED_BODY_KEY));
+
+    println!("✅ Builder truncation policy test passed!");
+}
+
 #[test]
 fn test_builder_validation_no_platforms() {
     // Should fail: empty platforms list
@@ -192,17 +226,26 @@ fn test_builder_validation_no_platforms() {
     println!("✅ Builder validation (no platforms) test passed!");
 }
 
-#[test]
-fn test_builder_validation_platform_limits() {
-    // Test that different platforms have different limits
+#[tokio::test]
+async fn test_builder_validation_platform_limits() {
+    // Each platform is held to the limit its own backend reports
+    let linux = StubBackend::default();
+    let windows = StubBackend::default();
+    *windows.max_title_length.lock().unwrap() = Some(128);
+    let mut backends: HashMap<Platform, Box<dyn PlatformBackend>> = HashMap::new();
+    backends.insert(Platform::Linux, Box::new(linux));
+    backends.insert(Platform::Windows, Box::new(windows));
+    let manager = NotificationManager::try_with_platform_manager(PlatformManager::with_backends(backends))
+        .expect("tests run inside a tokio runtime");
     let title_256 = "x".repeat(256);
 
-    // Should succeed for macOS (max 256)
+    // Should succeed for Linux (max 512)
     let result = NotificationBuilder::new()
         .with_title(title_256.clone())
         .with_body(RichText::plain("Body"))
-        .with_platforms(vec![Platform::MacOS])
-        .build();
+        .with_platforms(vec![Platform::Linux])
+        .build_negotiated(&manager)
+        .await;
     assert!(result.is_ok());
 
     // Should fail for Windows (max 128)
@@ -210,8 +253,1952 @@ fn test_builder_validation_platform_limits() {
         .with_title(title_256)
         .with_body(RichText::plain("Body"))
         .with_platforms(vec![Platform::Windows])
-        .build();
+        .build_negotiated(&manager)
+        .await;
     assert!(result.is_err());
 
     println!("✅ Builder validation (platform limits) test passed!");
 }
+
+/// Encode a noisy RGBA image as PNG so it compresses poorly
+fn noisy_png(width: u32, height: u32) -> Vec<u8> {
+    let image = image::RgbaImage::from_fn(width, height, |x, y| {
+        let v = x.wrapping_mul(2_654_435_761).wrapping_add(y.wrapping_mul(40_503)) as u8;
+        image::Rgba([v, v.rotate_left(3), v.rotate_left(5), 255])
+    });
+    let mut data = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut data), image::ImageFormat::Png)
+        .expect("PNG encoding should succeed");
+    data
+}
+
+#[test]
+fn test_image_processing_fits_platform_limits() {
+    let source = noisy_png(2000, 1200);
+    let target = ImageTarget::for_placement(Platform::Windows, ImagePlacement::Hero, Some(204_800));
+    assert!(source.len() > 204_800);
+
+    let processed = process_image(&source, &target)
+        .expect("processing should succeed")
+        .expect("oversized image should be re-encoded");
+
+    assert!(processed.data.len() <= 204_800);
+    assert!(processed.width <= 728 && processed.height <= 364);
+    assert!(matches!(processed.format, ImageFormat::Png | ImageFormat::Jpeg));
+
+    println!("✅ Image processing (platform limits) test passed!");
+}
+
+#[test]
+fn test_image_processing_passes_through_conforming_images() {
+    let source = noisy_png(64, 64);
+    let target = ImageTarget::for_placement(Platform::Linux, ImagePlacement::AppIcon, Some(1_048_576));
+
+    let processed = process_image(&source, &target).expect("processing should succeed");
+    assert!(processed.is_none());
+
+    println!("✅ Image processing (pass-through) test passed!");
+}
+
+#[test]
+fn test_image_processing_rasterizes_svg() {
+    let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16"><rect width="16" height="16" fill="red"/></svg>"#;
+    let target = ImageTarget::for_placement(Platform::Linux, ImagePlacement::AppIcon, None);
+
+    let processed = process_image(svg, &target)
+        .expect("processing should succeed")
+        .expect("SVG should be rasterized");
+
+    assert_eq!(processed.format, ImageFormat::Png);
+    assert_eq!((processed.width, processed.height), (128, 128));
+
+    println!("✅ Image processing (SVG) test passed!");
+}
+
+/// Minimal HTTP server serving `body` with an ETag and honoring `If-None-Match`
+///
+/// Returns the base URL and a counter of (requests, 304 responses).
+async fn spawn_image_server(
+    body: Vec<u8>,
+) -> (String, std::sync::Arc<std::sync::Mutex<(usize, usize)>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let address = listener.local_addr().expect("listener should have an address");
+    let counters = std::sync::Arc::new(std::sync::Mutex::new((0, 0)));
+    let server_counters = std::sync::Arc::clone(&counters);
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buffer).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buffer[..n]),
+                }
+            }
+            let request = String::from_utf8_lossy(&request).to_ascii_lowercase();
+            let not_modified = request.contains("if-none-match: \"v1\"");
+            {
+                let mut counters = server_counters.lock().unwrap();
+                counters.0 += 1;
+                if not_modified {
+                    counters.1 += 1;
+                }
+            }
+
+            let response = if not_modified {
+                b"HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+            } else {
+                let mut response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .into_bytes();
+                response.extend_from_slice(&body);
+                response
+            };
+            let _ = stream.write_all(&response).await;
+            let _ = stream.shutdown().await;
+        }
+    });
+
+    (format!("http://{}", address), counters)
+}
+
+#[tokio::test]
+async fn test_disk_image_cache_revalidates_and_shares_blobs() {
+    let dir = tempfile::tempdir().expect("temp dir should be created");
+    let body = noisy_png(32, 32);
+    let (base_url, counters) = spawn_image_server(body.clone()).await;
+
+    let cache = DiskImageCache::new(dir.path(), body.len() as u64)
+        .with_revalidate_after(std::time::Duration::ZERO)
+        .with_fetch_policy(ImageFetchPolicy::default().with_private_addresses(true));
+
+    // First fetch downloads, second revalidates with the stored ETag
+    let logo = Url::parse(&format!("{}/logo.png", base_url)).unwrap();
+    let first = cache.fetch(&logo).await.expect("first fetch should succeed");
+    let second = cache.fetch(&logo).await.expect("revalidation should succeed");
+    assert_eq!(first, second);
+    assert_eq!(std::fs::read(&first).unwrap(), body);
+    assert_eq!(*counters.lock().unwrap(), (2, 1));
+
+    // Another URL with identical bytes shares the content-addressed blob
+    let alias = Url::parse(&format!("{}/alias.png", base_url)).unwrap();
+    let shared = cache.fetch(&alias).await.expect("alias fetch should succeed");
+    assert_eq!(shared, first);
+    assert_eq!(cache.total_size().unwrap(), body.len() as u64);
+
+    println!("✅ Disk image cache test passed!");
+}
+
+/// Fetch through a throwaway disk cache with `policy` and return the error message
+async fn fetch_error(url: &str, policy: ImageFetchPolicy) -> String {
+    let dir = tempfile::tempdir().expect("temp dir should be created");
+    let cache = DiskImageCache::new(dir.path(), 1024 * 1024).with_fetch_policy(policy);
+    match cache.fetch(&Url::parse(url).unwrap()).await {
+        Err(NotificationError::ResourceError { message, .. }) => message,
+        other => panic!("Expected ResourceError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_image_fetch_policy_blocks_internal_addresses() {
+    let (base_url, counters) = spawn_image_server(noisy_png(8, 8)).await;
+    let port = Url::parse(&base_url).unwrap().port().unwrap();
+
+    // Literal link-local metadata address is rejected before connecting
+    let message = fetch_error("http://169.254.169.254/latest/meta-data", ImageFetchPolicy::default()).await;
+    assert!(message.contains("169.254.169.254"), "got {message}");
+
+    // Hostnames are checked after DNS resolution
+    let message = fetch_error(&format!("http://localhost:{}/logo.png", port), ImageFetchPolicy::default()).await;
+    assert!(message.contains("private, loopback or link-local"), "got {message}");
+    assert_eq!(counters.lock().unwrap().0, 0);
+
+    println!("✅ Image fetch policy (internal addresses) test passed!");
+}
+
+#[test]
+fn test_image_fetch_policy_checks_ipv4_embedded_in_ipv6() {
+    let policy = ImageFetchPolicy::default();
+    let check = |url: &str| policy.check_url(&Url::parse(url).unwrap());
+
+    // IPv4-mapped
+    assert!(check("http://[::ffff:127.0.0.1]/logo.png").is_err());
+    assert!(check("http://[::ffff:8.8.8.8]/logo.png").is_ok());
+
+    // IPv4-compatible
+    assert!(check("http://[::127.0.0.1]/logo.png").is_err());
+    assert!(check("http://[::10.0.0.1]/logo.png").is_err());
+    assert!(check("http://[::8.8.8.8]/logo.png").is_ok());
+
+    // NAT64 well-known prefix
+    assert!(check("http://[64:ff9b::169.254.169.254]/latest/meta-data").is_err());
+    assert!(check("http://[64:ff9b::192.168.1.1]/logo.png").is_err());
+    assert!(check("http://[64:ff9b::8.8.8.8]/logo.png").is_ok());
+
+    // 6to4, with the IPv4 address in the second and third groups
+    assert!(check("http://[2002:7f00:1::]/logo.png").is_err());
+    assert!(check("http://[2002:a9fe:a9fe::1]/latest/meta-data").is_err());
+    assert!(check("http://[2002:808:808::1]/logo.png").is_ok());
+
+    // Allowed when the policy permits internal addresses
+    let permissive = ImageFetchPolicy::default().with_private_addresses(true);
+    assert!(permissive.check_url(&Url::parse("http://[64:ff9b::127.0.0.1]/").unwrap()).is_ok());
+}
+
+#[tokio::test]
+async fn test_image_fetch_policy_host_lists_size_and_sniffing() {
+    let permissive = ImageFetchPolicy::default().with_private_addresses(true);
+
+    let message = fetch_error("https://tracker.example.com/pixel.png", permissive.clone().deny_host("example.com")).await;
+    assert!(message.contains("denied"), "got {message}");
+
+    let message = fetch_error("https://cdn.other.org/logo.png", permissive.clone().allow_host("example.com")).await;
+    assert!(message.contains("allowlist"), "got {message}");
+
+    let (image_url, _) = spawn_image_server(noisy_png(64, 64)).await;
+    let message = fetch_error(&format!("{}/big.png", image_url), permissive.clone().with_max_bytes(512)).await;
+    assert!(message.contains("too large"), "got {message}");
+
+    let (html_url, _) = spawn_image_server(b"<html><body>not an image</body></html>".to_vec()).await;
+    let message = fetch_error(&format!("{}/fake.png", html_url), permissive).await;
+    assert!(message.contains("not a supported image"), "got {message}");
+
+    println!("✅ Image fetch policy (hosts, size, sniffing) test passed!");
+}
+
+#[tokio::test]
+async fn test_data_url_images_decode_in_process() {
+    let png = noisy_png(4, 4);
+    let encoded = {
+        use base64::Engine as _;
+        base64::engine::general_purpose::STANDARD.encode(&png)
+    };
+
+    let url = Url::parse(&format!("data:image/png;base64,{}", encoded)).unwrap();
+    let data = ImageData::Url(url);
+    data.validate(&std::collections::HashMap::new()).expect("valid data: URL should validate");
+
+    let resolved = resolve_image_to_path(&data)
+        .await
+        .expect("data: URL should resolve")
+        .expect("data: URL should produce a file");
+    assert!(resolved.is_temp);
+    assert_eq!(resolved.path.extension().unwrap(), "png");
+    assert_eq!(std::fs::read(&resolved.path).unwrap(), png);
+
+    // The same bytes reuse their file instead of leaving a new one behind per send
+    let again = resolve_image_to_path(&data).await.unwrap().unwrap();
+    assert_eq!(again.path, resolved.path);
+    let embedded = ImageData::Embedded { data: png.clone(), format: ImageFormat::Png };
+    let embedded = resolve_image_to_path(&embedded).await.unwrap().unwrap();
+    assert_eq!(embedded.path, resolved.path);
+
+    // Written again once cleaned up
+    cleanup_temp_image(&resolved);
+    let rewritten = resolve_image_to_path(&data).await.unwrap().unwrap();
+    assert_eq!(std::fs::read(&rewritten.path).unwrap(), png);
+    cleanup_temp_image(&rewritten);
+
+    let svg = Url::parse("data:image/svg+xml,%3Csvg%20xmlns='http://www.w3.org/2000/svg'/%3E").unwrap();
+    let (bytes, format) = decode_image_data_url(&svg).expect("percent-encoded payload should decode");
+    assert_eq!(format, ImageFormat::Svg);
+    assert!(bytes.starts_with(b"<svg "));
+
+    for invalid in ["data:text/plain,hello", "data:image/png;base64,***", "data:image/png;base64,"] {
+        let data = ImageData::Url(Url::parse(invalid).unwrap());
+        assert!(
+            matches!(data.validate(&std::collections::HashMap::new()), Err(NotificationError::ValidationError { .. })),
+            "{invalid} should be rejected"
+        );
+    }
+
+    println!("✅ data: URL image test passed!");
+}
+
+#[tokio::test]
+async fn test_icon_theme_lookup_follows_inheritance_and_sizes() {
+    let root = tempfile::tempdir().unwrap();
+    let write = |relative: &str, contents: &[u8]| {
+        let path = root.path().join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    };
+
+    write(
+        "Custom/index.theme",
+        b"[Icon Theme]\nName=Custom\nInherits=Base\nDirectories=16x16/apps,32x32@2/apps\nScaledDirectories=32x32@2/apps\n\n\
+          [16x16/apps]\nSize=16\nType=Fixed\n\n[32x32@2/apps]\nSize=32\nScale=2\nType=Fixed\n",
+    );
+    write("Custom/16x16/apps/editor.png", &noisy_png(16, 16));
+    write("Custom/32x32@2/apps/editor.png", &noisy_png(64, 64));
+    write(
+        "Base/index.theme",
+        b"[Icon Theme]\nName=Base\nDirectories=48x48/apps\n\n[48x48/apps]\nSize=48\nType=Threshold\n",
+    );
+    write("Base/48x48/apps/terminal.png", &noisy_png(48, 48));
+    write(
+        "hicolor/index.theme",
+        b"[Icon Theme]\nName=Hicolor\nDirectories=scalable/apps\n\n[scalable/apps]\nSize=48\nMinSize=8\nMaxSize=512\nType=Scalable\n",
+    );
+    write("hicolor/scalable/apps/browser.svg", b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>");
+    write("loose.png", &noisy_png(8, 8));
+
+    let lookup = IconLookup::new()
+        .with_search_paths(vec![root.path().to_path_buf()])
+        .with_theme("Custom");
+    let found = |name: &str, size: u32, scale: u32| {
+        lookup
+            .lookup(name, size, scale)
+            .map(|path| path.strip_prefix(root.path()).unwrap().to_string_lossy().into_owned())
+    };
+
+    assert_eq!(found("editor", 16, 1).as_deref(), Some("Custom/16x16/apps/editor.png"));
+    assert_eq!(found("editor", 32, 2).as_deref(), Some("Custom/32x32@2/apps/editor.png"));
+    // No exact match: the closest size within the theme wins over parents
+    assert_eq!(found("editor", 48, 1).as_deref(), Some("Custom/32x32@2/apps/editor.png"));
+    assert_eq!(found("terminal", 50, 1).as_deref(), Some("Base/48x48/apps/terminal.png"));
+    assert_eq!(found("browser", 256, 1).as_deref(), Some("hicolor/scalable/apps/browser.svg"));
+    assert_eq!(found("loose", 48, 1).as_deref(), Some("loose.png"));
+    assert_eq!(found("missing", 48, 1), None);
+
+    #[cfg(target_os = "linux")]
+    {
+        set_icon_lookup(lookup.clone());
+
+        let resolved = resolve_image_to_path(&ImageData::SystemIcon("terminal".to_string()))
+            .await
+            .expect("theme icon should resolve")
+            .expect("Linux resolves system icons to files");
+        assert!(resolved.path.ends_with("Base/48x48/apps/terminal.png"));
+
+        let resolved = resolve_action_icon(&ActionIcon::System("browser".to_string()))
+            .await
+            .expect("action icon should resolve")
+            .expect("Linux resolves action icons to files");
+        assert!(resolved.path.ends_with("hicolor/scalable/apps/browser.svg"));
+
+        let error = resolve_image_to_path(&ImageData::SystemIcon("missing".to_string()))
+            .await
+            .expect_err("unknown icon should fail");
+        assert!(
+            matches!(&error, NotificationError::ValidationError { message, .. } if message.contains("'missing'")),
+            "got {error}"
+        );
+    }
+
+    println!("✅ Icon theme lookup test passed!");
+}
+
+#[test]
+fn test_sound_theme_lookup_and_hints() {
+    let root = tempfile::tempdir().unwrap();
+    let write = |relative: &str, contents: &[u8]| {
+        let path = root.path().join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    };
+
+    write(
+        "Custom/index.theme",
+        b"[Sound Theme]\nName=Custom\nInherits=freedesktop\nDirectories=stereo\n\n[stereo]\nOutputProfile=stereo\n",
+    );
+    write("Custom/stereo/dialog-warning.oga", b"OggS");
+    write("Custom/stereo/bell.disabled", b"");
+    write(
+        "freedesktop/index.theme",
+        b"[Sound Theme]\nName=Default\nDirectories=stereo\n\n[stereo]\nOutputProfile=stereo\n",
+    );
+    write("freedesktop/stereo/complete.oga", b"OggS");
+    write("freedesktop/stereo/bell.oga", b"OggS");
+    write("chime.wav", b"RIFF");
+
+    let lookup = SoundLookup::new()
+        .with_search_paths(vec![root.path().to_path_buf()])
+        .with_theme("Custom");
+    let found = |name: &str| {
+        lookup
+            .lookup(name)
+            .map(|path| path.strip_prefix(root.path()).unwrap().to_string_lossy().into_owned())
+    };
+
+    assert_eq!(found("dialog-warning").as_deref(), Some("Custom/stereo/dialog-warning.oga"));
+    // Unknown suffixes fall back to the more generic name
+    assert_eq!(found("dialog-warning-urgent").as_deref(), Some("Custom/stereo/dialog-warning.oga"));
+    assert_eq!(found("complete").as_deref(), Some("freedesktop/stereo/complete.oga"));
+    assert_eq!(found("chime").as_deref(), Some("chime.wav"));
+    // Disabled in the selected theme even though the parent provides it
+    assert_eq!(found("bell"), None);
+    assert_eq!(found("missing"), None);
+
+    assert_eq!(xdg_sound_name(SystemSound::Alert), "message-new-instant");
+    assert_eq!(xdg_sound_name(SystemSound::Critical), "dialog-warning");
+    assert_eq!(xdg_sound_name(SystemSound::Success), "complete");
+
+    set_sound_lookup(lookup);
+    let with_audio = |source: AudioSource| {
+        let mut content = NotificationContent::new("Build", RichText::plain("done"));
+        content.media.push(MediaAttachment::Audio {
+            source,
+            volume: 1.0,
+            loop_audio: false,
+            duration: None,
+        });
+        content
+    };
+
+    let content = with_audio(AudioSource::System(SystemSound::Success));
+    assert_eq!(resolve_sound_hint(&content, None), Some(SoundHint::Name("complete".to_string())));
+    // Per-platform user settings override the content's choice
+    assert_eq!(
+        resolve_sound_hint(&content, Some("dialog-information")),
+        Some(SoundHint::Name("dialog-information".to_string()))
+    );
+    assert_eq!(
+        resolve_sound_hint(&content, Some("chime.wav")),
+        Some(SoundHint::File(root.path().join("chime.wav")))
+    );
+
+    let content = with_audio(AudioSource::File("missing.oga".into()));
+    assert_eq!(resolve_sound_hint(&content, None), None);
+    let content = with_audio(AudioSource::File("freedesktop/stereo/complete.oga".into()));
+    assert_eq!(
+        resolve_sound_hint(&content, None),
+        Some(SoundHint::File(root.path().join("freedesktop/stereo/complete.oga")))
+    );
+    let content = with_audio(AudioSource::File("complete.oga".into()));
+    assert_eq!(
+        resolve_sound_hint(&content, None),
+        Some(SoundHint::File(root.path().join("freedesktop/stereo/complete.oga")))
+    );
+    assert_eq!(resolve_sound_hint(&with_audio(AudioSource::Silent), None), Some(SoundHint::Suppress));
+    assert_eq!(resolve_sound_hint(&with_audio(AudioSource::Default), None), None);
+
+    println!("✅ Sound theme lookup test passed!");
+}
+
+#[test]
+fn test_command_notification_failure() {
+    let output = "   Compiling app v0.1.0\n\
+        \x1b[31merror[E0425]\x1b[0m: cannot find value `x` in this scope\n \
+        --> src/main.rs:2:13\n\
+        error: could not compile `app` (bin \"app\") due to 1 previous error\n";
+    let notification = CommandNotification::new("cargo build --release --workspace --all-features")
+        .with_cwd("/work/app")
+        .with_exit_code(101)
+        .with_duration(std::time::Duration::from_millis(45_230))
+        .with_output(output)
+        .with_terminal_id(3)
+        .with_transcript_path("/tmp/transcript.jsonl")
+        .with_platforms(vec![Platform::Linux])
+        .build()
+        .expect("command notification should build");
+
+    let content = &notification.content;
+    assert_eq!(content.title, "✗ terminal 3: cargo build --release --workspace --a…");
+    assert_eq!(content.priority, Priority::High);
+    assert_eq!(content.dedup_key(), Some("terminal:3"));
+    assert_eq!(
+        content.body.to_plain_text(),
+        "exit 101 in 45.2s\ncwd: /work/app\n1 error\n\
+         error[E0425]: cannot find value `x` in this scope (src/main.rs:2:13)"
+    );
+
+    let actions: Vec<&str> = content.interactions.actions.iter().map(|action| action.id.as_str()).collect();
+    assert_eq!(actions, vec![OPEN_TRANSCRIPT_ACTION, RERUN_ACTION, COPY_COMMAND_ACTION]);
+    let open = &content.interactions.actions[0];
+    assert_eq!(open.url.as_ref().map(|url| url.as_str()), Some("file:///tmp/transcript.jsonl"));
+    assert_eq!(
+        open.payload.as_ref().and_then(|payload| payload.data.get("command")).map(String::as_str),
+        Some("cargo build --release --workspace --all-features")
+    );
+}
+
+#[test]
+fn test_command_notification_success_and_theme() {
+    let output: String = (1..=30).map(|line| format!("line {}\n", line)).collect();
+    let theme = CommandTheme {
+        success_icon: "OK".to_string(),
+        max_output_lines: 2,
+        show_cwd: false,
+        rerun_label: "Run again".to_string(),
+        ..CommandTheme::default()
+    };
+    let notification = CommandNotification::new("make")
+        .with_cwd("/work")
+        .with_exit_code(0)
+        .with_duration(std::time::Duration::from_millis(850))
+        .with_output(output)
+        .with_theme(theme)
+        .build()
+        .expect("command notification should build");
+
+    let content = &notification.content;
+    assert_eq!(content.title, "OK make");
+    assert_eq!(content.priority, Priority::Normal);
+    assert_eq!(content.dedup_key(), None);
+    assert_eq!(
+        content.body.to_plain_text(),
+        "success in 850ms\n...(28 lines hidden)\nline 29\nline 30"
+    );
+    assert_eq!(content.interactions.actions[0].label, "Run again");
+    assert_eq!(content.interactions.actions.len(), 2);
+
+    assert_eq!(format_duration(std::time::Duration::from_secs(192)), "3m 12s");
+    let running = CommandNotification::new("sleep 100");
+    assert_eq!(running.status(), CommandStatus::Running);
+    assert_eq!(running.title(), "⏳ sleep 100");
+}
+
+/// Backend that records deliveries and cancellations and reports scripted interactions
+#[derive(Clone, Default)]
+struct StubBackend {
+    delivered: Arc<Mutex<Vec<NotificationContent>>>,
+    cancelled: Arc<Mutex<Vec<String>>>,
+    /// App identity of each delivery and cancellation, in order
+    apps: Arc<Mutex<Vec<(&'static str, AppIdentity)>>>,
+    negotiations: Arc<Mutex<usize>>,
+    interactions: Arc<Mutex<Option<tokio::sync::broadcast::Sender<PlatformInteraction>>>>,
+    /// Every delivery fails with this kind when set, counting the attempts
+    failure: Arc<Mutex<Option<ErrorKind>>>,
+    failed_attempts: Arc<Mutex<usize>>,
+    /// Negotiating capabilities fails when set
+    negotiation_fails: Arc<Mutex<bool>>,
+    /// Body limit the server reports, instead of the Linux default
+    max_body_length: Arc<Mutex<Option<usize>>>,
+    /// Title limit the server reports, instead of the Linux default
+    max_title_length: Arc<Mutex<Option<usize>>>,
+}
+
+type BoxedResult<'a, T> =
+    std::pin::Pin<Box<dyn std::future::Future<Output = NotificationResult<T>> + Send + 'a>>;
+
+impl StubBackend {
+    /// Native ID of the `count`th delivery, once it happened
+    async fn wait_for_delivery(&self, count: usize) -> String {
+        loop {
+            if self.delivered.lock().unwrap().len() >= count {
+                return format!("stub-{}", count);
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    fn interact(&self, native_id: &str, kind: PlatformInteractionKind) {
+        let sender = self.interactions.lock().unwrap().clone().expect("manager subscribed");
+        sender
+            .send(PlatformInteraction {
+                platform: Platform::Linux,
+                native_id: native_id.to_string(),
+                kind,
+            })
+            .expect("listener running");
+    }
+}
+
+impl PlatformBackend for StubBackend {
+    fn negotiate_capabilities(&self) -> BoxedResult<'_, PlatformCapabilities> {
+        *self.negotiations.lock().unwrap() += 1;
+        if *self.negotiation_fails.lock().unwrap() {
+            return Box::pin(async {
+                Err(NotificationError::PlatformError {
+                    platform: Platform::Linux,
+                    kind: ErrorKind::Timeout,
+                    error_code: Some(1010),
+                    message: "GetCapabilities timed out".to_string(),
+                    source: None,
+                })
+            });
+        }
+        let max_body_length = self
+            .max_body_length
+            .lock()
+            .unwrap()
+            .or(Platform::Linux.default_capabilities().max_body_length);
+        let max_title_length = self
+            .max_title_length
+            .lock()
+            .unwrap()
+            .or(Platform::Linux.default_capabilities().max_title_length);
+        // Replaces notifications in place, like the D-Bus backend
+        Box::pin(async move {
+            Ok(PlatformCapabilities {
+                supports_update_content: true,
+                max_body_length,
+                max_title_length,
+                ..Platform::Linux.default_capabilities()
+            })
+        })
+    }
+
+    fn deliver_notification(&self, request: &NotificationRequest) -> BoxedResult<'_, DeliveryReceipt> {
+        let content = request.content.clone();
+        self.apps.lock().unwrap().push(("deliver", request.app.clone()));
+        if let Some(kind) = *self.failure.lock().unwrap() {
+            *self.failed_attempts.lock().unwrap() += 1;
+            return Box::pin(async move {
+                Err(NotificationError::PlatformError {
+                    platform: Platform::Linux,
+                    kind,
+                    error_code: None,
+                    message: "Notification server rejected the request".to_string(),
+                    source: None,
+                })
+            });
+        }
+        Box::pin(async move {
+            let mut delivered = self.delivered.lock().unwrap();
+            delivered.push(content);
+            Ok(DeliveryReceipt {
+                platform: Platform::Linux,
+                native_id: format!("stub-{}", delivered.len()),
+                delivered_at: std::time::SystemTime::now(),
+                delivery_latency: Duration::ZERO,
+                receipt_id: format!("receipt-{}", delivered.len()),
+                metadata: HashMap::new(),
+            })
+        })
+    }
+
+    fn update_notification(
+        &self,
+        _id: &str,
+        _update: &NotificationUpdate,
+        _app: &AppIdentity,
+    ) -> BoxedResult<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn cancel_notification(&self, id: &str, app: &AppIdentity) -> BoxedResult<'_, ()> {
+        self.cancelled.lock().unwrap().push(id.to_string());
+        self.apps.lock().unwrap().push(("cancel", app.clone()));
+        Box::pin(async { Ok(()) })
+    }
+
+    fn request_authorization(&self) -> BoxedResult<'_, bool> {
+        Box::pin(async { Ok(true) })
+    }
+
+    fn subscribe_interactions(&self) -> Option<tokio::sync::broadcast::Receiver<PlatformInteraction>> {
+        let (sender, receiver) = tokio::sync::broadcast::channel(16);
+        *self.interactions.lock().unwrap() = Some(sender);
+        Some(receiver)
+    }
+}
+
+fn stub_manager() -> (NotificationManager, StubBackend) {
+    let backend = StubBackend::default();
+    let mut backends: HashMap<Platform, Box<dyn PlatformBackend>> = HashMap::new();
+    backends.insert(Platform::Linux, Box::new(backend.clone()));
+    let manager = NotificationManager::try_with_platform_manager(PlatformManager::with_backends(backends))
+        .expect("tests run inside a tokio runtime");
+    (manager, backend)
+}
+
+#[tokio::test]
+async fn test_ask_returns_choice_and_withdraws_notification() {
+    let (manager, backend) = stub_manager();
+    let question = Question::new("Delete 3 branches?").with_platforms(vec![Platform::Linux]);
+    let choices = vec![
+        Choice::new("delete", "Delete").destructive(),
+        Choice::new("keep", "Keep").deny(),
+    ];
+
+    let answerer = backend.clone();
+    let (answer, _) = tokio::join!(
+        manager.ask(question, choices, Duration::from_secs(5)),
+        async move {
+            let native_id = answerer.wait_for_delivery(1).await;
+            answerer.interact(&native_id, PlatformInteractionKind::Action(ActionId::new("delete")));
+        }
+    );
+    assert_eq!(answer.expect("question answered"), Answer::Choice(ActionId::new("delete")));
+
+    let delivered = backend.delivered.lock().unwrap()[0].clone();
+    let styles: Vec<_> = delivered
+        .interactions
+        .actions
+        .iter()
+        .map(|action| (action.id.as_str().to_string(), action.style))
+        .collect();
+    assert_eq!(
+        styles,
+        vec![
+            ("delete".to_string(), ActionStyle::Destructive),
+            ("keep".to_string(), ActionStyle::Default),
+        ]
+    );
+    assert_eq!(*backend.cancelled.lock().unwrap(), vec!["stub-1".to_string()]);
+}
+
+#[tokio::test]
+async fn test_ask_text_reply_denial_and_timeout() {
+    let (manager, backend) = stub_manager();
+    let question = Question::new("Commit message?")
+        .with_text_reply("Describe the change")
+        .with_platforms(vec![Platform::Linux]);
+
+    let answerer = backend.clone();
+    let (answer, _) = tokio::join!(
+        manager.ask(question, vec![Choice::new("skip", "Skip").deny()], Duration::from_secs(5)),
+        async move {
+            let native_id = answerer.wait_for_delivery(1).await;
+            // Actions that aren't choices are ignored
+            answerer.interact(&native_id, PlatformInteractionKind::Action(ActionId::new("default")));
+            answerer.interact(
+                &native_id,
+                PlatformInteractionKind::Reply { input_id: None, text: "Fix typo".to_string() },
+            );
+        }
+    );
+    assert_eq!(answer.expect("question answered"), Answer::Text("Fix typo".to_string()));
+    assert!(matches!(
+        backend.delivered.lock().unwrap()[0].interactions.inputs.as_slice(),
+        [NotificationInput::Text { placeholder, .. }] if placeholder == "Describe the change"
+    ));
+
+    let answerer = backend.clone();
+    let (answer, _) = tokio::join!(
+        manager.ask(
+            Question::new("Push?").with_platforms(vec![Platform::Linux]),
+            vec![Choice::new("push", "Push"), Choice::new("cancel", "Cancel").deny()],
+            Duration::from_secs(5),
+        ),
+        async move {
+            let native_id = answerer.wait_for_delivery(2).await;
+            answerer.interact(&native_id, PlatformInteractionKind::Action(ActionId::new("cancel")));
+        }
+    );
+    assert_eq!(
+        answer.expect("question answered"),
+        Answer::Denied { choice: Some(ActionId::new("cancel")) }
+    );
+
+    let answer = manager
+        .ask(
+            Question::new("Still there?").with_platforms(vec![Platform::Linux]),
+            vec![Choice::new("yes", "Yes")],
+            Duration::from_millis(200),
+        )
+        .await;
+    assert_eq!(answer.expect("timeout is an answer"), Answer::TimedOut);
+    assert_eq!(backend.cancelled.lock().unwrap().len(), 3);
+}
+
+/// HTTP server answering with `statuses` in turn (the last one repeats), recording raw requests
+async fn spawn_callback_server(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<String>>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let address = listener.local_addr().expect("listener should have an address");
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let server_requests = Arc::clone(&requests);
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            loop {
+                if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                    let head = String::from_utf8_lossy(&request[..end]).to_ascii_lowercase();
+                    let length = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .and_then(|value| value.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+                match stream.read(&mut buffer).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buffer[..n]),
+                }
+            }
+            let status = {
+                let mut requests = server_requests.lock().unwrap();
+                requests.push(String::from_utf8_lossy(&request).into_owned());
+                statuses[(requests.len() - 1).min(statuses.len() - 1)]
+            };
+
+            let response = format!(
+                "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        }
+    });
+
+    (format!("http://{}", address), requests)
+}
+
+/// Outcomes recorded for a notification's action presses, once there are `count`
+async fn action_outcomes(handle: &NotificationHandle, count: usize) -> Vec<InteractionOutcome> {
+    for _ in 0..200 {
+        let outcomes: Vec<_> = handle
+            .analytics()
+            .await
+            .expect("notification tracked")
+            .user_behavior
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.interaction_type == InteractionType::ActionPressed)
+            .map(|interaction| interaction.outcome.clone())
+            .collect();
+        if outcomes.len() >= count {
+            return outcomes;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("action outcomes were not recorded");
+}
+
+fn plain_action(id: &str, payload: Option<ActionPayload>) -> NotificationAction {
+    NotificationAction {
+        id: ActionId::new(id),
+        label: id.to_string(),
+        icon: None,
+        style: ActionStyle::Default,
+        activation_type: ActivationType::Background,
+        url: None,
+        payload,
+        confirmation: None,
+    }
+}
+
+#[tokio::test]
+async fn test_action_handlers_run_with_timeout_and_record_outcomes() {
+    let (manager, _backend) = stub_manager();
+    let handlers = manager.action_handlers();
+    handlers.set_timeout(Duration::from_millis(100));
+
+    let deployed = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&deployed);
+    handlers.register("deploy", move |context: ActionContext| {
+        let seen = Arc::clone(&seen);
+        async move {
+            seen.lock().unwrap().push(context.data().and_then(|data| data.get("env").cloned()));
+            Ok(())
+        }
+    });
+    handlers.register("broken", |_| async {
+        Err(NotificationError::ValidationError {
+            field: "action".to_string(),
+            message: "nope".to_string(),
+        })
+    });
+    handlers.register("slow", |_| async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        Ok(())
+    });
+
+    let notification = NotificationBuilder::new()
+        .with_title("Release ready")
+        .with_platforms(vec![Platform::Linux])
+        .with_action(plain_action(
+            "deploy",
+            Some(ActionPayload {
+                data: HashMap::from([("env".to_string(), "prod".to_string())]),
+                callback_url: None,
+            }),
+        ))
+        .with_action(plain_action("broken", None))
+        .with_action(plain_action("slow", None))
+        .with_action(plain_action(OPEN_FILE_ACTION, None))
+        .build()
+        .expect("notification should build");
+    let handle = manager.send(notification).await.expect("notification queued");
+
+    for action in ["deploy", "broken", "slow", OPEN_FILE_ACTION, "unhandled"] {
+        manager.report_interaction(
+            handle.id,
+            Platform::Linux,
+            PlatformInteractionKind::Action(ActionId::new(action)),
+        );
+    }
+
+    let outcomes = action_outcomes(&handle, 5).await;
+    assert_eq!(*deployed.lock().unwrap(), vec![Some("prod".to_string())]);
+    assert!(outcomes.contains(&InteractionOutcome::Success));
+    let failures: Vec<_> = outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            InteractionOutcome::Failed { reason } => Some(reason.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(failures.len(), 3, "{:?}", outcomes);
+    assert!(failures.iter().any(|reason| reason.contains("nope")));
+    assert!(failures.iter().any(|reason| reason.contains("action slow")));
+    assert!(failures.iter().any(|reason| reason.contains("needs a file attachment")));
+}
+
+#[tokio::test]
+async fn test_action_callback_posts_payload_and_category_handler() {
+    let (manager, _backend) = stub_manager();
+    manager.webhooks().set_config(WebhookConfig {
+        max_attempts: 2,
+        backoff: BackoffStrategy::Fixed(Duration::from_millis(10)),
+        ..WebhookConfig::default()
+    });
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&handled);
+    manager.action_handlers().register_category("review", move |context: ActionContext| {
+        let seen = Arc::clone(&seen);
+        async move {
+            seen.lock().unwrap().push(context.action_id.to_string());
+            Ok(())
+        }
+    });
+
+    let (ok_url, ok_requests) = spawn_callback_server(vec![200]).await;
+    let (failing_url, _) = spawn_callback_server(vec![500]).await;
+    let callback = |url: &str| ActionPayload {
+        data: HashMap::from([("pr".to_string(), "42".to_string())]),
+        callback_url: Some(Url::parse(url).expect("valid url")),
+    };
+
+    let notification = NotificationBuilder::new()
+        .with_title("Review requested")
+        .with_platforms(vec![Platform::Linux])
+        .with_action(plain_action("approve", Some(callback(&ok_url))))
+        .with_action(plain_action("reject", Some(callback(&failing_url))))
+        .build()
+        .expect("notification should build");
+    let mut notification = notification;
+    notification.content = notification
+        .content
+        .with_category(NotificationCategory::new("review", "Code review"));
+    let handle = manager.send(notification).await.expect("notification queued");
+
+    for action in ["approve", "reject"] {
+        manager.report_interaction(
+            handle.id,
+            Platform::Linux,
+            PlatformInteractionKind::Action(ActionId::new(action)),
+        );
+    }
+
+    let outcomes = action_outcomes(&handle, 2).await;
+    assert!(outcomes.contains(&InteractionOutcome::Success));
+    // The category handler ran, but the callback failed and was dead-lettered
+    assert!(outcomes.contains(&InteractionOutcome::Partial));
+    assert_eq!(manager.webhooks().dead_letters().len(), 1);
+    let mut handled = handled.lock().unwrap().clone();
+    handled.sort();
+    assert_eq!(handled, vec!["approve".to_string(), "reject".to_string()]);
+
+    let requests = ok_requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].starts_with("POST / HTTP/1.1"));
+    assert!(requests[0].to_ascii_lowercase().contains("content-type: application/json"));
+    assert!(requests[0].contains("\"action_id\":\"approve\""));
+    assert!(requests[0].contains("\"data\":{\"pr\":\"42\"}"));
+}
+
+/// Header value (lowercase name) and body of a raw HTTP request
+fn header_and_body<'a>(request: &'a str, header: &str) -> (Option<&'a str>, &'a str) {
+    let (head, body) = request.split_once("\r\n\r\n").expect("complete request");
+    let value = head.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case(header).then(|| value.trim())
+    });
+    (value, body)
+}
+
+#[test]
+fn test_webhook_signature_and_traceparent_format() {
+    // RFC 4231-style known answer
+    assert_eq!(
+        sign_webhook(b"key", b"The quick brown fox jumps over the lazy dog"),
+        "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+    );
+
+    let span = TraceSpan::new("notify");
+    let traceparent = span.traceparent();
+    let parts: Vec<_> = traceparent.split('-').collect();
+    assert_eq!(parts.len(), 4);
+    assert_eq!((parts[0], parts[3]), ("00", "01"));
+    assert_eq!(parts[1], format!("{:032x}", span.trace_id.as_u128()));
+    assert_eq!(parts[2], format!("{:016x}", span.span_id.as_u64()));
+}
+
+#[tokio::test]
+async fn test_action_webhook_is_signed_traced_and_retried() {
+    let (manager, _backend) = stub_manager();
+    manager.webhooks().set_config(WebhookConfig {
+        secret: Some(b"s3cret".to_vec()),
+        max_attempts: 3,
+        backoff: BackoffStrategy::Fixed(Duration::from_millis(10)),
+        request_timeout: Duration::from_secs(2),
+    });
+    let (url, requests) = spawn_callback_server(vec![503, 503, 200]).await;
+
+    let mut notification = NotificationBuilder::new()
+        .with_title("Deploy finished")
+        .with_platforms(vec![Platform::Linux])
+        .with_action(plain_action(
+            "comment",
+            Some(ActionPayload {
+                data: HashMap::from([("build".to_string(), "7".to_string())]),
+                callback_url: Some(Url::parse(&url).expect("valid url")),
+            }),
+        ))
+        .build()
+        .expect("notification should build");
+    let span = TraceSpan::new("deploy");
+    notification.identity = notification.identity.with_trace_span(span.clone());
+    let correlation_id = notification.identity.correlation_id.clone();
+    let handle = manager.send(notification).await.expect("notification queued");
+
+    manager.report_interaction(
+        handle.id,
+        Platform::Linux,
+        PlatformInteractionKind::Reply {
+            input_id: Some(InputId::new("comment")),
+            text: "ship it".to_string(),
+        },
+    );
+
+    for _ in 0..200 {
+        if requests.lock().unwrap().len() >= 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 3, "two failures, then success");
+    assert!(manager.webhooks().dead_letters().is_empty());
+
+    let (signature, body) = header_and_body(&requests[2], "x-notification-signature");
+    assert_eq!(signature, Some(format!("sha256={}", sign_webhook(b"s3cret", body.as_bytes())).as_str()));
+    assert_eq!(header_and_body(&requests[2], "traceparent").0, Some(span.traceparent().as_str()));
+    assert_eq!(header_and_body(&requests[2], "x-correlation-id").0, Some(correlation_id.as_str()));
+
+    let envelope: WebhookEnvelope = serde_json::from_str(body).expect("JSON envelope");
+    assert_eq!(envelope.notification_id, handle.id.to_string());
+    assert_eq!(envelope.correlation_id, correlation_id.to_string());
+    assert_eq!(envelope.action_id, "comment");
+    assert_eq!(envelope.inputs, HashMap::from([("comment".to_string(), "ship it".to_string())]));
+    assert_eq!(envelope.data, HashMap::from([("build".to_string(), "7".to_string())]));
+    assert!(chrono::DateTime::parse_from_rfc3339(&envelope.timestamp).is_ok());
+}
+
+#[tokio::test]
+async fn test_undeliverable_webhooks_are_dead_lettered_and_redelivered() {
+    let webhooks = Webhooks::new();
+    webhooks.set_config(WebhookConfig {
+        max_attempts: 2,
+        backoff: BackoffStrategy::Fixed(Duration::from_millis(10)),
+        ..WebhookConfig::default()
+    });
+    let (failing_url, failing_requests) = spawn_callback_server(vec![500]).await;
+    let (rejecting_url, rejecting_requests) = spawn_callback_server(vec![400]).await;
+    let envelope = WebhookEnvelope::new("n-1", &CorrelationId::generate(), "approve");
+
+    let failing = Url::parse(&failing_url).expect("valid url");
+    assert!(webhooks.deliver(&failing, envelope.clone(), None).await.is_err());
+    assert_eq!(failing_requests.lock().unwrap().len(), 2);
+
+    // Client errors are not retried
+    let rejecting = Url::parse(&rejecting_url).expect("valid url");
+    assert!(webhooks.deliver(&rejecting, envelope.clone(), None).await.is_err());
+    assert_eq!(rejecting_requests.lock().unwrap().len(), 1);
+
+    let letters = webhooks.dead_letters().drain();
+    assert_eq!(letters.len(), 2);
+    assert_eq!((letters[0].attempts, letters[1].attempts), (2, 1));
+    assert_eq!(letters[0].envelope, envelope);
+    assert!(letters[1].last_error.contains("400"));
+    assert!(webhooks.dead_letters().is_empty());
+
+    // Redelivery once the receiver is back
+    let (ok_url, ok_requests) = spawn_callback_server(vec![200]).await;
+    let mut letter = letters[0].clone();
+    letter.url = Url::parse(&ok_url).expect("valid url");
+    webhooks.redeliver(letter).await.expect("redelivered");
+    assert_eq!(ok_requests.lock().unwrap().len(), 1);
+    assert!(webhooks.dead_letters().is_empty());
+}
+
+/// Report an interaction on a stub notification once the manager knows its native ID
+async fn interact_when_tracked(
+    manager: &NotificationManager,
+    backend: &StubBackend,
+    native_id: &str,
+    kind: PlatformInteractionKind,
+) -> NotificationId {
+    let mut events = manager.subscribe_interactions();
+    for _ in 0..100 {
+        backend.interact(native_id, kind.clone());
+        if let Ok(Ok(event)) = tokio::time::timeout(Duration::from_millis(20), events.recv()).await {
+            return event.notification_id;
+        }
+    }
+    panic!("interaction with {} was not recorded", native_id);
+}
+
+/// Confirmation metadata and outcomes of a notification's action presses, once there are `count`
+async fn confirmation_outcomes(
+    handle: &NotificationHandle,
+    count: usize,
+) -> Vec<(Option<String>, InteractionOutcome)> {
+    for _ in 0..200 {
+        let outcomes: Vec<_> = handle
+            .analytics()
+            .await
+            .expect("notification tracked")
+            .user_behavior
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.interaction_type == InteractionType::ActionPressed)
+            .map(|interaction| {
+                (interaction.metadata.get(CONFIRMATION_KEY).cloned(), interaction.outcome.clone())
+            })
+            .collect();
+        if outcomes.len() >= count {
+            return outcomes;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("action outcomes were not recorded");
+}
+
+fn confirmed_action(id: &str) -> NotificationAction {
+    NotificationAction {
+        style: ActionStyle::Destructive,
+        confirmation: Some(ActionConfirmation {
+            title: "Delete branch?".to_string(),
+            message: "This cannot be undone".to_string(),
+            confirm_label: "Delete".to_string(),
+            cancel_label: "Keep".to_string(),
+        }),
+        ..plain_action(id, None)
+    }
+}
+
+#[tokio::test]
+async fn test_confirmed_action_runs_only_after_confirm() {
+    let (manager, backend) = stub_manager();
+    let runs = Arc::new(Mutex::new(0));
+    let counter = Arc::clone(&runs);
+    manager.action_handlers().register("delete", move |_| {
+        let counter = Arc::clone(&counter);
+        async move {
+            *counter.lock().unwrap() += 1;
+            Ok(())
+        }
+    });
+
+    let notification = NotificationBuilder::new()
+        .with_title("Branch merged")
+        .with_platforms(vec![Platform::Linux])
+        .with_action(confirmed_action("delete"))
+        .build()
+        .expect("notification should build");
+    let handle = manager.send(notification).await.expect("notification queued");
+
+    let native_id = backend.wait_for_delivery(1).await;
+    let pressed = interact_when_tracked(
+        &manager,
+        &backend,
+        &native_id,
+        PlatformInteractionKind::Action(ActionId::new("delete")),
+    )
+    .await;
+    assert_eq!(pressed, handle.id);
+
+    let confirmation_native_id = backend.wait_for_delivery(2).await;
+    assert_eq!(*runs.lock().unwrap(), 0, "handler waits for the confirmation");
+    let confirmation = backend.delivered.lock().unwrap()[1].clone();
+    assert_eq!(confirmation.title, "Delete branch?");
+    assert_eq!(confirmation.body.to_plain_text(), "This cannot be undone");
+    let buttons: Vec<_> = confirmation
+        .interactions
+        .actions
+        .iter()
+        .map(|action| (action.id.as_str().to_string(), action.label.clone(), action.style))
+        .collect();
+    assert_eq!(
+        buttons,
+        vec![
+            (CONFIRM_ACTION.to_string(), "Delete".to_string(), ActionStyle::Destructive),
+            (CANCEL_ACTION.to_string(), "Keep".to_string(), ActionStyle::Default),
+        ]
+    );
+    // Linux replaces notifications, so the confirmation takes the original's place
+    let original_id = handle.id.to_string();
+    assert_eq!(confirmation.replaces(), Some(original_id.as_str()));
+
+    let answered = interact_when_tracked(
+        &manager,
+        &backend,
+        &confirmation_native_id,
+        PlatformInteractionKind::Action(ActionId::new(CONFIRM_ACTION)),
+    )
+    .await;
+    assert_ne!(answered, handle.id);
+
+    let outcomes = confirmation_outcomes(&handle, 1).await;
+    assert_eq!(outcomes, vec![(Some("confirmed".to_string()), InteractionOutcome::Success)]);
+    assert_eq!(*runs.lock().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_cancelled_and_expired_confirmations_skip_the_handler() {
+    let (manager, backend) = stub_manager();
+    let runs = Arc::new(Mutex::new(0));
+    let counter = Arc::clone(&runs);
+    manager.action_handlers().register("delete", move |_| {
+        let counter = Arc::clone(&counter);
+        async move {
+            *counter.lock().unwrap() += 1;
+            Ok(())
+        }
+    });
+    manager.action_handlers().set_confirmation_timeout(Duration::from_millis(200));
+
+    let notification = NotificationBuilder::new()
+        .with_title("Branch merged")
+        .with_platforms(vec![Platform::Linux])
+        .with_action(confirmed_action("delete"))
+        .build()
+        .expect("notification should build");
+    let handle = manager.send(notification).await.expect("notification queued");
+    backend.wait_for_delivery(1).await;
+
+    manager.report_interaction(
+        handle.id,
+        Platform::Linux,
+        PlatformInteractionKind::Action(ActionId::new("delete")),
+    );
+    let confirmation_native_id = backend.wait_for_delivery(2).await;
+    interact_when_tracked(
+        &manager,
+        &backend,
+        &confirmation_native_id,
+        PlatformInteractionKind::Action(ActionId::new(CANCEL_ACTION)),
+    )
+    .await;
+    let outcomes = confirmation_outcomes(&handle, 1).await;
+    assert_eq!(outcomes, vec![(Some("cancelled".to_string()), InteractionOutcome::Abandoned)]);
+
+    // Left unanswered, the confirmation expires and is withdrawn
+    manager.report_interaction(
+        handle.id,
+        Platform::Linux,
+        PlatformInteractionKind::Action(ActionId::new("delete")),
+    );
+    let expiring_native_id = backend.wait_for_delivery(3).await;
+    let outcomes = confirmation_outcomes(&handle, 2).await;
+    assert_eq!(outcomes[1], (Some("expired".to_string()), InteractionOutcome::Abandoned));
+    assert!(backend.cancelled.lock().unwrap().contains(&expiring_native_id));
+    assert_eq!(*runs.lock().unwrap(), 0);
+}
+
+#[test]
+fn test_desktop_entry_installs_idempotently_with_scheme_handler() {
+    let data_home = tempfile::tempdir().expect("temp dir");
+    let entry = DesktopEntry::from_bundle()
+        .with_exec("/opt/My App/bin/notify")
+        .with_url_scheme("Kodegen");
+
+    let contents = entry.render();
+    assert!(contents.starts_with("[Desktop Entry]\nType=Application\n"));
+    assert!(contents.contains(&format!("Name={}\n", BUNDLE_NAME)));
+    assert!(contents.contains("Exec=\"/opt/My App/bin/notify\" %u\n"));
+    assert!(contents.contains(&format!("Icon={}\n", BUNDLE_IDENTIFIER)));
+    assert!(contents.contains("MimeType=x-scheme-handler/kodegen;\n"));
+    assert!(contents.contains("Categories=Development;\n"));
+
+    let installed = entry.install_into(data_home.path()).expect("installed");
+    assert!(installed.changed);
+    assert_eq!(
+        installed.desktop_file,
+        data_home.path().join("applications/ai.kodegen.kodegen.desktop")
+    );
+    assert_eq!(std::fs::read_to_string(&installed.desktop_file).unwrap(), contents);
+    assert_eq!(installed.icon_files.len(), 5);
+    assert!(installed.icon_files.contains(
+        &data_home.path().join("icons/hicolor/128x128/apps/ai.kodegen.kodegen.png")
+    ));
+    assert!(installed.icon_files.iter().all(|icon| icon.is_file()));
+
+    // Nothing to do the second time; a changed entry is rewritten
+    assert!(!entry.install_into(data_home.path()).expect("installed").changed);
+    let updated = entry.clone().with_url_scheme("kodegen-dev");
+    assert!(updated.install_into(data_home.path()).expect("installed").changed);
+    assert!(
+        std::fs::read_to_string(&installed.desktop_file)
+            .unwrap()
+            .contains("MimeType=x-scheme-handler/kodegen;x-scheme-handler/kodegen-dev;\n")
+    );
+
+    let invalid = DesktopEntry::from_bundle().with_url_scheme("1nvalid scheme");
+    assert!(matches!(
+        invalid.install_into(data_home.path()),
+        Err(DesktopEntryError::InvalidScheme(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_app_identity_is_used_for_delivery_and_cancel() {
+    let (manager, backend) = stub_manager();
+    assert_eq!(manager.app_identity(), AppIdentity::default());
+    assert_eq!(manager.app_identity().desktop_entry_id(), BUNDLE_IDENTIFIER);
+
+    let host = AppIdentity::new("Host", "com.example.host").with_version("2.1.0");
+    let manager = manager.with_app_identity(host.clone());
+    let linter = AppIdentity::new("Linter", "com.example.linter")
+        .with_desktop_entry("linter")
+        .with_icon("linter-icon");
+    assert_eq!(linter.desktop_entry_id(), "linter");
+
+    let hosted = NotificationBuilder::new()
+        .with_title("Build finished")
+        .with_platforms(vec![Platform::Linux])
+        .build()
+        .expect("notification should build");
+    let on_behalf = NotificationBuilder::new()
+        .with_title("3 lint warnings")
+        .with_platforms(vec![Platform::Linux])
+        .with_app_identity(linter.clone())
+        .build()
+        .expect("notification should build");
+    assert_eq!(on_behalf.identity.creator_context.as_ref().unwrap().service_name, "com.example.linter");
+    // Only a creator set explicitly is kept when the manager fills in its app
+    assert_eq!(hosted.identity.creator_context, None);
+    let created = NotificationBuilder::new()
+        .with_title("Build finished")
+        .with_creator_context(CreatorContext::new("native-notifications"))
+        .with_app_identity(linter.clone())
+        .build()
+        .expect("notification should build");
+    assert_eq!(created.identity.creator_context, Some(CreatorContext::new("native-notifications")));
+
+    manager.send(hosted).await.expect("notification queued");
+    backend.wait_for_delivery(1).await;
+    manager.send(on_behalf).await.expect("notification queued");
+    backend.wait_for_delivery(2).await;
+
+    // Withdrawn on timeout, as the app it was shown as
+    let answer = manager
+        .ask(
+            Question::new("Fix automatically?").with_platforms(vec![Platform::Linux]),
+            vec![Choice::new("fix", "Fix")],
+            Duration::from_millis(100),
+        )
+        .await;
+    assert_eq!(answer.expect("timeout is an answer"), Answer::TimedOut);
+
+    let apps = backend.apps.lock().unwrap().clone();
+    assert_eq!(
+        apps,
+        vec![
+            ("deliver", host.clone()),
+            ("deliver", linter),
+            ("deliver", host.clone()),
+            ("cancel", host),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_server_restart_withdraws_notifications_and_renegotiates() {
+    let (manager, backend) = stub_manager();
+    let notification = NotificationBuilder::new()
+        .with_title("Tests running")
+        .with_platforms(vec![Platform::Linux])
+        .build()
+        .expect("notification should build");
+    let handle = manager.send(notification).await.expect("notification queued");
+    let native_id = backend.wait_for_delivery(1).await;
+    let negotiated = *backend.negotiations.lock().unwrap();
+
+    // The old server's IDs are reported withdrawn, then the server change itself
+    let withdrawn = interact_when_tracked(&manager, &backend, &native_id, PlatformInteractionKind::Withdrawn).await;
+    assert_eq!(withdrawn, handle.id);
+    backend.interact("", PlatformInteractionKind::ServerChanged);
+
+    let lifecycle = handle.lifecycle().await.expect("notification tracked");
+    assert_eq!(lifecycle.state, NotificationState::Expired);
+    let linux = &lifecycle.platform_states[&Platform::Linux];
+    assert_eq!(linux.native_id, None);
+    assert_eq!(linux.status, PlatformDeliveryStatus::Cancelled);
+    assert!(lifecycle.state_history.iter().any(|transition| {
+        transition.to_state == NotificationState::Expired
+            && matches!(transition.reason, TransitionReason::SystemEvent)
+    }));
+
+    // The ID may be handed out again by the new server, so it no longer maps here
+    let mut events = manager.subscribe_interactions();
+    backend.interact(&native_id, PlatformInteractionKind::Dismissed);
+    assert!(tokio::time::timeout(Duration::from_millis(100), events.recv()).await.is_err());
+
+    // Capabilities are negotiated again with the new server
+    for _ in 0..100 {
+        manager
+            .platform_manager()
+            .get_capabilities(Platform::Linux)
+            .await
+            .expect("capabilities negotiated");
+        if *backend.negotiations.lock().unwrap() > negotiated {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("capabilities were not renegotiated after the server changed");
+}
+
+#[tokio::test]
+async fn test_forced_close_expires_notification() {
+    let (manager, backend) = stub_manager();
+    let notification = NotificationBuilder::new()
+        .with_title("Build finished")
+        .with_platforms(vec![Platform::Linux])
+        .build()
+        .expect("notification should build");
+    let handle = manager.send(notification).await.expect("notification queued");
+    let native_id = backend.wait_for_delivery(1).await;
+
+    // Closed by the backend once its TTL ran out, rather than by the user
+    let expired = interact_when_tracked(&manager, &backend, &native_id, PlatformInteractionKind::Expired).await;
+    assert_eq!(expired, handle.id);
+
+    let lifecycle = handle.lifecycle().await.expect("notification tracked");
+    assert_eq!(lifecycle.state, NotificationState::Expired);
+    assert_eq!(lifecycle.platform_states[&Platform::Linux].native_id, None);
+    assert!(lifecycle.state_history.iter().any(|transition| {
+        transition.to_state == NotificationState::Expired
+            && matches!(transition.reason, TransitionReason::Expiration)
+    }));
+    assert!(handle.interaction(Duration::from_millis(50)).await.is_none());
+}
+
+#[test]
+fn test_server_quirk_profiles_and_overrides() {
+    let gnome = ServerInfo::new("gnome-shell", "GNOME", "46.0", "1.2");
+    let plasma = ServerInfo::new("Plasma", "KDE", "6.0", "1.2");
+    let mako = ServerInfo::new("mako", "emersion", "1.8", "1.2");
+    let dunst = ServerInfo::new("dunst", "knopwob", "1.11", "1.2");
+    assert_eq!(gnome.kind(), ServerKind::Gnome);
+    assert_eq!(plasma.kind(), ServerKind::Kde);
+    assert_eq!(ServerInfo::unknown().kind(), ServerKind::Other);
+
+    let quirks = ServerQuirks::for_server(&gnome);
+    assert!(quirks.enforce_timeout);
+    assert_eq!(quirks.max_actions, Some(3));
+    assert!(!quirks.markup.hyperlinks && !quirks.markup.extended);
+    assert!(ServerQuirks::for_server(&plasma).kde_urls);
+    assert!(ServerQuirks::for_server(&mako).actions_in_menu);
+    assert!(!ServerQuirks::for_server(&dunst).markup.hyperlinks);
+    assert_eq!(ServerQuirks::for_server(&ServerInfo::unknown()), ServerQuirks::default());
+
+    let quirks = ServerQuirks {
+        extra_capabilities: vec!["persistence".to_string()],
+        ignored_capabilities: vec!["sound".to_string()],
+        ..ServerQuirks::default()
+    };
+    assert_eq!(
+        quirks.adjust_capabilities(vec!["actions".to_string(), "sound".to_string()]),
+        vec!["actions".to_string(), "persistence".to_string()]
+    );
+
+    // A mako configured with action buttons
+    set_quirk_override(|info, quirks| {
+        if info.kind() == ServerKind::Mako {
+            quirks.actions_in_menu = false;
+        }
+    });
+    assert!(!server_quirks(&mako).actions_in_menu);
+    assert_eq!(server_quirks(&gnome), ServerQuirks::for_server(&gnome));
+    clear_quirk_override();
+    assert!(server_quirks(&mako).actions_in_menu);
+
+    let metadata: HashMap<_, _> = dunst.receipt_metadata().into_iter().collect();
+    assert_eq!(metadata["server_name"], "dunst");
+    assert_eq!(metadata["server_spec_version"], "1.2");
+}
+
+#[tokio::test]
+async fn test_dbus_and_resource_errors_are_classified() {
+    assert_eq!(
+        dbus_error_code("org.freedesktop.DBus.Error.ServiceUnknown"),
+        Some((1001, ErrorKind::ServiceUnavailable))
+    );
+    assert_eq!(
+        dbus_error_code("org.freedesktop.DBus.Error.InvalidArgs"),
+        Some((1050, ErrorKind::InvalidRequest))
+    );
+    assert_eq!(dbus_error_code("org.example.Error.Custom"), None);
+    // Codes are stable identifiers, so no two names may share one
+    let mut codes: Vec<i32> = DBUS_ERROR_CODES.iter().map(|(_, code, _)| *code).collect();
+    codes.sort_unstable();
+    codes.dedup();
+    assert_eq!(codes.len(), DBUS_ERROR_CODES.len());
+
+    let missing = ImageData::File(std::path::PathBuf::from("/nonexistent/notify-icon.png"));
+    let error = resolve_image_to_path(&missing).await.expect_err("missing file should fail");
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+    assert!(!error.is_retryable());
+}
+
+#[tokio::test]
+async fn test_expired_notifications_are_closed_on_the_platform() {
+    let (manager, backend) = stub_manager();
+    let short_lived = NotificationBuilder::new()
+        .with_title("Deploy starting")
+        .with_platforms(vec![Platform::Linux])
+        .with_expiration(ExpirationPolicy {
+            ttl: Some(Duration::from_millis(300)),
+            ..ExpirationPolicy::default()
+        })
+        .build()
+        .expect("notification should build");
+    let unanswered = NotificationBuilder::new()
+        .with_title("Retry the flaky test?")
+        .with_platforms(vec![Platform::Linux])
+        .with_action(plain_action("retry", None))
+        .with_expiration(ExpirationPolicy {
+            ttl: None,
+            interaction_timeout: Duration::from_millis(300),
+            ..ExpirationPolicy::default()
+        })
+        .build()
+        .expect("notification should build");
+    let kept = NotificationBuilder::new()
+        .with_title("Deploy finished")
+        .with_platforms(vec![Platform::Linux])
+        .with_expiration(ExpirationPolicy {
+            ttl: Some(Duration::from_millis(300)),
+            cleanup_on_expiry: false,
+            ..ExpirationPolicy::default()
+        })
+        .build()
+        .expect("notification should build");
+
+    let short_lived = manager.send(short_lived).await.expect("notification queued");
+    let short_lived_native = backend.wait_for_delivery(1).await;
+    let unanswered = manager.send(unanswered).await.expect("notification queued");
+    let unanswered_native = backend.wait_for_delivery(2).await;
+    let kept = manager.send(kept).await.expect("notification queued");
+    let kept_native = backend.wait_for_delivery(3).await;
+
+    for _ in 0..200 {
+        if backend.cancelled.lock().unwrap().len() >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    // Give the notification kept on screen the same time to be closed wrongly
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let mut cancelled = backend.cancelled.lock().unwrap().clone();
+    cancelled.sort();
+    assert_eq!(cancelled, vec![short_lived_native, unanswered_native]);
+
+    for handle in [&short_lived, &unanswered] {
+        let lifecycle = handle.lifecycle().await.expect("notification tracked");
+        assert_eq!(lifecycle.state, NotificationState::Expired);
+        assert_eq!(
+            lifecycle.platform_states[&Platform::Linux].status,
+            PlatformDeliveryStatus::Cancelled
+        );
+    }
+    let lifecycle = kept.lifecycle().await.expect("notification tracked");
+    assert_eq!(lifecycle.state, NotificationState::Expired);
+    assert_eq!(
+        lifecycle.platform_states[&Platform::Linux].native_id.as_deref(),
+        Some(kept_native.as_str())
+    );
+    assert_eq!(
+        lifecycle.platform_states[&Platform::Linux].status,
+        PlatformDeliveryStatus::Delivered
+    );
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_or_aborts_unfinished_notifications() {
+    fn note(title: &str) -> Notification {
+        NotificationBuilder::new()
+            .with_title(title)
+            .with_platforms(vec![Platform::Linux])
+            .build()
+            .expect("notification should build")
+    }
+
+    // Drain: everything queued is delivered and stays on screen
+    let (manager, backend) = stub_manager();
+    let mut handles = Vec::new();
+    for title in ["Build started", "Tests passed", "Deploy queued"] {
+        handles.push(manager.send(note(title)).await.expect("notification queued"));
+    }
+    let result = manager
+        .shutdown_with_policy(ShutdownPolicy::Drain, Duration::from_secs(5))
+        .await;
+    assert_eq!(
+        result,
+        ShutdownResult {
+            workers: WorkerShutdown::Clean,
+            delivered: 3,
+            failed: 0,
+            cancelled: 0,
+            closed: 0,
+            close_errors: 0,
+        }
+    );
+    assert_eq!(backend.delivered.lock().unwrap().len(), 3);
+    assert!(backend.cancelled.lock().unwrap().is_empty());
+    for handle in &handles {
+        let lifecycle = handle.lifecycle().await.expect("notification tracked");
+        assert_eq!(lifecycle.state, NotificationState::Delivered);
+    }
+
+    // Abort: shown and queued notifications alike are cancelled, shown ones closed
+    let (manager, backend) = stub_manager();
+    let shown = manager.send(note("Build started")).await.expect("notification queued");
+    let shown_native = backend.wait_for_delivery(1).await;
+    let queued = manager.send(note("Deploy queued")).await.expect("notification queued");
+    let result = manager
+        .shutdown_with_policy(ShutdownPolicy::Abort, Duration::from_secs(5))
+        .await;
+    assert_eq!(result.workers, WorkerShutdown::Clean);
+    assert_eq!(result.cancelled, 2);
+    assert_eq!(result.close_errors, 0);
+
+    // The second one may have been delivered before the workers stopped
+    let cancelled = backend.cancelled.lock().unwrap().clone();
+    assert_eq!(result.closed, cancelled.len());
+    assert_eq!(cancelled.len(), backend.delivered.lock().unwrap().len());
+    assert!(cancelled.contains(&shown_native));
+    for handle in [&shown, &queued] {
+        let lifecycle = handle.lifecycle().await.expect("notification tracked");
+        assert_eq!(lifecycle.state, NotificationState::Cancelled);
+        assert!(lifecycle.state_history.iter().any(|transition| {
+            transition.to_state == NotificationState::Cancelled
+                && matches!(transition.reason, TransitionReason::Cancellation)
+        }));
+    }
+    let lifecycle = shown.lifecycle().await.expect("notification tracked");
+    assert_eq!(
+        lifecycle.platform_states[&Platform::Linux].status,
+        PlatformDeliveryStatus::Cancelled
+    );
+}
+
+#[tokio::test]
+async fn test_handle_awaits_delivery_interaction_and_finish() {
+    let (manager, backend) = stub_manager();
+    let notification = NotificationBuilder::new()
+        .with_title("Retry the flaky test?")
+        .with_platforms(vec![Platform::Linux])
+        .with_action(plain_action("retry", None))
+        .build()
+        .expect("notification should build");
+    let handle = manager.send(notification).await.expect("notification queued");
+
+    let receipts = handle.delivered().await.expect("notification delivered");
+    assert_eq!(receipts.len(), 1);
+    assert_eq!(receipts[0].platform, Platform::Linux);
+    assert_eq!(receipts[0].native_id, "stub-1");
+
+    let mut transitions = handle.watch();
+    let (event, _) = tokio::join!(handle.interaction(Duration::from_secs(5)), async {
+        backend.interact("stub-1", PlatformInteractionKind::Action(ActionId::new("retry")));
+    });
+    let event = event.expect("interaction received");
+    assert_eq!(event.notification_id, handle.id);
+    assert_eq!(event.kind, PlatformInteractionKind::Action(ActionId::new("retry")));
+
+    let (finished, _) = tokio::join!(
+        handle.finished(),
+        manager.shutdown_with_policy(ShutdownPolicy::Abort, Duration::from_secs(5))
+    );
+    assert_eq!(finished, NotificationState::Cancelled);
+
+    let mut seen = Vec::new();
+    while let Some(transition) = transitions.next().await {
+        seen.push(transition);
+    }
+    let states: Vec<_> = seen.iter().map(|transition| transition.to_state.clone()).collect();
+    assert_eq!(
+        states,
+        vec![NotificationState::InteractionReceived, NotificationState::Cancelled]
+    );
+    assert!(matches!(seen[1].reason, TransitionReason::Cancellation));
+
+    // Finished notifications resolve right away
+    assert_eq!(handle.finished().await, NotificationState::Cancelled);
+    assert!(handle.watch().next().await.is_none());
+    assert!(handle.interaction(Duration::from_secs(5)).await.is_none());
+    assert_eq!(handle.delivered().await.expect("delivered before cancelling").len(), 1);
+}
+
+#[tokio::test]
+async fn test_send_survives_failed_negotiation_and_targets_without_backend() {
+    let (manager, backend) = stub_manager();
+    *backend.negotiation_fails.lock().unwrap() = true;
+    // Only Linux has a backend; macOS and Windows are skipped when negotiating
+    let notification = NotificationBuilder::new()
+        .with_title("Release tagged")
+        .with_platforms(vec![Platform::Linux, Platform::MacOS, Platform::Windows])
+        .build()
+        .expect("notification should build");
+    let handle = manager.send(notification).await.expect("send skips platforms it couldn't negotiate");
+    backend.wait_for_delivery(1).await;
+
+    assert!(handle.status().await.is_some());
+    // Nothing negotiated yet, so there is nothing to fall back to
+    assert!(manager.platform_manager().get_capabilities(Platform::Linux).await.is_err());
+    assert!(manager.platform_manager().get_capabilities(Platform::Windows).await.is_err());
+}
+
+#[tokio::test]
+async fn test_send_degrades_to_negotiated_features() {
+    // The stub negotiates Linux capabilities, which have no markup
+    let (manager, backend) = stub_manager();
+    let markdown = || {
+        NotificationBuilder::new()
+            .with_title("Deploy finished")
+            .with_body(RichText::Markdown("**3** services updated\n\n- api\n- web".to_string()))
+            .with_platforms(vec![Platform::Linux])
+    };
+
+    // Markup the platforms lack is flattened rather than sent
+    manager
+        .send(markdown().build().expect("notification should build"))
+        .await
+        .expect("markup degrades to plain text");
+    backend.wait_for_delivery(1).await;
+    let delivered = backend.delivered.lock().unwrap()[0].clone();
+    match delivered.body {
+        RichText::Plain(text) => assert!(text.contains("3 services updated") && !text.contains("**")),
+        other => panic!("Expected a plain body, got {:?}", other),
+    }
+
+    // Unless the caller can't do without it
+    let strategy = DegradationStrategy {
+        fail_on_critical_unsupported: true,
+        critical_features: ["markup".to_string()].into(),
+        ..DegradationStrategy::default()
+    };
+    let critical = markdown()
+        .with_degradation_strategy(strategy)
+        .build()
+        .expect("notification should build");
+    match manager.send(critical).await {
+        Err(NotificationError::ValidationError { field, .. }) => assert_eq!(field, "markup"),
+        other => panic!("Expected the critical feature to reject the notification, got {:?}", other.map(|handle| handle.id)),
+    }
+    assert_eq!(backend.delivered.lock().unwrap().len(), 1);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_send_rejects_unknown_system_icons_for_linux() {
+    let (manager, backend) = stub_manager();
+    let icon = MediaAttachment::Image {
+        data: ImageData::SystemIcon("no-such-icon-kodegen-test".to_string()),
+        placement: ImagePlacement::AppIcon,
+        alt_text: None,
+        dimensions: None,
+    };
+    let notification = NotificationBuilder::new()
+        .with_title("Build finished")
+        .with_platforms(vec![Platform::Linux])
+        .with_media(icon)
+        .build()
+        .expect("icon names are only resolved on send");
+    match manager.send(notification).await {
+        Err(NotificationError::ValidationError { message, .. }) => {
+            assert!(message.contains("'no-such-icon-kodegen-test'"), "got {message}");
+        }
+        other => panic!("Expected an unknown icon to fail the send, got {:?}", other.map(|handle| handle.id)),
+    }
+
+    let action = NotificationAction {
+        icon: Some(ActionIcon::System("no-such-action-icon-kodegen-test".to_string())),
+        ..plain_action("open", None)
+    };
+    let notification = NotificationBuilder::new()
+        .with_title("Build finished")
+        .with_platforms(vec![Platform::Linux])
+        .with_action(action)
+        .build()
+        .expect("icon names are only resolved on send");
+    assert!(matches!(
+        manager.send(notification).await,
+        Err(NotificationError::ValidationError { .. })
+    ));
+    assert!(backend.delivered.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_send_truncates_to_negotiated_limits() {
+    let (manager, backend) = stub_manager();
+    *backend.max_body_length.lock().unwrap() = Some(40);
+    let body = "cargo test: 212 passed, 3 failed in crate::lifecycle and crate::platform";
+    let note = |truncation: Option<TruncationPolicy>| {
+        let builder = NotificationBuilder::new()
+            .with_title("Tests failed")
+            .with_body(RichText::plain(body))
+            .with_platforms(vec![Platform::Linux]);
+        match truncation {
+            Some(policy) => builder.with_truncation(policy),
+            None => builder,
+        }
+        .build()
+        .expect("body fits the default Linux limit")
+    };
+
+    // Without a policy the live limit rejects the notification
+    assert!(manager.send(note(None)).await.is_err());
+
+    manager
+        .send(note(Some(TruncationPolicy::default())))
+        .await
+        .expect("notification truncated to the negotiated limit");
+    backend.wait_for_delivery(1).await;
+    let delivered = backend.delivered.lock().unwrap()[0].clone();
+    assert!(delivered.body.to_plain_text().len() <= 40);
+    assert_eq!(delivered.custom_data[TRUNCATED_BODY_KEY], body.len().to_string());
+}
+
+#[tokio::test]
+async fn test_handle_finishes_when_delivery_keeps_failing() {
+    // Permanent failures aren't retried, so the first one is final
+    let (manager, backend) = stub_manager();
+    *backend.failure.lock().unwrap() = Some(ErrorKind::InvalidRequest);
+    let notification = NotificationBuilder::new()
+        .with_title("Nightly build failed")
+        .with_platforms(vec![Platform::Linux])
+        .build()
+        .expect("notification should build");
+    let handle = manager.send(notification).await.expect("notification queued");
+
+    let mut transitions = handle.watch();
+    let mut last = None;
+    let watched = tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(transition) = transitions.next().await {
+            last = Some(transition.to_state);
+        }
+    });
+    watched.await.expect("watch ends at the final failure");
+    assert!(matches!(last, Some(NotificationState::Failed(_))));
+    assert!(matches!(handle.finished().await, NotificationState::Failed(_)));
+    assert!(matches!(
+        handle.delivered().await,
+        Err(NotificationError::DeliveryFailed { .. })
+    ));
+    assert!(handle.watch().next().await.is_none());
+    assert_eq!(*backend.failed_attempts.lock().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_deliveries_are_retried_with_backoff() {
+    let (manager, backend) = stub_manager();
+    *backend.failure.lock().unwrap() = Some(ErrorKind::ServiceUnavailable);
+    let notification = NotificationBuilder::new()
+        .with_title("Nightly build failed")
+        .with_platforms(vec![Platform::Linux])
+        .build()
+        .expect("notification should build");
+    let handle = manager.send(notification).await.expect("notification queued");
+
+    // Retryable failures are queued again until the policy runs out
+    let finished = tokio::time::timeout(Duration::from_secs(10), handle.finished())
+        .await
+        .expect("handle finishes once retries run out");
+    assert!(matches!(finished, NotificationState::Failed(_)));
+    let lifecycle = handle.lifecycle().await.unwrap();
+    assert_eq!(*backend.failed_attempts.lock().unwrap(), lifecycle.retry_policy.max_attempts as usize);
+    let retries: Vec<_> = lifecycle
+        .state_history
+        .iter()
+        .filter(|transition| matches!(transition.reason, TransitionReason::Retry))
+        .collect();
+    assert_eq!(retries.len(), lifecycle.retry_policy.max_attempts as usize - 1);
+    assert!(retries.iter().all(|transition| transition.to_state == NotificationState::Queued));
+
+    // A retry that succeeds delivers the notification
+    let (manager, backend) = stub_manager();
+    *backend.failure.lock().unwrap() = Some(ErrorKind::ServiceUnavailable);
+    let notification = NotificationBuilder::new()
+        .with_title("Nightly build failed")
+        .with_platforms(vec![Platform::Linux])
+        .build()
+        .expect("notification should build");
+    let handle = manager.send(notification).await.expect("notification queued");
+    while *backend.failed_attempts.lock().unwrap() == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    *backend.failure.lock().unwrap() = None;
+    let delivered = tokio::time::timeout(Duration::from_secs(10), handle.delivered())
+        .await
+        .expect("retry delivers the notification");
+    assert_eq!(delivered.expect("delivered on retry").len(), 1);
+    assert_eq!(*backend.failed_attempts.lock().unwrap(), 1);
+}
+
+fn stub_backends(backend: &StubBackend) -> PlatformManager {
+    let mut backends: HashMap<Platform, Box<dyn PlatformBackend>> = HashMap::new();
+    backends.insert(Platform::Linux, Box::new(backend.clone()));
+    PlatformManager::with_backends(backends)
+}
+
+#[test]
+fn test_blocking_manager_without_ambient_runtime() {
+    let backend = StubBackend::default();
+    let unavailable = NotificationManager::try_with_platform_manager(stub_backends(&backend));
+    assert!(matches!(unavailable, Err(NotificationError::ResourceError { .. })));
+
+    let manager = blocking::NotificationManager::with_platform_manager(stub_backends(&backend))
+        .expect("manager starts its own runtime");
+    let note = |title: &str| {
+        NotificationBuilder::new()
+            .with_title(title)
+            .with_platforms(vec![Platform::Linux])
+            .build()
+            .expect("notification should build")
+    };
+
+    let id = manager.send(note("Pre-commit hook finished")).expect("notification queued");
+    let receipts = manager
+        .wait_delivered(id, Duration::from_secs(5))
+        .expect("notification delivered");
+    assert_eq!(receipts[0].native_id, "stub-1");
+
+    let update = NotificationUpdate {
+        content_changes: HashMap::from([("body".to_string(), "2 files fixed".to_string())]),
+        ..NotificationUpdate::default()
+    };
+    manager.update(id, update.clone()).expect("notification updated");
+    manager.cancel(id).expect("notification cancelled");
+    assert_eq!(backend.cancelled.lock().unwrap().clone(), vec!["stub-1".to_string()]);
+    assert!(matches!(
+        manager.update(id, update),
+        Err(NotificationError::ValidationError { .. })
+    ));
+
+    // Dropping delivers what is still queued
+    manager.send(note("Lint passed")).expect("notification queued");
+    manager.send(note("Tests passed")).expect("notification queued");
+    drop(manager);
+    assert_eq!(backend.delivered.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_blocking_manager_refuses_async_context() {
+    let backend = StubBackend::default();
+    let result = blocking::NotificationManager::with_platform_manager(stub_backends(&backend));
+    assert!(matches!(result, Err(NotificationError::ResourceError { .. })));
+}